@@ -0,0 +1,89 @@
+// Mass properties of a closed mesh with uniform density
+// every triangle forms a tetrahedron with the origin, the signed integrals
+// over these tetrahedra add up to the integrals over the enclosed solid
+// https://doi.org/10.1080/2151237X.2006.10129220 (Explicit exact formulas for the 3-D tetrahedron inertia tensor)
+use nalgebra::{Matrix3, Vector3};
+
+use crate::model::{Mesh, MeshError};
+
+// inertia tensor about the center of mass
+// errors when the mesh is open or does not enclose a volume
+pub fn inertia_tensor(mesh: &Mesh, density: f64) -> Result<Matrix3<f64>, MeshError> {
+    // the integrals of an open surface depend on where the origin is
+    if !mesh.is_watertight() {
+        return Err(MeshError::NotClosed);
+    }
+
+    // covariance of the canonical tetrahedron (0, e1, e2, e3)
+    let canonical = Matrix3::new(2.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 2.0) / 120.0;
+
+    let mut volume = 0.0f64;
+    let mut moment = Vector3::zeros();
+    let mut covariance = Matrix3::zeros();
+
+    for triangle in mesh.triangles() {
+        let [a, b, c] = triangle.vertices.map(Vector3::<f64>::from);
+        let transform = Matrix3::from_columns(&[a, b, c]);
+        let determinant = transform.determinant();
+
+        volume += determinant / 6.0;
+        moment += (a + b + c) * (determinant / 24.0);
+        covariance += transform * canonical * transform.transpose() * determinant;
+    }
+
+    let diagonal = mesh.diagonal()? as f64;
+    if volume.abs() <= diagonal.powi(3) * 1e-9 {
+        return Err(MeshError::NotClosed);
+    }
+
+    // inward facing normals flip the sign of every integral
+    if volume < 0.0 {
+        volume = -volume;
+        moment = -moment;
+        covariance = -covariance;
+    }
+
+    // parallel axis theorem, move the covariance to the center of mass
+    let center = moment / volume;
+    let covariance = (covariance - center * center.transpose() * volume) * density;
+
+    Ok(Matrix3::identity() * covariance.trace() - covariance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, model::Vec3};
+
+    #[test]
+    fn unit_cube_matches_the_analytic_tensor() {
+        // m (a² + a²) / 12 on the diagonal and no products of inertia
+        let tensor = inertia_tensor(&fixtures::cube(), 1.0).unwrap();
+        let expected = Matrix3::identity() / 6.0;
+        assert!((tensor - expected).abs().max() < 1e-9, "{tensor}");
+    }
+
+    #[test]
+    fn density_scales_the_tensor() {
+        let tensor = inertia_tensor(&fixtures::cube(), 2.5).unwrap();
+        assert!((tensor[(0, 0)] - 2.5 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tensor_is_about_the_center_of_mass() {
+        let offset = fixtures::box_mesh(Vec3(10.0, -4.0, 3.0), Vec3(11.0, -3.0, 4.0));
+        let tensor = inertia_tensor(&offset, 1.0).unwrap();
+        assert!(
+            (tensor - Matrix3::identity() / 6.0).abs().max() < 1e-6,
+            "{tensor}"
+        );
+    }
+
+    #[test]
+    fn open_mesh_is_rejected() {
+        assert!(matches!(
+            inertia_tensor(&fixtures::open_cube(), 1.0),
+            Err(MeshError::NotClosed)
+        ));
+    }
+}
@@ -1,10 +1,48 @@
+pub mod inertia;
+pub mod orientation;
+pub mod projection;
+pub mod sampling;
+pub mod section;
+pub mod simplify;
+pub mod thickness;
 pub mod triangulation;
 
-use crate::model::{Face, Mesh, Triangle, Vec3};
+use crate::model::{Face, Group, GroupKind, Mesh, MeshError, Unit, Vec3};
 use core::f32;
 use rayon::prelude::*;
 
+pub use inertia::inertia_tensor;
+pub use orientation::{align_to_principal_axes, minimize_footprint, rotate};
+pub use projection::projected_area;
+pub use sampling::sample_surface;
+pub use section::cross_section_area;
+pub use thickness::{thin_wall_faces, wall_thickness_per_vertex};
+
 pub fn volume(mesh: &Mesh) -> f64 {
+    signed_volume(mesh).abs()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CheckedVolume {
+    pub volume: f64,
+    // false when the mesh has holes or non-manifold edges,
+    // the volume is then only an approximation
+    pub watertight: bool,
+}
+
+// same as `volume`, along with whether the value can be trusted
+pub fn volume_checked(mesh: &Mesh) -> CheckedVolume {
+    CheckedVolume {
+        volume: volume(mesh),
+        watertight: mesh.is_watertight(),
+    }
+}
+
+// positive when the faces wind counter-clockwise seen from outside,
+// negative for an inside-out mesh
+// the chunks summed in parallel are merged along with their compensations, so the
+// result is as accurate as a single Kahan sum over every triangle
+pub fn signed_volume(mesh: &Mesh) -> f64 {
     if mesh.faces.is_empty() {
         return 0.0;
     }
@@ -12,50 +50,123 @@ pub fn volume(mesh: &Mesh) -> f64 {
     const PARALLEL_THRESHOLD: usize = 1000;
     const CHUNK_SIZE: usize = 1000;
 
-    let total_volume: f64 = if mesh.faces.len() >= PARALLEL_THRESHOLD {
+    let total_volume = if mesh.faces.len() >= PARALLEL_THRESHOLD {
         mesh.faces
             .par_chunks(CHUNK_SIZE)
             .map(|chunk| kahan_sum_faces(chunk, &mesh.vertices))
-            .sum()
+            .reduce(KahanSum::default, KahanSum::merge)
     } else {
         kahan_sum_faces(&mesh.faces, &mesh.vertices)
     };
 
-    total_volume.abs()
+    total_volume.value()
+}
+
+// compensated (Kahan) sum, `compensation` holds the low order bits lost so far (negated)
+#[derive(Debug, Clone, Copy, Default)]
+struct KahanSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl KahanSum {
+    #[inline]
+    fn add(&mut self, value: f64) {
+        let y = value - self.compensation;
+        let t = self.sum + y;
+        self.compensation = (t - self.sum) - y;
+        self.sum = t;
+    }
+
+    // adds the other sum, including the bits it lost, instead of only its rounded total
+    fn merge(mut self, other: KahanSum) -> KahanSum {
+        self.add(other.sum);
+        self.add(-other.compensation);
+        self
+    }
+
+    fn value(self) -> f64 {
+        self.sum - self.compensation
+    }
 }
 
 #[inline]
-fn kahan_sum_faces(faces: &[Face], vertices: &[Vec3]) -> f64 {
-    let mut sum = 0.0f64;
-    let mut compensation = 0.0f64;
-
-    for face in faces {
-        let indices = &face.v;
-        let n = indices.len();
-        if n < 3 {
-            continue;
-        }
+fn kahan_sum_faces(faces: &[Face], vertices: &[Vec3]) -> KahanSum {
+    let mut sum = KahanSum::default();
+    for triangle in faces.iter().flat_map(|face| face.triangles(vertices)) {
+        sum.add(triangle.signed_volume());
+    }
+    sum
+}
 
-        let v0 = vertices[indices[0]];
-        for i in 1..(n - 1) {
-            let v1 = vertices[indices[i]];
-            let v2 = vertices[indices[i + 1]];
+// distribution of the triangle areas as (bin_low, bin_high, count), with `bins` bins of
+// equal width between the smallest and the largest area. the last bin includes its upper
+// bound. when every triangle has the same area a single bin holds all of them
+pub fn area_histogram(mesh: &Mesh, bins: usize) -> Vec<(f64, f64, usize)> {
+    let areas: Vec<f64> = mesh.triangles().map(|triangle| triangle.area()).collect();
+    if areas.is_empty() || bins == 0 {
+        return Vec::new();
+    }
+
+    let (min, max) = areas
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &area| {
+            (min.min(area), max.max(area))
+        });
+    if max <= min {
+        return vec![(min, max, areas.len())];
+    }
+
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for area in areas {
+        let bin = (((area - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
 
-            let volume = Triangle {
-                vertices: [v0, v1, v2],
-            }
-            .signed_volume();
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let low = min + width * i as f64;
+            let high = if i + 1 == bins { max } else { low + width };
+            (low, high, count)
+        })
+        .collect()
+}
+
+// scales the mesh to the new diagonal about its bounding box center
+pub fn scale(mesh: &mut Mesh, new_diagonal: f32) -> Result<(), MeshError> {
+    scale_with_pivot(mesh, new_diagonal, Pivot::Center)
+}
+
+// point that stays in place when scaling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    // center of the bounding box
+    Center,
+    // minimum corner of the bounding box, e.g., to keep a model resting on the build plate
+    Min,
+    // the origin of the coordinate system
+    Origin,
+}
 
-            let y = volume - compensation;
-            let t = sum + y;
-            compensation = (t - sum) - y;
-            sum = t;
+impl Pivot {
+    fn point(self, min_vertex: Vec3, max_vertex: Vec3) -> Vec3 {
+        match self {
+            Pivot::Center => Vec3(
+                (min_vertex.0 + max_vertex.0) / 2.0,
+                (min_vertex.1 + max_vertex.1) / 2.0,
+                (min_vertex.2 + max_vertex.2) / 2.0,
+            ),
+            Pivot::Min => min_vertex,
+            Pivot::Origin => Vec3(0.0, 0.0, 0.0),
         }
     }
-    sum
 }
 
-pub fn scale(mesh: &mut Mesh, new_diagonal: f32) -> anyhow::Result<()> {
+// same as `scale`, keeping the pivot in place instead of the center
+pub fn scale_with_pivot(mesh: &mut Mesh, new_diagonal: f32, pivot: Pivot) -> Result<(), MeshError> {
     let (min_vertex, max_vertex) = mesh.bounds()?;
 
     let dx = max_vertex.0 - min_vertex.0;
@@ -64,28 +175,561 @@ pub fn scale(mesh: &mut Mesh, new_diagonal: f32) -> anyhow::Result<()> {
 
     let current_diagonal = (dx * dx + dy * dy + dz * dz).sqrt();
     if current_diagonal == 0.0 {
-        return Err(anyhow::anyhow!("mesh has 0 dimensions"));
+        return Err(MeshError::ZeroDimensions);
+    }
+
+    scale_about(
+        mesh,
+        new_diagonal / current_diagonal,
+        pivot.point(min_vertex, max_vertex),
+    );
+
+    Ok(())
+}
+
+// uniformly scales the mesh down so its bounding box fits inside a box of the given size,
+// e.g., the build volume of a printer. the bounding box center stays in place
+// returns the scale factor used, 1.0 when the mesh already fits (it is never scaled up)
+pub fn fit_within(mesh: &mut Mesh, box_dims: Vec3) -> Result<f32, MeshError> {
+    if !(box_dims.0 > 0.0 && box_dims.1 > 0.0 && box_dims.2 > 0.0) {
+        return Err(MeshError::ZeroDimensions);
     }
 
-    let center_x = (min_vertex.0 + max_vertex.0) / 2.0;
-    let center_y = (min_vertex.1 + max_vertex.1) / 2.0;
-    let center_z = (min_vertex.2 + max_vertex.2) / 2.0;
+    let (min_vertex, max_vertex) = mesh.bounds()?;
+    let extents = max_vertex - min_vertex;
 
-    let scale_factor = new_diagonal / current_diagonal;
+    // flat axes fit any box
+    let factor = [
+        (extents.0, box_dims.0),
+        (extents.1, box_dims.1),
+        (extents.2, box_dims.2),
+    ]
+    .into_iter()
+    .filter(|&(extent, _)| extent > 0.0)
+    .map(|(extent, size)| size / extent)
+    .fold(1.0f32, f32::min);
+
+    if factor < 1.0 {
+        scale_about(mesh, factor, Pivot::Center.point(min_vertex, max_vertex));
+    }
 
+    Ok(factor)
+}
+
+// uniformly scales the mesh by the factor, the pivot stays in place
+pub fn scale_about(mesh: &mut Mesh, scale_factor: f32, pivot: Vec3) {
     mesh.vertices.par_iter_mut().for_each(|vertex| {
-        vertex.0 = (vertex.0 - center_x) * scale_factor + center_x;
-        vertex.1 = (vertex.1 - center_y) * scale_factor + center_y;
-        vertex.2 = (vertex.2 - center_z) * scale_factor + center_z;
+        vertex.0 = (vertex.0 - pivot.0) * scale_factor + pivot.0;
+        vertex.1 = (vertex.1 - pivot.1) * scale_factor + pivot.1;
+        vertex.2 = (vertex.2 - pivot.2) * scale_factor + pivot.2;
     });
+    mesh.invalidate_face_areas();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+// mirrors the mesh across the plane perpendicular to the axis through the origin
+// mirroring flips the winding, so faces are reversed to keep normals facing outwards
+pub fn mirror(mesh: &mut Mesh, plane: Axis) {
+    let negate = |v: &mut Vec3| match plane {
+        Axis::X => v.0 = -v.0,
+        Axis::Y => v.1 = -v.1,
+        Axis::Z => v.2 = -v.2,
+    };
+
+    mesh.vertices.par_iter_mut().for_each(negate);
+    mesh.normals.par_iter_mut().for_each(negate);
+    mesh.faces.par_iter_mut().for_each(Face::reverse);
+    mesh.invalidate_face_areas();
+}
+
+// changes the unit of the coordinates, multiplying them by the ratio of the units
+// (e.g., 25.4 from inches to millimeters). an unknown unit is taken as millimeters
+// returns the factor the coordinates were multiplied by
+pub fn convert_units(mesh: &mut Mesh, to: Unit) -> f32 {
+    let from = mesh.unit.unwrap_or(Unit::Mm);
+    let factor = from.millimeters() / to.millimeters();
+
+    scale_about(mesh, factor, Vec3(0.0, 0.0, 0.0));
+    mesh.unit = Some(to);
+
+    factor
+}
+
+// moves every vertex by the offset
+pub fn translate(mesh: &mut Mesh, offset: Vec3) {
+    mesh.vertices
+        .par_iter_mut()
+        .for_each(|vertex| *vertex += offset);
+    mesh.invalidate_face_areas();
+}
+
+// moves the mesh so its bounding box center sits at the origin
+pub fn center(mesh: &mut Mesh) -> Result<(), MeshError> {
+    let (min_vertex, max_vertex) = mesh.bounds()?;
+
+    let offset = Vec3(
+        -(min_vertex.0 + max_vertex.0) / 2.0,
+        -(min_vertex.1 + max_vertex.1) / 2.0,
+        -(min_vertex.2 + max_vertex.2) / 2.0,
+    );
+    translate(mesh, offset);
+
+    Ok(())
+}
+
+// centers the mesh on X/Y and moves it so it rests on Z = 0,
+// which is where slicers expect the model to be
+pub fn place_on_ground(mesh: &mut Mesh) -> Result<(), MeshError> {
+    let (min_vertex, max_vertex) = mesh.bounds()?;
+
+    let offset = Vec3(
+        -(min_vertex.0 + max_vertex.0) / 2.0,
+        -(min_vertex.1 + max_vertex.1) / 2.0,
+        -min_vertex.2,
+    );
+    translate(mesh, offset);
 
     Ok(())
 }
 
-pub fn diagonal(mesh: &Mesh) -> anyhow::Result<f32, anyhow::Error> {
+// the common measurements of a mesh, see `measure`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Measurements {
+    // corners of the axis aligned bounding box
+    pub min: Vec3,
+    pub max: Vec3,
+    // center of the bounding box
+    pub center: Vec3,
+    // extents of the bounding box along X, Y and Z
+    pub dimensions: Vec3,
+    pub diagonal: f32,
+    // negative for an inside-out mesh, `volume` is its absolute value
+    pub signed_volume: f64,
+    pub volume: f64,
+    pub area: f64,
+    pub triangles: usize,
+}
+
+// bounds, dimensions, diagonal, volume, area and triangle count in one call
+// fails like `diagonal` for an empty mesh or one without dimensions
+pub fn measure(mesh: &Mesh) -> Result<Measurements, MeshError> {
+    let (min, max) = mesh.bounds()?;
+    let dimensions = max - min;
+    let diagonal = dimensions.length();
+    if diagonal == 0.0 {
+        return Err(MeshError::ZeroDimensions);
+    }
+    let signed_volume = signed_volume(mesh);
+
+    Ok(Measurements {
+        min,
+        max,
+        center: (min + max) * 0.5,
+        dimensions,
+        diagonal,
+        signed_volume,
+        volume: signed_volume.abs(),
+        area: mesh.face_areas().iter().fold(0.0, |sum, area| sum + area),
+        triangles: mesh.triangle_count(),
+    })
+}
+
+// extents of the bounding box along X, Y and Z
+pub fn dimensions(mesh: &Mesh) -> Result<Vec3, MeshError> {
+    let (min_vertex, max_vertex) = mesh.bounds()?;
+
+    Ok(max_vertex - min_vertex)
+}
+
+// the axis-aligned bounding box as a closed mesh of 12 triangles with outward facing normals
+pub fn bounding_box_mesh(mesh: &Mesh) -> Result<Mesh, MeshError> {
+    let (min, max) = mesh.bounds()?;
+
+    let mut bounding_box = Mesh::new();
+    // corner i takes the max coordinate along X for bit 0, Y for bit 1 and Z for bit 2
+    bounding_box.vertices = (0..8)
+        .map(|i| {
+            Vec3(
+                if i & 1 == 0 { min.0 } else { max.0 },
+                if i & 2 == 0 { min.1 } else { max.1 },
+                if i & 4 == 0 { min.2 } else { max.2 },
+            )
+        })
+        .collect();
+    bounding_box.faces = [
+        [0, 2, 3],
+        [0, 3, 1],
+        [4, 5, 7],
+        [4, 7, 6],
+        [0, 1, 5],
+        [0, 5, 4],
+        [2, 6, 7],
+        [2, 7, 3],
+        [0, 4, 6],
+        [0, 6, 2],
+        [1, 3, 7],
+        [1, 7, 5],
+    ]
+    .into_iter()
+    .map(|v| Face {
+        v: v.into_iter().collect(),
+        ..Face::default()
+    })
+    .collect();
+    bounding_box.groups.push(Group {
+        name: String::from("bounding_box"),
+        kind: GroupKind::Object,
+        material: None,
+        face_range: 0..bounding_box.faces.len(),
+    });
+
+    Ok(bounding_box)
+}
+
+// paints every connected component of the mesh a distinct color, stored in `face_colors`
+// the colors only depend on the order of the components, so runs are reproducible
+// returns the number of components
+pub fn color_components(mesh: &mut Mesh) -> usize {
+    let (labels, count) = mesh.face_components();
+    mesh.face_colors = labels.into_iter().map(component_color).collect();
+
+    count
+}
+
+// hues spaced by the golden angle, so neighboring labels get clearly different colors
+fn component_color(label: usize) -> [u8; 3] {
+    const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+    hsv_color((label as f64 * GOLDEN_RATIO_CONJUGATE).fract(), 0.65, 0.95)
+}
+
+// RGB of the HSV color, with the hue in 0..1 (a full turn)
+pub(crate) fn hsv_color(hue: f64, saturation: f64, value: f64) -> [u8; 3] {
+    let hue = hue.rem_euclid(1.0) * 6.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let offset = value - chroma;
+
+    [r, g, b].map(|c| ((c + offset) * 255.0).round() as u8)
+}
+
+// lowest and highest Z of the mesh, e.g. for the print height
+pub fn z_range(mesh: &Mesh) -> Result<(f32, f32), MeshError> {
+    let (min_vertex, max_vertex) = mesh.bounds()?;
+
+    Ok((min_vertex.2, max_vertex.2))
+}
+
+pub fn diagonal(mesh: &Mesh) -> Result<f32, MeshError> {
     mesh.diagonal()
 }
 
 pub fn triangle_count(mesh: &Mesh) -> usize {
     mesh.triangle_count()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn mirroring_keeps_the_volume_positive() {
+        let mut mesh = fixtures::obj(fixtures::TETRAHEDRON);
+        let before = signed_volume(&mesh);
+        assert!(before > 0.0);
+
+        mirror(&mut mesh, Axis::X);
+        assert!((signed_volume(&mesh) - before).abs() < 1e-9);
+        let (min, max) = mesh.bounds().unwrap();
+        assert_eq!((min.0, max.0), (-1.0, 0.0));
+    }
+
+    fn offset_box() -> Mesh {
+        fixtures::box_mesh(Vec3(3.0, -7.0, 2.0), Vec3(5.0, -1.0, 6.0))
+    }
+
+    #[test]
+    fn center_makes_the_bounds_symmetric() {
+        let mut mesh = offset_box();
+        center(&mut mesh).unwrap();
+
+        let (min, max) = mesh.bounds().unwrap();
+        assert_eq!(min, -max);
+        assert_eq!(max, Vec3(1.0, 3.0, 2.0));
+    }
+
+    #[test]
+    fn place_on_ground_centers_x_and_y_and_rests_on_z() {
+        let mut mesh = offset_box();
+        place_on_ground(&mut mesh).unwrap();
+
+        let (min, max) = mesh.bounds().unwrap();
+        assert_eq!((min.0, min.1), (-max.0, -max.1));
+        assert_eq!((min.2, max.2), (0.0, 4.0));
+    }
+
+    #[test]
+    fn centering_an_empty_mesh_fails() {
+        assert!(matches!(center(&mut Mesh::new()), Err(MeshError::Empty)));
+    }
+
+    #[test]
+    fn dimensions_are_the_extents_of_the_box() {
+        let mesh = fixtures::box_mesh(Vec3(-1.0, 2.0, 0.5), Vec3(1.0, 5.0, 4.5));
+        assert_eq!(dimensions(&mesh).unwrap(), Vec3(2.0, 3.0, 4.0));
+        assert!(matches!(dimensions(&Mesh::new()), Err(MeshError::Empty)));
+    }
+
+    #[test]
+    fn fitting_scales_about_the_center() {
+        let mut mesh = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(10.0, 10.0, 10.0));
+        let factor = fit_within(&mut mesh, Vec3(5.0, 20.0, 20.0)).unwrap();
+
+        assert_eq!(factor, 0.5);
+        assert_eq!(
+            mesh.bounds().unwrap(),
+            (Vec3(2.5, 2.5, 2.5), Vec3(7.5, 7.5, 7.5))
+        );
+    }
+
+    #[test]
+    fn fitting_mesh_is_left_alone() {
+        let mut mesh = offset_box();
+        let before = mesh.vertices.clone();
+
+        assert_eq!(fit_within(&mut mesh, Vec3(10.0, 10.0, 10.0)).unwrap(), 1.0);
+        assert_eq!(mesh.vertices, before);
+        assert!(matches!(
+            fit_within(&mut mesh, Vec3(0.0, 10.0, 10.0)),
+            Err(MeshError::ZeroDimensions)
+        ));
+    }
+
+    #[test]
+    fn histogram_bins_span_the_area_range() {
+        // three triangles of area 0.5 and one of area 2
+        let mesh = fixtures::obj(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 2 0 0\nv 0 2 0\n\
+            f 1 2 3\nf 1 3 2\nf 2 3 1\nf 1 4 5\n",
+        );
+
+        let histogram = area_histogram(&mesh, 4);
+        assert_eq!(
+            histogram,
+            [
+                (0.5, 0.875, 3),
+                (0.875, 1.25, 0),
+                (1.25, 1.625, 0),
+                (1.625, 2.0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_areas_fill_a_single_bin() {
+        assert_eq!(area_histogram(&fixtures::cube(), 10), [(0.5, 0.5, 12)]);
+        assert!(area_histogram(&Mesh::new(), 10).is_empty());
+    }
+
+    // boxes along X, enough faces for the parallel paths
+    fn box_row(count: usize) -> Mesh {
+        let mut row = Mesh::new();
+        for i in 0..count {
+            let x = i as f32 * 1.5;
+            row.merge(&fixtures::box_mesh(
+                Vec3(x, 0.0, 0.0),
+                Vec3(x + 1.0, 0.5, 0.25),
+            ));
+        }
+        row
+    }
+
+    #[test]
+    fn parallel_volume_matches_a_serial_sum() {
+        let row = box_row(500);
+        assert!(row.faces.len() >= 1000);
+
+        let serial = kahan_sum_faces(&row.faces, &row.vertices).value();
+        assert!((signed_volume(&row) - serial).abs() < 1e-9);
+        assert!((serial - 500.0 * 0.125).abs() < 1e-6);
+    }
+
+    #[test]
+    fn inverted_cube_has_a_negative_signed_volume() {
+        let mut cube = fixtures::cube();
+        cube.flip();
+
+        assert!((signed_volume(&cube) + 1.0).abs() < 1e-9);
+        assert!((volume(&cube) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn open_cube_volume_is_not_trusted() {
+        let closed = volume_checked(&fixtures::cube());
+        assert!(closed.watertight);
+        assert!((closed.volume - 1.0).abs() < 1e-9);
+
+        let open = volume_checked(&fixtures::open_cube());
+        assert!(!open.watertight);
+        assert_eq!(open.volume, volume(&fixtures::open_cube()));
+    }
+
+    #[test]
+    fn z_range_is_the_vertical_extent() {
+        let mesh = fixtures::box_mesh(Vec3(0.0, 0.0, -2.0), Vec3(1.0, 1.0, 18.0));
+        assert_eq!(z_range(&mesh).unwrap(), (-2.0, 18.0));
+    }
+
+    #[test]
+    fn scaling_about_the_minimum_keeps_the_minimum_corner() {
+        let mut mesh = offset_box();
+        let (min, max) = mesh.bounds().unwrap();
+        let diagonal = mesh.diagonal().unwrap();
+
+        scale_with_pivot(&mut mesh, diagonal * 2.0, Pivot::Min).unwrap();
+        let (scaled_min, scaled_max) = mesh.bounds().unwrap();
+        assert_eq!(scaled_min, min);
+        let expected = min + (max - min) * 2.0;
+        assert!((scaled_max - expected).length() < 1e-4, "{:?}", scaled_max);
+    }
+
+    #[test]
+    fn scaling_about_the_origin_scales_the_coordinates() {
+        let mut mesh = offset_box();
+        let (min, max) = mesh.bounds().unwrap();
+        let diagonal = mesh.diagonal().unwrap();
+
+        scale_with_pivot(&mut mesh, diagonal * 0.5, Pivot::Origin).unwrap();
+        let (scaled_min, scaled_max) = mesh.bounds().unwrap();
+        assert!((scaled_min - min * 0.5).length() < 1e-5, "{:?}", scaled_min);
+        assert!((scaled_max - max * 0.5).length() < 1e-5, "{:?}", scaled_max);
+    }
+
+    #[test]
+    fn bounding_box_mesh_is_a_closed_box_of_the_extents() {
+        let sphere = fixtures::uv_sphere(1.0, 8, 16);
+        let (min, max) = sphere.bounds().unwrap();
+        let extents = max - min;
+
+        let bounding_box = bounding_box_mesh(&sphere).unwrap();
+        assert_eq!(bounding_box.vertices.len(), 8);
+        assert_eq!(bounding_box.triangle_count(), 12);
+        assert!(bounding_box.is_watertight());
+        assert_eq!(bounding_box.bounds().unwrap(), (min, max));
+        let expected = extents.0 as f64 * extents.1 as f64 * extents.2 as f64;
+        assert!((signed_volume(&bounding_box) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn two_components_get_two_distinct_colors() {
+        let mut mesh = fixtures::cube();
+        mesh.merge(&offset_box());
+        assert_eq!(color_components(&mut mesh), 2);
+
+        // the 6 quads of the cube, then the 12 triangles of the box
+        assert_eq!(mesh.face_colors.len(), 18);
+        let (cube, other) = mesh.face_colors.split_at(6);
+        assert!(cube.iter().all(|&color| color == cube[0]));
+        assert!(other.iter().all(|&color| color == other[0]));
+        assert_ne!(cube[0], other[0]);
+
+        // the same colors on every run
+        let mut again = mesh.clone();
+        color_components(&mut again);
+        assert_eq!(again.face_colors, mesh.face_colors);
+    }
+
+    #[test]
+    fn converting_inches_to_millimeters_scales_by_25_4() {
+        let mut mesh = fixtures::cube();
+        mesh.unit = Some(Unit::In);
+        let diagonal = mesh.diagonal().unwrap();
+
+        assert_eq!(convert_units(&mut mesh, Unit::Mm), 25.4);
+        assert_eq!(mesh.unit, Some(Unit::Mm));
+        assert!((mesh.diagonal().unwrap() - diagonal * 25.4).abs() < 1e-4);
+        // back again
+        assert_eq!(convert_units(&mut mesh, Unit::In), 1.0 / 25.4);
+        assert!((mesh.diagonal().unwrap() - diagonal).abs() < 1e-5);
+    }
+
+    #[test]
+    fn merged_partial_sums_match_a_single_pass() {
+        // corner triangles of signed volume x³ / 6: ±2^57 / 6 (about 2.4e16) and 1 / 6
+        let (big, small) = (524_288.0, 1.0);
+        let mut mesh = Mesh::new();
+        mesh.vertices = vec![
+            Vec3(big, 0.0, 0.0),
+            Vec3(0.0, big, 0.0),
+            Vec3(0.0, 0.0, big),
+            Vec3(small, 0.0, 0.0),
+            Vec3(0.0, small, 0.0),
+            Vec3(0.0, 0.0, small),
+        ];
+        let face = |v: [usize; 3]| Face {
+            v: v.into_iter().collect(),
+            ..Face::default()
+        };
+        for _ in 0..32 {
+            mesh.faces.push(face([0, 1, 2]));
+            mesh.faces.extend((0..3).map(|_| face([3, 4, 5])));
+            mesh.faces.push(face([0, 2, 1]));
+            mesh.faces.extend((0..3).map(|_| face([3, 4, 5])));
+        }
+
+        let single = kahan_sum_faces(&mesh.faces, &mesh.vertices).value();
+        // chunks splitting the large volumes apart round the small ones away,
+        // adding only their totals drifts while merging keeps the lost bits
+        for size in [2, 3, 4, 5, 6, 7] {
+            let chunks: Vec<KahanSum> = mesh
+                .faces
+                .chunks(size)
+                .map(|faces| kahan_sum_faces(faces, &mesh.vertices))
+                .collect();
+            let naive: f64 = chunks.iter().map(|chunk| chunk.value()).sum();
+            let merged = chunks
+                .into_iter()
+                .fold(KahanSum::default(), KahanSum::merge)
+                .value();
+
+            assert!((naive - single).abs() > 1e-9, "{} vs {}", naive, single);
+            assert!((merged - single).abs() < 1e-9, "{} vs {}", merged, single);
+        }
+        assert!((signed_volume(&mesh) - single).abs() < 1e-9);
+    }
+
+    #[test]
+    fn measurements_match_the_individual_functions() {
+        let mesh = offset_box();
+        let measurements = measure(&mesh).unwrap();
+
+        assert_eq!((measurements.min, measurements.max), mesh.bounds().unwrap());
+        assert_eq!(measurements.center, Vec3(4.0, -4.0, 4.0));
+        assert_eq!(measurements.dimensions, dimensions(&mesh).unwrap());
+        assert_eq!(measurements.diagonal, diagonal(&mesh).unwrap());
+        assert_eq!(measurements.signed_volume, signed_volume(&mesh));
+        assert_eq!(measurements.volume, volume(&mesh));
+        // 2 x 6 x 4
+        assert!((measurements.volume - 48.0).abs() < 1e-9);
+        let area: f64 = mesh.triangles().map(|triangle| triangle.area()).sum();
+        assert!((measurements.area - area).abs() < 1e-9);
+        assert!((measurements.area - 88.0).abs() < 1e-9);
+        assert_eq!(measurements.triangles, 12);
+
+        assert!(matches!(measure(&Mesh::new()), Err(MeshError::Empty)));
+    }
+}
@@ -0,0 +1,231 @@
+// alignment of a mesh with its principal axes
+// the axes are the eigenvectors of the covariance of the surface, every triangle
+// weighted by its area so the result does not depend on how finely it is tessellated
+use std::f32::consts::PI;
+
+use nalgebra::{Matrix3, Rotation3, SymmetricEigen, Vector3};
+use rayon::prelude::*;
+
+use crate::{
+    calculate::{Axis, projection::projected_area_with_resolution},
+    model::{Mesh, MeshError, Vec3},
+};
+
+// angles tried about every axis by `minimize_footprint`, 12 steps of 15° over a half turn
+// (a half turn more projects the same silhouette, mirrored)
+const FOOTPRINT_STEPS: usize = 12;
+// grid resolution of the silhouettes compared by `minimize_footprint`,
+// coarse so the search stays fast, the rotations are only 15° apart anyway
+const FOOTPRINT_RESOLUTION: usize = 128;
+
+// rotates the mesh about its centroid so the axis of largest spread lies along X,
+// the second along Y and the smallest along Z.
+// every axis points to the side the mesh reaches farther out, so the result is
+// deterministic. when two spreads are equal (e.g., a cube) their axes are arbitrary
+pub fn align_to_principal_axes(mesh: &mut Mesh) -> Result<(), MeshError> {
+    let (centroid, covariance) = surface_covariance(mesh)?;
+
+    let eigen = SymmetricEigen::new(covariance);
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+
+    let vertices: Vec<Vector3<f64>> = mesh
+        .vertices
+        .iter()
+        .map(|&v| Vector3::from(v) - centroid)
+        .collect();
+    // reach of the mesh on both sides of the centroid along the axis
+    let oriented = |axis: Vector3<f64>| {
+        let (min, max) = vertices.iter().fold((0.0f64, 0.0f64), |(min, max), v| {
+            let t = v.dot(&axis);
+            (min.min(t), max.max(t))
+        });
+        if -min > max { -axis } else { axis }
+    };
+
+    let x = oriented(eigen.eigenvectors.column(order[0]).into_owned());
+    let y = oriented(eigen.eigenvectors.column(order[1]).into_owned());
+    // a right handed frame, so the mesh is rotated and never mirrored
+    let z = x.cross(&y);
+    let rotation = Matrix3::from_rows(&[x.transpose(), y.transpose(), z.transpose()]);
+
+    let to_vec3 = |v: Vector3<f64>| Vec3(v.x as f32, v.y as f32, v.z as f32);
+    mesh.vertices
+        .par_iter_mut()
+        .zip(vertices.par_iter())
+        .for_each(|(vertex, &offset)| *vertex = to_vec3(rotation * offset + centroid));
+    mesh.normals
+        .par_iter_mut()
+        .for_each(|normal| *normal = to_vec3(rotation * Vector3::from(*normal)));
+    mesh.invalidate_face_areas();
+
+    Ok(())
+}
+
+// rotation by `angle_x` about X followed by `angle_y` about Y, in radians
+fn rotation(angle_x: f32, angle_y: f32) -> Matrix3<f64> {
+    let about_x = Rotation3::from_axis_angle(&Vector3::x_axis(), angle_x as f64);
+    let about_y = Rotation3::from_axis_angle(&Vector3::y_axis(), angle_y as f64);
+    (about_y * about_x).into_inner()
+}
+
+// rotates the mesh about the center of its bounding box, by `angle_x` about X
+// and then by `angle_y` about Y (in radians)
+pub fn rotate(mesh: &mut Mesh, angle_x: f32, angle_y: f32) -> Result<(), MeshError> {
+    let (min, max) = mesh.bounds()?;
+    let center = Vector3::from(Vec3(
+        (min.0 + max.0) / 2.0,
+        (min.1 + max.1) / 2.0,
+        (min.2 + max.2) / 2.0,
+    ));
+    let rotation = rotation(angle_x, angle_y);
+
+    let to_vec3 = |v: Vector3<f64>| Vec3(v.x as f32, v.y as f32, v.z as f32);
+    mesh.vertices.par_iter_mut().for_each(|vertex| {
+        *vertex = to_vec3(rotation * (Vector3::from(*vertex) - center) + center);
+    });
+    mesh.normals
+        .par_iter_mut()
+        .for_each(|normal| *normal = to_vec3(rotation * Vector3::from(*normal)));
+    mesh.invalidate_face_areas();
+
+    Ok(())
+}
+
+// angles (about X, then Y, in radians) of the rotation that gives the smallest
+// silhouette on the print bed (the XY plane), e.g., standing a plate on its edge.
+// the criterion is the bed area only, so long parts end up standing on their smallest
+// side rather than lying flat (`align_to_principal_axes` lays them down instead).
+// only a coarse grid of rotations is tried, so the result is an approximation.
+// on ties the smaller rotation wins, so (0, 0) when the current orientation is as good
+pub fn minimize_footprint(mesh: &Mesh) -> (f32, f32) {
+    let step = PI / FOOTPRINT_STEPS as f32;
+    let angles: Vec<(f32, f32)> = (0..FOOTPRINT_STEPS * FOOTPRINT_STEPS)
+        .map(|i| {
+            let (x, y) = (i / FOOTPRINT_STEPS, i % FOOTPRINT_STEPS);
+            (x as f32 * step, y as f32 * step)
+        })
+        .collect();
+
+    let areas: Vec<f64> = angles
+        .par_iter()
+        .map(|&(angle_x, angle_y)| {
+            let rotation = rotation(angle_x, angle_y);
+            let mut rotated = Mesh::new();
+            rotated.vertices = mesh
+                .vertices
+                .iter()
+                .map(|&v| {
+                    let v = rotation * Vector3::from(v);
+                    Vec3(v.x as f32, v.y as f32, v.z as f32)
+                })
+                .collect();
+            rotated.faces = mesh.faces.clone();
+
+            projected_area_with_resolution(&rotated, Axis::Z, FOOTPRINT_RESOLUTION)
+        })
+        .collect();
+
+    // rasterizing makes the areas of equal footprints differ slightly,
+    // a rotation has to be clearly better to replace a smaller one
+    let mut best = 0;
+    for (i, &area) in areas.iter().enumerate() {
+        if area < areas[best] * 0.99 {
+            best = i;
+        }
+    }
+
+    angles[best]
+}
+
+// area weighted centroid and covariance of the surface
+fn surface_covariance(mesh: &Mesh) -> Result<(Vector3<f64>, Matrix3<f64>), MeshError> {
+    let mut area = 0.0f64;
+    let mut moment = Vector3::zeros();
+    let mut second_moment = Matrix3::zeros();
+
+    for triangle in mesh.triangles() {
+        let [a, b, c] = triangle.vertices.map(Vector3::<f64>::from);
+        let triangle_area = (b - a).cross(&(c - a)).norm() / 2.0;
+        let sum = a + b + c;
+
+        area += triangle_area;
+        moment += sum * (triangle_area / 3.0);
+        // integral of x xᵀ over the triangle
+        second_moment +=
+            (a * a.transpose() + b * b.transpose() + c * c.transpose() + sum * sum.transpose())
+                * (triangle_area / 12.0);
+    }
+
+    if area == 0.0 {
+        return Err(if mesh.faces.is_empty() {
+            MeshError::Empty
+        } else {
+            MeshError::ZeroDimensions
+        });
+    }
+
+    let centroid = moment / area;
+    Ok((
+        centroid,
+        second_moment / area - centroid * centroid.transpose(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate, fixtures};
+
+    fn assert_extents(mesh: &Mesh, expected: Vec3, tolerance: f32) {
+        let extents = calculate::dimensions(mesh).unwrap();
+        assert!((extents - expected).length() < tolerance, "{extents:?}");
+    }
+
+    #[test]
+    fn rotated_box_is_aligned_with_the_world_axes() {
+        // longest along Z, shortest along X
+        let mut mesh = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 2.0, 4.0));
+        rotate(&mut mesh, 0.3, 0.5).unwrap();
+        let volume = calculate::signed_volume(&mesh);
+
+        align_to_principal_axes(&mut mesh).unwrap();
+        // the bounding box hugs the box again, with the axes sorted by length
+        assert_extents(&mesh, Vec3(4.0, 2.0, 1.0), 1e-4);
+        // rotated, not mirrored
+        assert!((calculate::signed_volume(&mesh) - volume).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rotating_about_the_center_keeps_it_in_place() {
+        let mut mesh = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(2.0, 2.0, 4.0));
+        rotate(&mut mesh, PI / 2.0, 0.0).unwrap();
+
+        assert_extents(&mesh, Vec3(2.0, 4.0, 2.0), 1e-5);
+        let (min, max) = mesh.bounds().unwrap();
+        assert!(((min + max) * 0.5 - Vec3(1.0, 1.0, 2.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn zero_area_mesh_cannot_be_aligned() {
+        let mut mesh = fixtures::obj("v 0 0 0\nv 1 0 0\nv 2 0 0\nf 1 2 3\n");
+        assert!(matches!(
+            align_to_principal_axes(&mut mesh),
+            Err(MeshError::ZeroDimensions)
+        ));
+    }
+
+    #[test]
+    fn long_box_stands_on_its_smallest_side() {
+        // lying along X, 10 long
+        let mut mesh = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(10.0, 1.0, 1.0));
+
+        let (angle_x, angle_y) = minimize_footprint(&mesh);
+        rotate(&mut mesh, angle_x, angle_y).unwrap();
+        assert_extents(&mesh, Vec3(1.0, 1.0, 10.0), 1e-4);
+
+        // aligning lays it flat again
+        align_to_principal_axes(&mut mesh).unwrap();
+        assert_extents(&mesh, Vec3(10.0, 1.0, 1.0), 1e-4);
+    }
+}
@@ -0,0 +1,127 @@
+// Projected (silhouette) area
+// triangles are projected onto the plane perpendicular to the axis and rasterized
+// into a shared grid, so overlapping triangles are only counted once
+use crate::{
+    calculate::Axis,
+    model::{Mesh, Vec2, Vec3},
+};
+
+// number of grid cells along the longer side of the projected bounding box
+// the area is accurate to roughly one cell along the silhouette outline
+pub const PROJECTED_AREA_RESOLUTION: usize = 1024;
+
+// area of the union of all triangles projected along the axis
+// every grid cell whose center is covered by at least one triangle counts fully
+pub fn projected_area(mesh: &Mesh, axis: Axis) -> f64 {
+    projected_area_with_resolution(mesh, axis, PROJECTED_AREA_RESOLUTION)
+}
+
+// same as `projected_area` with `resolution` cells along the longer side,
+// coarser grids are faster but less accurate
+pub fn projected_area_with_resolution(mesh: &Mesh, axis: Axis, resolution: usize) -> f64 {
+    let project = |v: Vec3| match axis {
+        Axis::X => Vec2(v.1, v.2),
+        Axis::Y => Vec2(v.0, v.2),
+        Axis::Z => Vec2(v.0, v.1),
+    };
+
+    let triangles: Vec<[Vec2; 3]> = mesh
+        .triangles()
+        .map(|triangle| triangle.vertices.map(project))
+        .collect();
+    if triangles.is_empty() {
+        return 0.0;
+    }
+
+    let (mut min, mut max) = (
+        Vec2(f32::INFINITY, f32::INFINITY),
+        Vec2(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    );
+    for point in triangles.iter().flatten() {
+        min = Vec2(min.0.min(point.0), min.1.min(point.1));
+        max = Vec2(max.0.max(point.0), max.1.max(point.1));
+    }
+
+    let (width, height) = ((max.0 - min.0) as f64, (max.1 - min.1) as f64);
+    if width <= 0.0 || height <= 0.0 {
+        return 0.0;
+    }
+
+    let cell = width.max(height) / resolution.max(1) as f64;
+    let columns = ((width / cell).ceil() as usize).max(1);
+    let rows = ((height / cell).ceil() as usize).max(1);
+    let mut covered = vec![false; columns * rows];
+
+    for triangle in &triangles {
+        let [a, b, c] = triangle.map(|p| ((p.0 - min.0) as f64, (p.1 - min.1) as f64));
+
+        let area = edge(a, b, c);
+        if area == 0.0 {
+            // seen edge on, covers nothing
+            continue;
+        }
+
+        // cells whose centers can lie inside the triangle's bounding box
+        let cell_range = |lo: f64, hi: f64, count: usize| {
+            let first = ((lo / cell - 0.5).ceil().max(0.0)) as usize;
+            let last = ((hi / cell - 0.5).floor() as isize).min(count as isize - 1);
+            first..(last + 1).max(0) as usize
+        };
+        let xs = cell_range(a.0.min(b.0).min(c.0), a.0.max(b.0).max(c.0), columns);
+        let ys = cell_range(a.1.min(b.1).min(c.1), a.1.max(b.1).max(c.1), rows);
+
+        for y in ys {
+            for x in xs.clone() {
+                let p = ((x as f64 + 0.5) * cell, (y as f64 + 0.5) * cell);
+
+                // p is inside when all edge functions share the sign of the triangle's area
+                let w = [edge(a, b, p), edge(b, c, p), edge(c, a, p)];
+                if w.iter().all(|&w| w * area >= 0.0) {
+                    covered[y * columns + x] = true;
+                }
+            }
+        }
+    }
+
+    covered.iter().filter(|&&c| c).count() as f64 * cell * cell
+}
+
+// twice the signed area of the triangle (a, b, p)
+#[inline]
+fn edge(a: (f64, f64), b: (f64, f64), p: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate, fixtures};
+
+    const SQUARE: &str = "v 0 0 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\nf 1 2 3 4\n";
+
+    #[test]
+    fn stacked_squares_count_once() {
+        let square = fixtures::obj(SQUARE);
+        let mut stacked = square.clone();
+        let mut lifted = square.clone();
+        calculate::translate(&mut lifted, Vec3(0.0, 0.0, 1.0));
+        stacked.merge(&lifted);
+
+        assert!((projected_area(&square, Axis::Z) - 4.0).abs() < 1e-6);
+        assert!((projected_area(&stacked, Axis::Z) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn square_seen_edge_on_has_no_area() {
+        let square = fixtures::obj(SQUARE);
+        assert_eq!(projected_area(&square, Axis::X), 0.0);
+    }
+
+    #[test]
+    fn cube_footprint_is_one_face() {
+        let cube = fixtures::cube();
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert!((projected_area(&cube, axis) - 1.0).abs() < 1e-6);
+        }
+    }
+}
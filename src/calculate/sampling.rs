@@ -0,0 +1,108 @@
+// random points spread uniformly over the surface
+// triangles are picked with a probability proportional to their area, then a point
+// is picked uniformly inside the triangle
+use crate::model::{Mesh, Vec3};
+
+// `n` points on the surface, the same seed always gives the same points
+// empty when the mesh has no area
+pub fn sample_surface(mesh: &Mesh, n: usize, seed: u64) -> Vec<Vec3> {
+    let triangles: Vec<_> = mesh.triangles().collect();
+
+    // cumulative area, cdf[i] is the area of the triangles 0..=i
+    let mut total = 0.0f64;
+    let cdf: Vec<f64> = triangles
+        .iter()
+        .map(|triangle| {
+            total += triangle.area();
+            total
+        })
+        .collect();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rng = SplitMix64(seed);
+    (0..n)
+        .map(|_| {
+            let target = rng.next_f64() * total;
+            // first triangle whose cumulative area exceeds the target,
+            // zero area triangles are never picked
+            let index = cdf
+                .partition_point(|&area| area <= target)
+                .min(triangles.len() - 1);
+            let [a, b, c] = triangles[index].vertices;
+
+            // https://www.cs.princeton.edu/~funk/tog02.pdf (section 4.2)
+            let r1 = rng.next_f64().sqrt();
+            let r2 = rng.next_f64();
+            let (u, v, w) = (1.0 - r1, r1 * (1.0 - r2), r1 * r2);
+            let mix = |a: f32, b: f32, c: f32| (u * a as f64 + v * b as f64 + w * c as f64) as f32;
+
+            Vec3(mix(a.0, b.0, c.0), mix(a.1, b.1, c.1), mix(a.2, b.2, c.2))
+        })
+        .collect()
+}
+
+// small deterministic generator, good enough for sampling and free of dependencies
+// https://prng.di.unimi.it/splitmix64.c
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn points_stay_inside_a_single_triangle() {
+        let triangle = fixtures::obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+
+        let points = sample_surface(&triangle, 1000, 7);
+        assert_eq!(points.len(), 1000);
+        for p in points {
+            // barycentric coordinates of the right triangle at the origin
+            let (u, v, w) = (1.0 - p.0 - p.1, p.0, p.1);
+            assert!(u >= -1e-6 && v >= 0.0 && w >= 0.0, "{p:?}");
+            assert!((u + v + w - 1.0).abs() < 1e-6);
+            assert_eq!(p.2, 0.0);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_points() {
+        let cube = fixtures::cube();
+        assert_eq!(sample_surface(&cube, 50, 1), sample_surface(&cube, 50, 1));
+        assert_ne!(sample_surface(&cube, 50, 1), sample_surface(&cube, 50, 2));
+    }
+
+    #[test]
+    fn triangles_are_picked_by_area() {
+        // a triangle of area 0.5 at z = 0 and one of area 1.5 at z = 1
+        let mesh = fixtures::obj(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nv 3 0 1\nv 0 1 1\nf 1 2 3\nf 4 5 6\n",
+        );
+
+        let points = sample_surface(&mesh, 4000, 3);
+        let low = points.iter().filter(|p| p.2 == 0.0).count() as f64 / 4000.0;
+        assert!((low - 0.25).abs() < 0.03, "{low}");
+    }
+
+    #[test]
+    fn mesh_without_area_has_no_samples() {
+        assert!(sample_surface(&Mesh::new(), 10, 0).is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+// Planar cross sections
+// every triangle crossing the Z = z plane contributes one line segment,
+// segments sharing an end point are chained into closed contour loops
+use std::collections::HashMap;
+
+use crate::{
+    calculate::triangulation::triangulate_face,
+    model::{Mesh, Vec3},
+};
+
+// segment of a triangle crossing the plane
+// end points are identified by the mesh edge they lie on, so segments of
+// neighboring triangles can be chained without comparing floats
+struct Segment {
+    start: (usize, usize),
+    end: (usize, usize),
+    points: [Vec3; 2],
+}
+
+// area of the solid's cross section at the Z = z plane
+// holes are subtracted, assuming the mesh has consistent outward winding
+pub fn cross_section_area(mesh: &Mesh, z: f32) -> f64 {
+    let area: f64 = section_loops(mesh, z)
+        .iter()
+        .map(|contour| {
+            // shoelace formula
+            // https://en.wikipedia.org/wiki/Shoelace_formula
+            let mut sum = 0.0f64;
+            for i in 0..contour.len() {
+                let a = contour[i];
+                let b = contour[(i + 1) % contour.len()];
+                sum += a.0 as f64 * b.1 as f64 - b.0 as f64 * a.1 as f64;
+            }
+            sum / 2.0
+        })
+        .sum();
+
+    area.abs()
+}
+
+// closed contours where the mesh crosses the Z = z plane
+// outer contours run counter clockwise seen from +Z and holes clockwise
+// chains that do not close (e.g., at holes in the mesh) are dropped
+pub fn section_loops(mesh: &Mesh, z: f32) -> Vec<Vec<Vec3>> {
+    chain_segments(plane_segments(mesh, z))
+}
+
+fn plane_segments(mesh: &Mesh, z: f32) -> Vec<Segment> {
+    let vertices = &mesh.vertices;
+    let mut segments = Vec::new();
+
+    for face in &mesh.faces {
+        for [i0, i1, i2] in triangulate_face(vertices, &face.v) {
+            let indices = [i0, i1, i2];
+
+            // vertices on the plane count as above it, so triangles lying in the plane
+            // produce no segment and shared vertices are not counted twice
+            let above = indices.map(|i| vertices[i].2 >= z);
+            if above.iter().all(|&a| a) || above.iter().all(|&a| !a) {
+                continue;
+            }
+
+            let mut crossings: Vec<((usize, usize), Vec3)> = Vec::with_capacity(2);
+            for k in 0..3 {
+                let (a, b) = (indices[k], indices[(k + 1) % 3]);
+                if above[k] == above[(k + 1) % 3] {
+                    continue;
+                }
+
+                // always interpolate from the lower index so both triangles of the edge agree
+                let (a, b) = if a < b { (a, b) } else { (b, a) };
+                let (va, vb) = (vertices[a], vertices[b]);
+                let t = (z - va.2) / (vb.2 - va.2);
+                let point = Vec3(va.0 + (vb.0 - va.0) * t, va.1 + (vb.1 - va.1) * t, z);
+                crossings.push(((a, b), point));
+            }
+
+            if crossings.len() != 2 {
+                continue;
+            }
+
+            // orient the segment along Z x normal, which runs counter clockwise
+            // around the outside of a solid with outward facing normals
+            let [v0, v1, v2] = [vertices[i0], vertices[i1], vertices[i2]];
+            let normal = (v1 - v0).cross(v2 - v0);
+            let direction = Vec3(0.0, 0.0, 1.0).cross(normal);
+
+            let (first, second) = (crossings[0], crossings[1]);
+            let (start, end) = if (second.1 - first.1).dot(direction) >= 0.0 {
+                (first, second)
+            } else {
+                (second, first)
+            };
+
+            segments.push(Segment {
+                start: start.0,
+                end: end.0,
+                points: [start.1, end.1],
+            });
+        }
+    }
+
+    segments
+}
+
+fn chain_segments(segments: Vec<Segment>) -> Vec<Vec<Vec3>> {
+    let mut by_start: HashMap<(usize, usize), usize> = HashMap::with_capacity(segments.len());
+    for (index, segment) in segments.iter().enumerate() {
+        by_start.insert(segment.start, index);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut loops = Vec::new();
+
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+
+        let mut contour = Vec::new();
+        let mut current = first;
+        let closed = loop {
+            used[current] = true;
+            contour.push(segments[current].points[0]);
+
+            match by_start.get(&segments[current].end) {
+                Some(&next) if next == first => break true,
+                Some(&next) if !used[next] => current = next,
+                _ => break false,
+            }
+        };
+
+        if closed && contour.len() >= 3 {
+            loops.push(contour);
+        }
+    }
+
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn cube_section_is_constant_between_its_bounds() {
+        let cube = fixtures::cube();
+
+        for z in [0.1, 0.5, 0.9] {
+            assert!((cross_section_area(&cube, z) - 1.0).abs() < 1e-6, "z = {z}");
+            assert_eq!(section_loops(&cube, z).len(), 1);
+        }
+        assert_eq!(cross_section_area(&cube, -0.5), 0.0);
+        assert_eq!(cross_section_area(&cube, 1.5), 0.0);
+    }
+
+    #[test]
+    fn open_section_is_dropped() {
+        let mut open = fixtures::cube();
+        // the side at x = 1
+        open.faces.remove(3);
+        open.groups.clear();
+
+        assert!(section_loops(&open, 0.5).is_empty());
+    }
+}
@@ -0,0 +1,418 @@
+// Mesh simplification using quadric error metrics
+// https://www.cs.cmu.edu/~garland/Papers/quadrics.pdf
+//
+// every vertex accumulates the planes of its triangles into a quadric,
+// the error of moving a vertex to a position is the sum of squared distances to those planes.
+// edges are collapsed cheapest first until the target triangle count is reached.
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use nalgebra::{Matrix3, Matrix4, Vector3, Vector4};
+
+use crate::{
+    calculate::triangulation::triangulate_face,
+    model::{Face, Mesh, Vec3},
+};
+
+// candidate edge collapse, ordered so the BinaryHeap pops the cheapest first
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    // versions of both vertices when the candidate was computed,
+    // a candidate is stale once either vertex changed
+    versions: (u32, u32),
+    target: Vec3,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Simplifier {
+    vertices: Vec<Vec3>,
+    quadrics: Vec<Matrix4<f64>>,
+    versions: Vec<u32>,
+    // vertices on a boundary edge are never moved, so holes keep their shape
+    locked: Vec<bool>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    // triangles using each vertex (may contain dead triangles)
+    vertex_triangles: Vec<Vec<usize>>,
+}
+
+// the smallest ratio `simplify_ratio` accepts, so at least some of the mesh remains
+pub const MIN_RATIO: f32 = 0.001;
+
+// same as `simplify`, targeting a share of the current triangle count (0..1)
+// the ratio is clamped to MIN_RATIO..1 and the target never drops below one triangle
+pub fn simplify_ratio(mesh: &Mesh, ratio: f32) -> Mesh {
+    let ratio = if ratio.is_nan() {
+        1.0
+    } else {
+        ratio.clamp(MIN_RATIO, 1.0)
+    };
+    let target = (mesh.triangle_count() as f64 * ratio as f64).round() as usize;
+
+    simplify(mesh, target.max(1))
+}
+
+// returns a simplified copy of the mesh with at most `target_triangles` triangles when possible
+// boundary edges are preserved, so the result may stay above the target on open meshes
+// the faces are triangulated and the groups of the input are not kept
+pub fn simplify(mesh: &Mesh, target_triangles: usize) -> Mesh {
+    let triangles: Vec<[usize; 3]> = mesh
+        .faces
+        .iter()
+        .flat_map(|face| triangulate_face(&mesh.vertices, &face.v))
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .collect();
+
+    let mut simplifier = Simplifier::new(mesh.vertices.clone(), triangles);
+    simplifier.run(target_triangles);
+    simplifier.into_mesh()
+}
+
+impl Simplifier {
+    fn new(vertices: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Self {
+        let mut quadrics = vec![Matrix4::zeros(); vertices.len()];
+        let mut vertex_triangles = vec![Vec::new(); vertices.len()];
+
+        for (index, triangle) in triangles.iter().enumerate() {
+            let quadric = plane_quadric(&vertices, triangle);
+            for &v in triangle {
+                quadrics[v] += quadric;
+                vertex_triangles[v].push(index);
+            }
+        }
+
+        let mut edge_count: HashMap<(usize, usize), usize> = HashMap::new();
+        for triangle in &triangles {
+            for i in 0..3 {
+                *edge_count
+                    .entry(edge_key(triangle[i], triangle[(i + 1) % 3]))
+                    .or_insert(0) += 1;
+            }
+        }
+
+        let mut locked = vec![false; vertices.len()];
+        for ((a, b), count) in edge_count {
+            if count != 2 {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+
+        Self {
+            versions: vec![0; vertices.len()],
+            alive: vec![true; triangles.len()],
+            vertices,
+            quadrics,
+            locked,
+            triangles,
+            vertex_triangles,
+        }
+    }
+
+    fn run(&mut self, target_triangles: usize) {
+        let mut alive_count = self.triangles.len();
+        let mut heap = BinaryHeap::new();
+
+        let mut seen: HashSet<(usize, usize)> = HashSet::new();
+        for triangle in &self.triangles {
+            for i in 0..3 {
+                let edge = edge_key(triangle[i], triangle[(i + 1) % 3]);
+                if seen.insert(edge)
+                    && let Some(collapse) = self.candidate(edge.0, edge.1)
+                {
+                    heap.push(collapse);
+                }
+            }
+        }
+
+        while alive_count > target_triangles {
+            let Some(collapse) = heap.pop() else {
+                break;
+            };
+
+            if (self.versions[collapse.a], self.versions[collapse.b]) != collapse.versions {
+                continue;
+            }
+
+            let Some(removed) = self.collapse(&collapse) else {
+                continue;
+            };
+            alive_count -= removed;
+
+            // the surviving vertex moved, so all of its edges need a new cost
+            for neighbor in self.neighbors(collapse.a) {
+                if let Some(candidate) = self.candidate(collapse.a, neighbor) {
+                    heap.push(candidate);
+                }
+            }
+        }
+    }
+
+    fn candidate(&self, a: usize, b: usize) -> Option<Collapse> {
+        if self.locked[a] || self.locked[b] {
+            return None;
+        }
+
+        let quadric = self.quadrics[a] + self.quadrics[b];
+        let (cost, target) = optimal_position(&quadric, self.vertices[a], self.vertices[b]);
+
+        Some(Collapse {
+            cost,
+            a,
+            b,
+            versions: (self.versions[a], self.versions[b]),
+            target,
+        })
+    }
+
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self.vertex_triangles[v]
+            .iter()
+            .filter(|&&t| self.alive[t])
+            .flat_map(|&t| self.triangles[t])
+            .filter(|&n| n != v)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+
+        neighbors
+    }
+
+    // merges b into a, moving a to the collapse target
+    // returns the number of triangles removed, None when the collapse would damage the surface
+    fn collapse(&mut self, collapse: &Collapse) -> Option<usize> {
+        let (a, b) = (collapse.a, collapse.b);
+
+        // link condition: the edge may only be shared by as many triangles as
+        // a and b have common neighbors, otherwise the collapse makes the surface non-manifold
+        let neighbors_a = self.neighbors(a);
+        let neighbors_b = self.neighbors(b);
+        let common = neighbors_a
+            .iter()
+            .filter(|n| neighbors_b.binary_search(n).is_ok())
+            .count();
+        let shared: Vec<usize> = self.vertex_triangles[a]
+            .iter()
+            .copied()
+            .filter(|&t| self.alive[t] && self.triangles[t].contains(&b))
+            .collect();
+        if shared.is_empty() || common != shared.len() {
+            return None;
+        }
+
+        // reject collapses that flip the orientation of a surviving triangle
+        for &v in &[a, b] {
+            for &t in &self.vertex_triangles[v] {
+                if !self.alive[t] || shared.contains(&t) {
+                    continue;
+                }
+
+                let before = triangle_normal(&self.vertices, self.triangles[t], None);
+                let after = triangle_normal(
+                    &self.vertices,
+                    self.triangles[t],
+                    Some((v, collapse.target)),
+                );
+                if before.dot(after) <= 0.0 {
+                    return None;
+                }
+            }
+        }
+
+        for &t in &shared {
+            self.alive[t] = false;
+        }
+
+        let moved = std::mem::take(&mut self.vertex_triangles[b]);
+        for t in moved {
+            if !self.alive[t] {
+                continue;
+            }
+            for v in self.triangles[t].iter_mut() {
+                if *v == b {
+                    *v = a;
+                }
+            }
+            self.vertex_triangles[a].push(t);
+        }
+
+        self.vertices[a] = collapse.target;
+        self.quadrics[a] = self.quadrics[a] + self.quadrics[b];
+        self.versions[a] += 1;
+        self.versions[b] += 1;
+
+        Some(shared.len())
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::default();
+        // lookup table: old index -> new index
+        let mut remap: Vec<Option<usize>> = vec![None; self.vertices.len()];
+
+        for (triangle, _) in self
+            .triangles
+            .iter()
+            .zip(&self.alive)
+            .filter(|(_, alive)| **alive)
+        {
+            let mut face = Face::default();
+            for &v in triangle {
+                let idx = *remap[v].get_or_insert_with(|| {
+                    mesh.vertices.push(self.vertices[v]);
+                    mesh.vertices.len() - 1
+                });
+                face.v.push(idx);
+            }
+            mesh.faces.push(face);
+        }
+
+        mesh
+    }
+}
+
+#[inline]
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+// fundamental error quadric K = p * p^T of the triangle's plane p = (a, b, c, d)
+fn plane_quadric(vertices: &[Vec3], triangle: &[usize; 3]) -> Matrix4<f64> {
+    let normal = triangle_normal(vertices, *triangle, None);
+    let point: Vector3<f64> = vertices[triangle[0]].into();
+    let normal = Vector3::new(normal.0 as f64, normal.1 as f64, normal.2 as f64);
+
+    let plane = Vector4::new(normal.x, normal.y, normal.z, -normal.dot(&point));
+    plane * plane.transpose()
+}
+
+// normal of the triangle, optionally with one of its vertices moved to a new position
+fn triangle_normal(vertices: &[Vec3], triangle: [usize; 3], moved: Option<(usize, Vec3)>) -> Vec3 {
+    let position = |v: usize| match moved {
+        Some((moved, target)) if moved == v => target,
+        _ => vertices[v],
+    };
+
+    let [v0, v1, v2] = triangle.map(position);
+    (v1 - v0).cross(v2 - v0).normalize()
+}
+
+// position minimizing the quadric error, falling back to the best of the endpoints
+// and the midpoint when the quadric is singular (e.g., flat regions)
+fn optimal_position(quadric: &Matrix4<f64>, a: Vec3, b: Vec3) -> (f64, Vec3) {
+    let error = |v: Vec3| {
+        let v = Vector4::new(v.0 as f64, v.1 as f64, v.2 as f64, 1.0);
+        (v.transpose() * quadric * v)[0]
+    };
+
+    let midpoint = Vec3((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0, (a.2 + b.2) / 2.0);
+    let edge_length = (b - a).length();
+
+    let system: Matrix3<f64> = quadric.fixed_view::<3, 3>(0, 0).into();
+    if system.determinant().abs() > 1e-12
+        && let Some(inverse) = system.try_inverse()
+    {
+        let solution = inverse * -quadric.fixed_view::<3, 1>(0, 3);
+        let v = Vec3(solution.x as f32, solution.y as f32, solution.z as f32);
+        // nearly singular systems can place the vertex far away from the edge
+        if v.is_finite() && (v - midpoint).length() <= 2.0 * edge_length {
+            return (error(v), v);
+        }
+    }
+
+    [a, b, midpoint]
+        .into_iter()
+        .map(|v| (error(v), v))
+        .min_by(|x, y| x.0.total_cmp(&y.0))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn assert_bounds_within(mesh: &Mesh, original: &Mesh, tolerance: f32) {
+        let (min, max) = mesh.bounds().unwrap();
+        let (original_min, original_max) = original.bounds().unwrap();
+        let size = (original_max - original_min).length();
+        assert!(
+            (min - original_min).length() <= tolerance * size,
+            "{min:?} vs {original_min:?}"
+        );
+        assert!(
+            (max - original_max).length() <= tolerance * size,
+            "{max:?} vs {original_max:?}"
+        );
+    }
+
+    #[test]
+    fn sphere_simplified_to_half_keeps_its_bounds() {
+        let sphere = fixtures::uv_sphere(1.0, 24, 48);
+        let simplified = simplify_ratio(&sphere, 0.5);
+
+        assert!(simplified.triangle_count() <= sphere.triangle_count() / 2);
+        assert_bounds_within(&simplified, &sphere, 0.01);
+        assert!(simplified.is_watertight());
+    }
+
+    #[test]
+    fn sphere_simplified_to_a_tenth_stays_closed() {
+        let sphere = fixtures::uv_sphere(1.0, 24, 48);
+        let simplified = simplify_ratio(&sphere, 0.1);
+
+        assert!(simplified.triangle_count() <= sphere.triangle_count() / 10);
+        assert_bounds_within(&simplified, &sphere, 0.01);
+        assert!(simplified.is_watertight());
+    }
+
+    #[test]
+    fn boundary_edges_are_kept() {
+        let open = fixtures::open_cube();
+        let simplified = simplify(&open, 1);
+
+        let (min, max) = simplified.bounds().unwrap();
+        assert_eq!((min, max), open.bounds().unwrap());
+        assert_eq!(simplified.boundary_edges().len(), 4);
+    }
+
+    #[test]
+    fn half_ratio_halves_a_thousand_triangles() {
+        let sphere = fixtures::uv_sphere(1.0, 21, 25);
+        assert_eq!(sphere.triangle_count(), 1000);
+
+        let simplified = simplify_ratio(&sphere, 0.5);
+        let count = simplified.triangle_count();
+        assert!((450..=500).contains(&count), "{count}");
+    }
+
+    #[test]
+    fn ratio_is_clamped() {
+        let sphere = fixtures::uv_sphere(1.0, 21, 25);
+        assert!(simplify_ratio(&sphere, 0.0).triangle_count() >= 1);
+        assert_eq!(simplify_ratio(&sphere, 2.0).triangle_count(), 1000);
+        assert_eq!(simplify_ratio(&sphere, f32::NAN).triangle_count(), 1000);
+    }
+}
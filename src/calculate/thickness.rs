@@ -0,0 +1,135 @@
+// wall thickness of a closed mesh
+// the thickness at a face is the distance from its center, straight into the solid
+// (against the outward facing normal), to the opposite side of the wall
+use rayon::prelude::*;
+
+use crate::{
+    calculate::{hsv_color, triangulation::compute_polygon_normal},
+    model::{Mesh, Vec3},
+    query::Bvh,
+};
+
+// indices of the faces where the wall is thinner than `min_thickness`
+// assumes outward facing normals, faces whose ray leaves the mesh are never flagged
+pub fn thin_wall_faces(mesh: &Mesh, min_thickness: f32) -> Vec<usize> {
+    wall_thickness_per_face(mesh)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, thickness)| thickness < min_thickness)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// thickness of the wall at every face, parallel to `faces`
+// infinite for faces whose ray leaves the mesh (e.g., through a hole) and degenerate faces
+pub fn wall_thickness_per_face(mesh: &Mesh) -> Vec<f32> {
+    let bvh = Bvh::new(mesh);
+
+    mesh.faces
+        .par_iter()
+        .enumerate()
+        .map(|(index, face)| {
+            let normal = compute_polygon_normal(&mesh.vertices, &face.v).normalize();
+            if face.v.len() < 3 || normal == Vec3(0.0, 0.0, 0.0) {
+                return f32::INFINITY;
+            }
+
+            let sum = face
+                .v
+                .iter()
+                .fold(Vec3(0.0, 0.0, 0.0), |sum, &i| sum + mesh.vertices[i]);
+            let center = sum * (1.0 / face.v.len() as f32);
+
+            bvh.cast_ray(center, -normal, Some(index))
+                .map_or(f32::INFINITY, |hit| hit.distance)
+        })
+        .collect()
+}
+
+// minimum wall thickness at every vertex, parallel to `vertices`: the thinnest wall
+// of the faces around it. requires a watertight mesh with outward facing normals,
+// infinite for vertices no face uses or whose faces all ray out of the mesh
+pub fn wall_thickness_per_vertex(mesh: &Mesh) -> Vec<f32> {
+    let mut thickness = vec![f32::INFINITY; mesh.vertices.len()];
+    for (face, face_thickness) in mesh.faces.iter().zip(wall_thickness_per_face(mesh)) {
+        for &i in &face.v {
+            thickness[i] = thickness[i].min(face_thickness);
+        }
+    }
+
+    thickness
+}
+
+// heatmap colors of the thickness values, red for the thinnest through green to blue
+// for the thickest. infinite values (no opposite wall found) are blue as well
+pub fn thickness_colors(thickness: &[f32]) -> Vec<[u8; 3]> {
+    let (min, max) = thickness
+        .iter()
+        .filter(|t| t.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &t| {
+            (min.min(t), max.max(t))
+        });
+    let range = max - min;
+
+    thickness
+        .iter()
+        .map(|&t| {
+            // 0 for the thinnest wall, 1 for the thickest
+            let share = if !t.is_finite() {
+                1.0
+            } else if range > 0.0 {
+                (t - min) / range
+            } else {
+                0.0
+            };
+            // hue from red (0°) to blue (240°)
+            hsv_color(share as f64 * 2.0 / 3.0, 0.85, 0.95)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn thin_box_wall_is_flagged_and_thick_one_is_not() {
+        // the top and bottom of a 10 x 10 plate 0.2 thick, 2 triangles each
+        let plate = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(10.0, 10.0, 0.2));
+        let thin = thin_wall_faces(&plate, 0.4);
+        assert_eq!(thin.len(), 4);
+        // all of them flat, the side walls are 10 across
+        for face in thin {
+            let z: Vec<f32> = plate.faces[face]
+                .v
+                .iter()
+                .map(|&i| plate.vertices[i].2)
+                .collect();
+            assert!(z.iter().all(|&height| height == z[0]), "{:?}", z);
+        }
+
+        let block = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(10.0, 10.0, 5.0));
+        assert!(thin_wall_faces(&block, 0.4).is_empty());
+    }
+
+    #[test]
+    fn cube_has_the_same_thickness_at_every_vertex() {
+        let cube = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(2.0, 2.0, 2.0));
+
+        let thickness = wall_thickness_per_vertex(&cube);
+        assert_eq!(thickness.len(), 8);
+        for t in thickness {
+            assert!((t - 2.0).abs() < 1e-4, "{}", t);
+        }
+    }
+
+    #[test]
+    fn thinnest_wall_is_red_and_missing_walls_are_blue() {
+        let colors = thickness_colors(&[1.0, 3.0, f32::INFINITY]);
+        // red, then blue for both the thickest and the infinite one
+        assert_eq!(colors[0], hsv_color(0.0, 0.85, 0.95));
+        assert_eq!(colors[1], hsv_color(2.0 / 3.0, 0.85, 0.95));
+        assert_eq!(colors[2], colors[1]);
+    }
+}
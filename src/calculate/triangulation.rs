@@ -1,6 +1,8 @@
-use crate::model::{Triangle, Vec2, Vec3};
+use smallvec::SmallVec;
 
-fn compute_polygon_normal(vertices: &[Vec3], indices: &[usize]) -> Vec3 {
+use crate::model::{Face, Mesh, MeshError, Triangle, Vec2, Vec3};
+
+pub(crate) fn compute_polygon_normal(vertices: &[Vec3], indices: &[usize]) -> Vec3 {
     let mut nx = 0.0;
     let mut ny = 0.0;
     let mut nz = 0.0;
@@ -65,7 +67,7 @@ fn project_to_2d(vertices: &[Vec3], indices: &[usize]) -> Vec<Vec2> {
 }
 
 fn is_convex(prev: Vec2, curr: Vec2, next: Vec2, winding_positive: bool) -> bool {
-    let cross = (curr.substraction(prev)).cross(next.substraction(curr));
+    let cross = (curr - prev).cross(next - curr);
     if winding_positive {
         cross > 0.0
     } else {
@@ -76,9 +78,9 @@ fn is_convex(prev: Vec2, curr: Vec2, next: Vec2, winding_positive: bool) -> bool
 // check if a point is inside a triangle using barycentric coordinates
 // https://mathworld.wolfram.com/BarycentricCoordinates.html
 fn point_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
-    let v0 = b.substraction(a);
-    let v1 = c.substraction(a);
-    let v2 = point.substraction(a);
+    let v0 = b - a;
+    let v1 = c - a;
+    let v2 = point - a;
 
     let dot00 = v0.dot(v0); // ||v0||²
     let dot01 = v0.dot(v1); // v0 · v1
@@ -102,13 +104,11 @@ fn point_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
 
 // Polygon triangulation using Ear clipping algorithm
 // https://www.geometrictools.com/Documentation/TriangulationByEarClipping.pdf
-pub fn triangulate(
-    vertices: &[Vec3],
-    indices: &[usize],
-) -> anyhow::Result<Vec<Triangle>, anyhow::Error> {
+// returns the triangles as positions into `indices`
+fn ear_clip(vertices: &[Vec3], indices: &[usize]) -> Result<Vec<[usize; 3]>, MeshError> {
     if indices.len() < 3 {
-        return Err(anyhow::anyhow!(
-            "cannot triangulate polygon with less than 3 vertices"
+        return Err(MeshError::InvalidPolygon(
+            "cannot triangulate polygon with less than 3 vertices",
         ));
     }
 
@@ -137,9 +137,7 @@ pub fn triangulate(
     while remaining_vertices > 3 {
         loop_count += 1;
         if loop_count > 2 * (remaining_vertices * remaining_vertices) {
-            return Err(anyhow::anyhow!(
-                "failed to triangulate polygon: possible non-simple polygon"
-            ));
+            return Err(MeshError::InvalidPolygon("possible non-simple polygon"));
         }
 
         let prev_idx = (i + remaining_vertices - 1) % remaining_vertices;
@@ -174,13 +172,11 @@ pub fn triangulate(
             continue;
         }
 
-        triangles.push(Triangle {
-            vertices: [
-                vertices[indices[active_indices[prev_idx]]],
-                vertices[indices[active_indices[i]]],
-                vertices[indices[active_indices[next_idx]]],
-            ],
-        });
+        triangles.push([
+            active_indices[prev_idx],
+            active_indices[i],
+            active_indices[next_idx],
+        ]);
         // remove the ear vertex
         active_indices.remove(i);
         remaining_vertices -= 1;
@@ -193,13 +189,236 @@ pub fn triangulate(
     let b = active_indices[1];
     let c = active_indices[2];
 
-    triangles.push(Triangle {
-        vertices: [
-            vertices[indices[a]],
-            vertices[indices[b]],
-            vertices[indices[c]],
-        ],
-    });
+    triangles.push([a, b, c]);
+
+    Ok(triangles)
+}
+
+pub fn triangulate(vertices: &[Vec3], indices: &[usize]) -> Result<Vec<Triangle>, MeshError> {
+    let triangles = ear_clip(vertices, indices)?
+        .into_iter()
+        .map(|[a, b, c]| Triangle {
+            vertices: [
+                vertices[indices[a]],
+                vertices[indices[b]],
+                vertices[indices[c]],
+            ],
+        })
+        .collect();
 
     Ok(triangles)
 }
+
+// triangulates a closed polygon given by its vertices in order
+// concave polygons are handled by ear clipping in the polygon's best-fit plane,
+// polygons ear clipping can not handle (e.g., self intersecting) fall back to a fan
+// returns the triangles as indices into `vertices`
+pub fn triangulate_polygon(vertices: &[Vec3]) -> Vec<[usize; 3]> {
+    let indices: Vec<usize> = (0..vertices.len()).collect();
+    triangulate_face(vertices, &indices)
+}
+
+// same as `triangulate_polygon` for a face indexing into the mesh vertices
+// returns the triangles as indices into `vertices`
+pub fn triangulate_face(vertices: &[Vec3], indices: &[usize]) -> Vec<[usize; 3]> {
+    face_corners(vertices, indices)
+        .into_iter()
+        .map(|[a, b, c]| [indices[a], indices[b], indices[c]])
+        .collect()
+}
+
+// same as `triangulate_face`, returning the triangles as positions into `indices`
+// so the texture and normal indices of the corners can be split the same way
+pub fn face_corners(vertices: &[Vec3], indices: &[usize]) -> Vec<[usize; 3]> {
+    match indices.len() {
+        0..=2 => Vec::new(),
+        3 => vec![[0, 1, 2]],
+        n => ear_clip(vertices, indices).unwrap_or_else(|_| {
+            let positions: Vec<usize> = (0..n).collect();
+            fan(&positions)
+        }),
+    }
+}
+
+// splits every face with more than 3 vertices into triangles, in place
+// the triangles keep the texture and normal indices, smoothing group and color of their face,
+// and the group face ranges are moved to cover the triangles. faces with less than
+// 3 vertices are kept as they are
+pub fn triangulate_mesh(mesh: &mut Mesh) {
+    split_faces(mesh, face_corners);
+}
+
+// same as `triangulate_mesh`, splitting every polygon into a fan around its first vertex
+// instead of ear clipping. only correct for convex polygons, kept for tools expecting fans
+pub fn fan_triangulate_mesh(mesh: &mut Mesh) {
+    split_faces(mesh, |_, indices| {
+        let positions: Vec<usize> = (0..indices.len()).collect();
+        fan(&positions)
+    });
+}
+
+// replaces every face with more than 3 vertices with the triangles `corners` splits it into
+fn split_faces(mesh: &mut Mesh, corners: impl Fn(&[Vec3], &[usize]) -> Vec<[usize; 3]>) {
+    let has_colors = mesh.face_colors.len() == mesh.faces.len();
+    let mut faces = Vec::with_capacity(mesh.triangle_count());
+    let mut face_colors = Vec::new();
+    // lookup table: old index -> index of its first triangle
+    let mut first_triangle = Vec::with_capacity(mesh.faces.len() + 1);
+
+    for (index, face) in mesh.faces.iter().enumerate() {
+        first_triangle.push(faces.len());
+        let triangles = if face.v.len() > 3 {
+            corners(&mesh.vertices, &face.v)
+        } else {
+            Vec::new()
+        };
+
+        if triangles.is_empty() {
+            faces.push(face.clone());
+        }
+        for corners in triangles {
+            faces.push(Face {
+                v: pick(&face.v, corners, face.v.len()),
+                vt: pick(&face.vt, corners, face.v.len()),
+                vn: pick(&face.vn, corners, face.v.len()),
+                smoothing_group: face.smoothing_group,
+            });
+        }
+
+        if has_colors {
+            face_colors.resize(faces.len(), mesh.face_colors[index]);
+        }
+    }
+    first_triangle.push(faces.len());
+
+    for group in &mut mesh.groups {
+        let before = mesh.faces.len();
+        group.face_range = first_triangle[group.face_range.start.min(before)]
+            ..first_triangle[group.face_range.end.min(before)];
+    }
+    mesh.faces = faces;
+    if has_colors {
+        mesh.face_colors = face_colors;
+    }
+    mesh.invalidate_face_areas();
+}
+
+// the entries of the corners of a triangle, from an array parallel to the face's vertices
+// arrays that are not parallel (e.g., empty texture indices) stay empty
+fn pick<T: Copy>(indices: &SmallVec<[T; 4]>, corners: [usize; 3], n: usize) -> SmallVec<[T; 4]> {
+    if indices.len() == n {
+        corners.iter().map(|&corner| indices[corner]).collect()
+    } else {
+        SmallVec::new()
+    }
+}
+
+// Fan triangulation: Connect v0 to v(i) and v(i+1)
+// only correct for convex polygons
+pub fn fan(indices: &[usize]) -> Vec<[usize; 3]> {
+    (1..indices.len().saturating_sub(1))
+        .map(|i| [indices[0], indices[i], indices[i + 1]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // L shape of area 3, starting next to the reflex corner at (1, 1), so a fan
+    // from the first vertex covers the notch at [1, 2]²
+    const L_SHAPE: [(f32, f32); 6] = [
+        (2.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 2.0),
+        (0.0, 2.0),
+        (0.0, 0.0),
+        (2.0, 0.0),
+    ];
+    // chevron pentagon of area 3 with the reflex corner at (1, 1)
+    const CHEVRON: [(f32, f32); 5] = [(2.0, 2.0), (1.0, 1.0), (0.0, 2.0), (0.0, 0.0), (2.0, 0.0)];
+
+    fn polygon(points: &[(f32, f32)]) -> Vec<Vec3> {
+        points.iter().map(|&(x, y)| Vec3(x, y, 0.0)).collect()
+    }
+
+    fn area(vertices: &[Vec3], triangles: &[[usize; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                Triangle {
+                    vertices: [vertices[a], vertices[b], vertices[c]],
+                }
+                .area()
+            })
+            .sum()
+    }
+
+    // even-odd test of a point against the polygon
+    fn inside(vertices: &[Vec3], point: Vec2) -> bool {
+        let mut inside = false;
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            if (a.1 > point.1) != (b.1 > point.1)
+                && point.0 < a.0 + (point.1 - a.1) / (b.1 - a.1) * (b.0 - a.0)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    fn assert_covers(points: &[(f32, f32)], expected_area: f64) {
+        let vertices = polygon(points);
+        let triangles = triangulate_polygon(&vertices);
+
+        assert_eq!(triangles.len(), vertices.len() - 2);
+        assert!((area(&vertices, &triangles) - expected_area).abs() < 1e-6);
+        for &[a, b, c] in &triangles {
+            let centroid = (vertices[a] + vertices[b] + vertices[c]) * (1.0 / 3.0);
+            assert!(
+                inside(&vertices, Vec2(centroid.0, centroid.1)),
+                "triangle {:?} lies outside the polygon",
+                [a, b, c]
+            );
+        }
+    }
+
+    #[test]
+    fn ear_clipping_triangulates_concave_polygons() {
+        assert_covers(&L_SHAPE, 3.0);
+        assert_covers(&CHEVRON, 3.0);
+    }
+
+    #[test]
+    fn fan_covers_the_notch_of_concave_polygons() {
+        // the case ear clipping exists for
+        let vertices = polygon(&L_SHAPE);
+        let indices: Vec<usize> = (0..vertices.len()).collect();
+        assert!(area(&vertices, &fan(&indices)) > 3.5);
+    }
+
+    #[test]
+    fn concave_faces_are_split_by_ear_clipping() {
+        let mut mesh = Mesh::new();
+        mesh.vertices = polygon(&L_SHAPE);
+        mesh.faces.push(Face {
+            v: (0..6).collect(),
+            ..Face::default()
+        });
+
+        let area: f64 = mesh.triangles().map(|t| t.area()).sum();
+        assert!((area - 3.0).abs() < 1e-6);
+        triangulate_mesh(&mut mesh);
+        assert_eq!(mesh.faces.len(), 4);
+    }
+
+    #[test]
+    fn face_areas_are_recomputed_after_triangulating() {
+        let mut cube = crate::fixtures::cube();
+        assert_eq!(cube.face_areas(), [1.0; 6]);
+
+        triangulate_mesh(&mut cube);
+        assert_eq!(cube.face_areas(), [0.5; 12]);
+    }
+}
@@ -0,0 +1,183 @@
+// meshes shared by the unit tests
+use std::{collections::HashMap, f32::consts::PI};
+
+use crate::{
+    calculate,
+    model::{Face, Mesh, MeshCodec, Vec3, obj::ObjCodec},
+};
+
+// unit cube [0, 1]³ of 6 quads with outward facing normals
+pub const CUBE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 2 3 7 6
+f 3 4 8 7
+f 4 1 5 8
+";
+
+// corner tetrahedron of the unit cube with outward facing normals
+pub const TETRAHEDRON: &str =
+    "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n";
+
+// parses and welds an OBJ source, like `load` does for files
+pub fn obj(source: &str) -> Mesh {
+    let mut mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+    mesh.weld();
+    mesh
+}
+
+pub fn cube() -> Mesh {
+    obj(CUBE)
+}
+
+// the unit cube without its top face
+pub fn open_cube() -> Mesh {
+    let mut mesh = cube();
+    mesh.faces.remove(1);
+    mesh.groups.clear();
+    mesh
+}
+
+// closed box of 12 triangles between the two corners
+pub fn box_mesh(min: Vec3, max: Vec3) -> Mesh {
+    let mut corners = Mesh::new();
+    corners.vertices = vec![min, max];
+    calculate::bounding_box_mesh(&corners).unwrap()
+}
+
+// closed UV sphere around the origin with outward facing triangles,
+// `stacks` rings of latitude from pole to pole and `slices` of longitude
+pub fn uv_sphere(radius: f32, stacks: usize, slices: usize) -> Mesh {
+    let mut mesh = Mesh::new();
+    mesh.vertices.push(Vec3(0.0, 0.0, radius));
+    for i in 1..stacks {
+        let theta = PI * i as f32 / stacks as f32;
+        for j in 0..slices {
+            let phi = 2.0 * PI * j as f32 / slices as f32;
+            mesh.vertices.push(Vec3(
+                radius * theta.sin() * phi.cos(),
+                radius * theta.sin() * phi.sin(),
+                radius * theta.cos(),
+            ));
+        }
+    }
+    mesh.vertices.push(Vec3(0.0, 0.0, -radius));
+
+    let south = mesh.vertices.len() - 1;
+    let ring = |i: usize, j: usize| 1 + (i - 1) * slices + j % slices;
+    let mut triangles = Vec::new();
+    for j in 0..slices {
+        triangles.push([0, ring(1, j), ring(1, j + 1)]);
+        for i in 1..stacks - 1 {
+            let (a, b, c, d) = (
+                ring(i, j),
+                ring(i + 1, j),
+                ring(i + 1, j + 1),
+                ring(i, j + 1),
+            );
+            triangles.push([a, b, c]);
+            triangles.push([a, c, d]);
+        }
+        triangles.push([south, ring(stacks - 1, j + 1), ring(stacks - 1, j)]);
+    }
+    mesh.faces = triangles
+        .into_iter()
+        .map(|v| Face {
+            v: v.into_iter().collect(),
+            ..Face::default()
+        })
+        .collect();
+
+    mesh
+}
+
+// closed icosphere around the origin with outward facing triangles, an icosahedron
+// whose triangles are split in 4 `subdivisions` times, the new vertices pushed onto the sphere
+pub fn icosphere(radius: f32, subdivisions: usize) -> Mesh {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut vertices = vec![
+        Vec3(-1.0, t, 0.0),
+        Vec3(1.0, t, 0.0),
+        Vec3(-1.0, -t, 0.0),
+        Vec3(1.0, -t, 0.0),
+        Vec3(0.0, -1.0, t),
+        Vec3(0.0, 1.0, t),
+        Vec3(0.0, -1.0, -t),
+        Vec3(0.0, 1.0, -t),
+        Vec3(t, 0.0, -1.0),
+        Vec3(t, 0.0, 1.0),
+        Vec3(-t, 0.0, -1.0),
+        Vec3(-t, 0.0, 1.0),
+    ];
+    let mut triangles = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // edges shared by two triangles get a single midpoint
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push((vertices[a] + vertices[b]) * 0.5);
+                vertices.len() - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut mesh = Mesh::new();
+    mesh.vertices = vertices
+        .into_iter()
+        .map(|v| v.normalize() * radius)
+        .collect();
+    mesh.faces = triangles
+        .into_iter()
+        .map(|v| Face {
+            v: v.into_iter().collect(),
+            ..Face::default()
+        })
+        .collect();
+
+    mesh
+}
+
+// writes the mesh with `codec` and parses the output again
+pub fn round_trip(codec: &dyn MeshCodec, mesh: &Mesh) -> Mesh {
+    let mut bytes = Vec::new();
+    codec.write_to(&mut bytes, mesh).unwrap();
+    codec.parse(&bytes).unwrap()
+}
@@ -1,5 +1,163 @@
 pub mod calculate;
+#[cfg(test)]
+mod fixtures;
 pub mod model;
+pub mod query;
 pub mod repair;
 pub mod ui;
 pub mod util;
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use model::{Format, MAX_TRIANGLES, Mesh, MeshCodec, MeshError, WeldStats};
+
+// how long the phases of `load_with_report` took and what welding merged
+#[derive(Debug, Clone, Copy)]
+pub struct LoadReport {
+    // reading, decompressing and parsing the file
+    pub parse_time: Duration,
+    pub weld_time: Duration,
+    pub weld: WeldStats,
+}
+
+// reads, parses and welds a mesh file
+// the format is detected from the content and the extension, see `Format::detect`
+// gzip compressed files are decompressed first, materials of OBJ files
+// and external glTF buffers are loaded relative to the file
+pub fn load(path: &Path) -> Result<(Mesh, Format), MeshError> {
+    let (mesh, format, _) = load_with_report(path)?;
+
+    Ok((mesh, format))
+}
+
+// same as `load`, along with the timings of the parse and weld phases
+pub fn load_with_report(path: &Path) -> Result<(Mesh, Format, LoadReport), MeshError> {
+    let read_start = Instant::now();
+    let bytes = std::fs::read(path)?;
+    let read_time = read_start.elapsed();
+    let hint = path.to_str().and_then(Format::from_name);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    let (mesh, format, mut report) =
+        load_bytes_with_report(&bytes, hint, None, Some(base_dir), MAX_TRIANGLES)?;
+    report.parse_time += read_time;
+
+    Ok((mesh, format, report))
+}
+
+// same as `load` for a mesh already in memory, `hint` is the format of the file name
+// it came from, if any, preferred over the content as `Format::detect` explains
+pub fn load_bytes(bytes: &[u8], hint: Option<Format>) -> Result<(Mesh, Format), MeshError> {
+    let (mesh, format, _) = load_bytes_with_report(bytes, hint, None, None, MAX_TRIANGLES)?;
+
+    Ok((mesh, format))
+}
+
+// parses (see `parse_bytes`) and welds a mesh already in memory, along with the timings
+// of both phases. materials of OBJ files are loaded relative to `base_dir`, if any
+pub fn load_bytes_with_report(
+    bytes: &[u8],
+    hint: Option<Format>,
+    format: Option<Format>,
+    base_dir: Option<&Path>,
+    max_triangles: u32,
+) -> Result<(Mesh, Format, LoadReport), MeshError> {
+    let parse_start = Instant::now();
+    let (mut mesh, format) = parse_bytes(bytes, hint, format, base_dir, max_triangles)?;
+    let parse_time = parse_start.elapsed();
+
+    let weld_start = Instant::now();
+    let weld = mesh.weld();
+    let report = LoadReport {
+        parse_time,
+        weld_time: weld_start.elapsed(),
+        weld,
+    };
+
+    if let Some(base_dir) = base_dir
+        && !mesh.matlibs.is_empty()
+        && let Err(err) = mesh.load_materials(base_dir)
+    {
+        ui::print_warn(&format!("failed to load materials: {}", err));
+    }
+
+    Ok((mesh, format, report))
+}
+
+// decompresses, detects the format of and parses the mesh without welding it
+// `format` skips the detection and parses the mesh as that format (e.g., from a CLI flag)
+// files referenced by the mesh (e.g., glTF buffers) are resolved relative to `base_dir`
+// binary STL files may declare up to `max_triangles`, see `MAX_TRIANGLES`
+pub fn parse_bytes(
+    bytes: &[u8],
+    hint: Option<Format>,
+    format: Option<Format>,
+    #[cfg_attr(not(feature = "gltf"), allow(unused_variables))] base_dir: Option<&Path>,
+    max_triangles: u32,
+) -> Result<(Mesh, Format), MeshError> {
+    // nothing to detect a format from
+    if bytes.is_empty() {
+        return Err(MeshError::Empty);
+    }
+
+    let buffer = model::decompress(bytes)?;
+
+    let format = Format::detect_with_hint(&buffer, hint, format, max_triangles)
+        .ok_or(MeshError::UnsupportedFormat)?;
+
+    let mesh = match format {
+        Format::STL => model::stl::StlCodec { max_triangles }.parse(&buffer)?,
+        #[cfg(feature = "gltf")]
+        Format::GLTF => model::gltf::GltfCodec.parse_with_base(&buffer, base_dir)?,
+        _ => format.get_codec().parse(&buffer)?,
+    };
+
+    Ok((mesh, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, model::MeshCodec};
+
+    #[test]
+    fn load_detects_parses_and_welds_a_file() {
+        let dir = std::env::temp_dir().join(format!("mesh_rs-load-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cube.stl");
+        model::stl::StlCodec::default()
+            .write(&path, &fixtures::cube())
+            .unwrap();
+
+        let (mesh, format, report) = load_with_report(&path).unwrap();
+        assert_eq!(format, Format::STL);
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 12);
+        assert_eq!(report.weld.vertices_after, 8);
+        // reading the file takes some time, welding 8 vertices may not register
+        assert!(report.parse_time > Duration::ZERO);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_bytes_detects_the_format_from_the_content() {
+        let (mesh, format) = load_bytes(fixtures::CUBE.as_bytes(), None).unwrap();
+        assert_eq!(format, Format::OBJ);
+        assert_eq!(mesh.faces.len(), 6);
+
+        assert!(matches!(load_bytes(&[], None), Err(MeshError::Empty)));
+    }
+
+    #[test]
+    fn header_only_stl_is_an_empty_mesh() {
+        // an 80 byte header and a triangle count of 0
+        let (mesh, format) = load_bytes(&[0; 84], Some(Format::STL)).unwrap();
+        assert_eq!(format, Format::STL);
+        assert!(mesh.faces.is_empty());
+        assert!(mesh.vertices.is_empty());
+    }
+}
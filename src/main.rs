@@ -1,13 +1,27 @@
-use std::{fs::OpenOptions, io::Read, path::PathBuf};
+use std::{
+    collections::HashSet,
+    fs::OpenOptions,
+    io::{BufWriter, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use mesh_rs::{
-    calculate,
-    model::{self, MeshCodec, obj::ObjCodec, stl::StlCodec},
-    ui,
-    util::{warn_topology, warn_units},
+    LoadReport,
+    calculate::{
+        self, Axis, Pivot,
+        simplify::{simplify, simplify_ratio},
+        triangulation,
+    },
+    model::{self, Mesh, MeshCodec, MeshError, Unit, obj::ObjCodec, xyz::PointCloudCodec},
+    query::{self, sdf, slice, voxel},
+    repair, ui,
+    util::{MIN_MM_VALUE, warn_topology, warn_units},
 };
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use memmap2::Mmap;
+use rayon::prelude::*;
 
 #[derive(Parser)]
 #[command(name = "Mesh tool")]
@@ -18,6 +32,7 @@ use clap::{Parser, Subcommand};
 Supported Formats:
 - STL (Binary and ASCII)
 - OBJ (Wavefront)
+- OFF (Geomview)
 
 Examples:
   # Get volume of a mesh
@@ -27,11 +42,79 @@ Examples:
   object_resize input.obj scale 100 -o output.obj"
 )]
 struct Cli {
-    /// The input file path (e.g., model.stl, model.obj, etc.)
+    /// The input file path (e.g., model.stl, model.obj, etc.), `-` for stdin
     ///
     /// The tool automatically detects the file format based on the content or extension.
     input: PathBuf,
 
+    /// Parse the input as this format (stl, obj, off, ...) instead of detecting it
+    ///
+    /// For files without an extension, from stdin or that the detection gets wrong.
+    #[arg(long, global = true, value_parser = parse_format)]
+    format: Option<model::Format>,
+
+    /// Memory-map the input file instead of reading it into memory
+    ///
+    /// Avoids copying large files before parsing. The file must not be modified
+    /// by another process while it is mapped, doing so is undefined behavior.
+    #[arg(long, global = true)]
+    mmap: bool,
+
+    /// Only print the resulting values and errors
+    ///
+    /// Takes precedence over --verbose when both are passed.
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print additional details such as parse and compute timings
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Number of decimals written to text based output formats (e.g., OBJ)
+    #[arg(long, global = true, default_value_t = model::DEFAULT_PRECISION)]
+    precision: usize,
+
+    /// Maximum number of triangles a binary STL may declare
+    ///
+    /// Protects against files declaring a huge count to force large allocations.
+    /// Pass 0 to disable the limit for very large scans.
+    #[arg(long, global = true, default_value_t = model::MAX_TRIANGLES)]
+    max_triangles: u32,
+
+    /// Disable colored output
+    ///
+    /// Color is also disabled when the NO_COLOR environment variable is set
+    /// or when stdout is not a terminal (e.g., redirected to a log file).
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Remove vertices with NaN or infinite coordinates and the faces using them
+    ///
+    /// Corrupt files can contain such vertices, which break most calculations.
+    #[arg(long, global = true)]
+    sanitize: bool,
+
+    /// Split polygons written to STL into fans of triangles instead of ear clipping
+    ///
+    /// Fans are only correct for convex polygons, concave ones come out wrong.
+    #[arg(long, global = true)]
+    fan: bool,
+
+    /// Print how long parsing, welding and the command took
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// Unit of the input coordinates, none of the formats store it
+    #[arg(long, global = true, value_enum)]
+    units: Option<UnitArg>,
+
+    /// Convert the coordinates to this unit before running the command
+    ///
+    /// Multiplies every coordinate (e.g., by 25.4 from inches to millimeters).
+    /// The input is taken as millimeters when --units is not given.
+    #[arg(long, global = true, value_enum)]
+    to_units: Option<UnitArg>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -43,11 +126,28 @@ enum Commands {
     /// This calculates the distance between the minimum and maximum corners of the axis-aligned bounding box.
     Diagonal,
 
+    /// Get the width, height and depth of the mesh
+    ///
+    /// The extents of the axis-aligned bounding box along X, Y and Z.
+    Dimensions,
+
+    /// Get the Z range of the mesh, e.g. for estimating the print height
+    ///
+    /// Prints the lowest and highest Z and the height between them.
+    Height {
+        /// Layer height, prints the estimated number of layers when given
+        layer_height: Option<f32>,
+    },
+
     /// Get the volume of the mesh
     ///
-    /// Calculates the signed volume of the mesh. Assumes the mesh is watertight and manifold.
+    /// Calculates the volume of the mesh. Assumes the mesh is watertight and manifold.
     /// The unit is cubic units based on the input file's units (usually mm^3).
-    Volume,
+    Volume {
+        /// Print the signed volume, negative when the mesh is inside out
+        #[arg(long)]
+        signed: bool,
+    },
 
     /// Get the triangle count of the mesh
     ///
@@ -55,7 +155,17 @@ enum Commands {
     Triangles,
 
     /// Get comprehensive statistics (volume, diagonal, and triangle count)
-    Stats,
+    Stats {
+        /// Compare the normals stored in an STL file with the ones computed from the winding
+        ///
+        /// Warns when many stored normals are wrong, a sign of a bad exporter.
+        #[arg(long)]
+        check_normals: bool,
+
+        /// Also print the triangle count, surface area and volume of every group
+        #[arg(long)]
+        by_group: bool,
+    },
 
     /// Scale the mesh to a target diagonal length
     ///
@@ -65,74 +175,746 @@ enum Commands {
         /// The target diagonal length in the same units as the input file
         target_diagonal: f32,
 
+        /// The point that stays in place
+        #[arg(long, value_enum, default_value = "center")]
+        pivot: PivotArg,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_scaled.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Convert the mesh to another format
+    ///
+    /// The output format is picked from the extension of the output path.
+    /// `.xyz` (or --points) writes the vertices as a point cloud.
+    /// OBJ output keeps quads and other polygons, STL output is always triangulated.
+    Convert {
+        /// The output file path, `-` writes to stdout in the input format
+        output: PathBuf,
+
+        /// Write the vertices as an XYZ point cloud, regardless of the extension
+        #[arg(long)]
+        points: bool,
+
+        /// Split quads and other polygons into triangles
+        #[arg(long)]
+        triangulate: bool,
+
+        /// Paint every connected component a distinct color
+        ///
+        /// The colors are written to binary STL files (in the attribute bytes).
+        #[arg(long)]
+        color_components: bool,
+    },
+
+    /// Remove faces that lie inside the solid
+    ///
+    /// Drops internal walls left over from merging solids, which distort the volume
+    /// and print time. Uses ray casting and assumes consistent outward facing normals.
+    Deintersect {
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_deintersected.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Make the mesh printable in one go
+    ///
+    /// Runs, in order: welding of nearby vertices, removal of degenerate and duplicate faces,
+    /// removal of unused vertices, consistent orientation and filling of small holes.
+    /// Every step can be skipped.
+    Repair {
+        /// Distance below which vertices are merged, defaults to a millionth of the diagonal
+        #[arg(long)]
+        tolerance: Option<f32>,
+
+        /// Holes with at most this many edges are filled
+        #[arg(long, default_value_t = repair::MAX_HOLE_EDGES)]
+        max_hole_edges: usize,
+
+        /// Skip welding nearby vertices
+        #[arg(long)]
+        no_weld: bool,
+
+        /// Skip removing degenerate faces
+        #[arg(long)]
+        no_degenerate: bool,
+
+        /// Skip removing duplicate faces
+        #[arg(long)]
+        no_duplicates: bool,
+
+        /// Skip removing unused vertices
+        #[arg(long)]
+        no_unused: bool,
+
+        /// Skip orienting the faces consistently
+        #[arg(long)]
+        no_orient: bool,
+
+        /// Skip filling small holes
+        #[arg(long)]
+        no_fill_holes: bool,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_repaired.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Reduce the triangle count of the mesh
+    ///
+    /// Collapses edges driven by quadric error metrics, so flat regions are simplified first
+    /// and sharp features are kept. Boundary edges are preserved.
+    Simplify {
+        /// The number of triangles to reduce the mesh to
+        #[arg(required_unless_present = "ratio")]
+        target: Option<usize>,
+
+        /// Share of the current triangles to keep (0..1) instead of a triangle count
+        #[arg(long, conflicts_with = "target")]
+        ratio: Option<f32>,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_simplified.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Get the area of the mesh's cross section at a height
+    ///
+    /// Intersects the mesh with the horizontal plane Z = z and sums the area of the
+    /// enclosed contours. Holes are subtracted, assuming consistent outward facing normals.
+    CrossSection {
+        /// The height of the cutting plane
+        z: f32,
+    },
+
+    /// Get the footprint (silhouette) area of the mesh seen along an axis
+    ///
+    /// Overlapping parts are only counted once. The area is computed on a raster grid,
+    /// so it is accurate to roughly 1/1024 of the mesh's extent along the outline.
+    Footprint {
+        /// The axis to look along
+        #[arg(value_enum, default_value = "z")]
+        axis: AxisArg,
+    },
+
+    /// Find the faces where the walls are thinner than a threshold
+    ///
+    /// Measures the distance from every face straight into the solid to the opposite
+    /// side of the wall. Assumes a closed mesh with outward facing normals.
+    ThinWalls {
+        /// The minimum wall thickness, e.g. the nozzle diameter
+        thickness: f32,
+    },
+
+    /// Write a heatmap of the wall thickness as vertex colors
+    ///
+    /// Every vertex gets the thickness of the thinnest wall of its faces, from red (thinnest)
+    /// to blue (thickest). Requires a watertight mesh with outward facing normals.
+    Thickness {
+        /// The output file path, an OBJ file (the format storing vertex colors), `-` for stdout
+        output: PathBuf,
+    },
+
+    /// Check whether the stored vertex normals match the geometry
+    ///
+    /// Compares every normal referenced by a face corner (OBJ `vn`) with the area weighted
+    /// normal computed from the faces around the vertex. Flat shaded meshes store face
+    /// normals, which deviate at the corners.
+    NormalsAudit {
+        /// Largest deviation, in degrees, a normal may have to count as matching
+        #[arg(short, long, default_value_t = model::stl::NORMAL_TOLERANCE_DEGREES)]
+        tolerance: f32,
+    },
+
+    /// Get the inertia tensor about the center of mass
+    ///
+    /// Assumes a closed mesh with uniform density. Prints the tensor and the
+    /// principal moments of inertia (its eigenvalues).
+    Inertia {
+        /// Density of the material in mass per cubic unit
+        #[arg(long, default_value_t = 1.0)]
+        density: f64,
+    },
+
+    /// Get statistics for every mesh file in a directory
+    ///
+    /// The input path is the directory to scan for .stl, .obj and .off files. Files are processed
+    /// in parallel, files that fail to load are reported and skipped.
+    Batch {
+        /// Also scan sub directories
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Print the results as a JSON array instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Validate the mesh for 3D printing and exit with a code per failure class
+    ///
+    /// Exit codes: 0 clean, 1 the file could not be read or parsed, 2 non-manifold edges,
+    /// 3 holes (boundary edges), 4 degenerate faces, 5 warnings (only with --strict).
+    /// When several checks fail, the lowest code is used.
+    Validate {
+        /// Also fail on warnings (duplicate faces, suspiciously small models)
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Get the total length of the mesh's edges
+    ///
+    /// Every edge is counted once, even when it is shared by two faces.
+    Edges {
+        /// Also print the edge length of every group
+        #[arg(long)]
+        by_group: bool,
+    },
+
+    /// Print how many edges meet at the vertices
+    ///
+    /// Prints a histogram of the vertex valences and the number of irregular interior
+    /// vertices (valence other than 6, or 4 when most faces are quads), useful for judging
+    /// the quality of a remesh.
+    Valence,
+
+    /// Print the size of the mesh as an indexed triangle list
+    ///
+    /// Polygons are split into triangles and only the unique positions the triangles use
+    /// are kept, with `u32` indices, which is the layout GPU vertex and index buffers expect.
+    Index {
+        /// Optional output file path for the indexed mesh (.obj or .stl)
+        ///
+        /// Use `-` to write to stdout in the input format.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Scale the mesh down to fit inside a box, e.g., the build volume of a printer
+    ///
+    /// The mesh is scaled uniformly about its bounding box center and never scaled up.
+    Fit {
+        /// Width of the box (X)
+        x: f32,
+
+        /// Depth of the box (Y)
+        y: f32,
+
+        /// Height of the box (Z)
+        z: f32,
+
         /// Optional output file path
         ///
-        /// If not provided, the output will be saved as <input_stem>_scaled.<ext>
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_fitted.<ext>
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+
+    /// Print a histogram of the triangle areas
+    ///
+    /// Useful for mesh quality reports, e.g., spotting slivers next to huge triangles.
+    Histogram {
+        /// Number of bins between the smallest and the largest area
+        #[arg(short, long, default_value_t = 10)]
+        bins: usize,
+    },
+
+    /// Sample the signed distance to the surface on a regular grid
+    ///
+    /// Distances are negative inside the mesh. The grid covers the bounding box
+    /// padded by 10% of the diagonal, with x varying fastest.
+    Sdf {
+        /// Number of samples along every axis, at most 512
+        #[arg(short, long, default_value_t = 64)]
+        resolution: usize,
+
+        /// Output file path
+        ///
+        /// `.npy` writes a NumPy array of shape (z, y, x), anything else raw little endian f32.
+        /// Use `-` to write raw samples to stdout.
+        output: PathBuf,
+    },
+
+    /// Slice the mesh into horizontal layers, e.g. for a slicer preview
+    ///
+    /// Every layer is cut at its middle height and the contours are chained into closed loops.
+    Slice {
+        /// Height of a layer
+        layer_height: f32,
+
+        /// Output file path
+        ///
+        /// `.svg` draws the layers on top of each other, anything else writes a line
+        /// `layer <z> <loops>` per layer followed by the X Y points of every loop.
+        /// Use `-` to write the text format to stdout.
+        output: PathBuf,
+    },
+
+    /// Count the connected components (shells) and how many of them are closed
+    ///
+    /// Faces sharing a vertex belong to the same component. A component is solid
+    /// when every edge is shared by exactly two of its faces.
+    Components,
+
+    /// Voxelize the solid and report the number of filled voxels
+    ///
+    /// The volume of the filled voxels is a cross-check of the calculated volume.
+    /// The mesh should be watertight.
+    Voxelize {
+        /// Edge length of a voxel
+        size: f32,
+    },
+
+    /// Sample points uniformly over the surface and write them as an XYZ point cloud
+    ///
+    /// Triangles are picked proportionally to their area.
+    Sample {
+        /// Number of points
+        count: usize,
+
+        /// Output file path, use `-` to write to stdout
+        output: PathBuf,
+
+        /// Seed of the random generator, the same seed gives the same points
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+
+    /// Snap every vertex to a grid and merge the vertices ending up in the same place
+    ///
+    /// Useful for deterministic deduplication and smaller files.
+    Quantize {
+        /// Grid spacing, e.g. 0.01 keeps two decimals
+        grid: f32,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_quantized.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check whether the mesh is watertight
+    ///
+    /// Exits with code 0 when every edge is shared by exactly two faces and 1 otherwise,
+    /// so it can be used as a CI gate.
+    Watertight,
+
+    /// Move the mesh so its bounding box is centered at the origin
+    Center {
+        /// Center only on X/Y and rest the mesh on Z = 0, as slicers expect
+        #[arg(long)]
+        ground: bool,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_centered.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Mirror the mesh across a coordinate plane
+    ///
+    /// Negates the chosen coordinate of every vertex. The face winding is reversed
+    /// as well, so the surface keeps facing outwards.
+    Mirror {
+        /// The axis whose coordinate is negated (x mirrors across the YZ plane)
+        #[arg(value_enum)]
+        axis: AxisArg,
+
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_mirrored.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Flip the orientation of the mesh
+    ///
+    /// Reverses the winding of every face and the vertex normals, turning the surface
+    /// inside out. Useful for meshes exported from left-handed tools.
+    Flip {
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_flipped.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Write the bounding box of the mesh as a closed box mesh
+    ///
+    /// Handy for checking the alignment of a mesh in a viewer.
+    /// The output format is picked from the extension of the output path.
+    BoxMesh {
+        /// The output file path, `-` writes to stdout in the input format
+        output: PathBuf,
+    },
+
+    /// Rotate the mesh so its principal axes line up with X, Y and Z
+    ///
+    /// The axis along which the surface spreads the most ends up on X, the smallest on Z.
+    /// Useful for giving every mesh of a dataset the same orientation.
+    Align {
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_aligned.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rotate the mesh so it covers the least area of the print bed
+    ///
+    /// Tries a coarse grid of rotations about X and Y (15° steps) and keeps the one with
+    /// the smallest silhouette on the XY plane. The mesh is rotated about its center.
+    /// Long parts end up standing on end, use `align` to lay them flat instead.
+    AutoOrient {
+        /// Optional output file path
+        ///
+        /// Use `-` to write to stdout. If not provided, the output will be saved as <input_stem>_oriented.<ext>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Compare the mesh against another one and exit with 1 when they differ
+    ///
+    /// Compares the vertex and triangle counts, the bounds, the volume and an estimate of
+    /// the Hausdorff distance between the surfaces. Differing counts are only reported,
+    /// the meshes are equal when the geometry matches within the tolerance.
+    Diff {
+        /// The mesh to compare against
+        other: PathBuf,
+
+        /// Largest allowed distance between the bounds and the surfaces, the volumes may
+        /// differ by the tolerance times the surface area
+        #[arg(short, long, default_value_t = 1e-4)]
+        tolerance: f32,
+
+        /// Points sampled on each surface for the Hausdorff distance, 0 skips it
+        #[arg(long, default_value_t = 10_000)]
+        samples: usize,
+    },
+}
+
+// values of the axis, pivot and unit arguments, the library types do not depend on clap
+#[derive(Clone, Copy, ValueEnum)]
+enum AxisArg {
+    X,
+    Y,
+    Z,
+}
+
+impl From<AxisArg> for Axis {
+    fn from(axis: AxisArg) -> Self {
+        match axis {
+            AxisArg::X => Axis::X,
+            AxisArg::Y => Axis::Y,
+            AxisArg::Z => Axis::Z,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PivotArg {
+    /// Center of the bounding box
+    Center,
+    /// Minimum corner of the bounding box, e.g. to keep a model on the build plate
+    Min,
+    /// Origin of the coordinate system
+    Origin,
+}
+
+impl From<PivotArg> for Pivot {
+    fn from(pivot: PivotArg) -> Self {
+        match pivot {
+            PivotArg::Center => Pivot::Center,
+            PivotArg::Min => Pivot::Min,
+            PivotArg::Origin => Pivot::Origin,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum UnitArg {
+    Mm,
+    Cm,
+    M,
+    In,
+}
+
+impl From<UnitArg> for Unit {
+    fn from(unit: UnitArg) -> Self {
+        match unit {
+            UnitArg::Mm => Unit::Mm,
+            UnitArg::Cm => Unit::Cm,
+            UnitArg::M => Unit::M,
+            UnitArg::In => Unit::In,
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-    if !cli.input.exists() {
+
+    // quiet wins when both flags are passed
+    if cli.quiet {
+        ui::set_level(ui::Level::Quiet);
+    } else if cli.verbose {
+        ui::set_level(ui::Level::Verbose);
+    }
+    let max_triangles = match cli.max_triangles {
+        0 => u32::MAX,
+        limit => limit,
+    };
+
+    // https://no-color.org
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if cli.no_color || no_color_env || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    if cli.input != Path::new("-") && !cli.input.exists() {
         ui::print_error(&format!("Input file does not exist: {:?}", cli.input));
         std::process::exit(1);
     }
 
-    let mut file = OpenOptions::new().read(true).open(&cli.input)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
+    if let Some(Commands::Batch { recursive, json }) = cli.command {
+        return batch(
+            &cli.input,
+            recursive,
+            json,
+            cli.mmap,
+            cli.format,
+            max_triangles,
+        );
+    }
 
-    let format = model::Format::from_magic_bytes(&buffer)
-        .or_else(|| {
-            let ext = cli.input.extension()?.to_str()?;
-            match ext.to_lowercase().as_str() {
-                "stl" => Some(model::Format::STL),
-                "obj" => Some(model::Format::OBJ),
-                _ => None,
-            }
+    if let Some(
+        Commands::Scale { output, .. }
+        | Commands::Simplify { output, .. }
+        | Commands::Center { output, .. }
+        | Commands::Deintersect { output, .. }
+        | Commands::Repair { output, .. }
+        | Commands::Mirror { output, .. }
+        | Commands::Flip { output }
+        | Commands::Fit { output, .. }
+        | Commands::Quantize { output, .. }
+        | Commands::Align { output }
+        | Commands::AutoOrient { output }
+        | Commands::Index { output },
+    ) = &cli.command
+        && output.as_deref() == Some(Path::new("-"))
+    {
+        ui::reserve_stdout();
+    }
+    if let Some(
+        Commands::Convert { output, .. }
+        | Commands::Sdf { output, .. }
+        | Commands::Sample { output, .. }
+        | Commands::Slice { output, .. }
+        | Commands::BoxMesh { output }
+        | Commands::Thickness { output },
+    ) = &cli.command
+        && output == Path::new("-")
+    {
+        ui::reserve_stdout();
+    }
+
+    let parse_start = Instant::now();
+    let check_normals = matches!(
+        cli.command,
+        Some(Commands::Stats {
+            check_normals: true,
+            ..
         })
-        .ok_or_else(|| anyhow::anyhow!("unsupported file format"))?;
+    );
+    let (format, mut mesh, duplicate_vertices, report, wrong_normals) = load(
+        &cli.input,
+        cli.mmap,
+        cli.format,
+        max_triangles,
+        check_normals,
+    )?;
+    ui::print_verbose(
+        "Parsed",
+        &format!(
+            "{} vertices, {} faces in {:.2?}",
+            mesh.vertices.len(),
+            mesh.faces.len(),
+            parse_start.elapsed()
+        ),
+    );
 
-    let mut mesh = match format {
-        model::Format::STL => StlCodec.parse(&buffer)?,
-        model::Format::OBJ => ObjCodec.parse(&buffer)?,
-    };
-    mesh.weld();
+    if cli.sanitize {
+        let vertices = mesh.vertices.len();
+        let removed_faces = mesh.sanitize();
+        let removed_vertices = vertices - mesh.vertices.len();
+        if removed_vertices > 0 {
+            ui::print_warn(&format!(
+                "removed {} non finite vertices and {} faces using them.",
+                removed_vertices, removed_faces
+            ));
+        }
+    }
+
+    mesh.unit = cli.units.map(Unit::from);
+    if let Some(unit) = cli.to_units.map(Unit::from) {
+        let from = mesh.unit.unwrap_or(Unit::Mm);
+        let factor = calculate::convert_units(&mut mesh, unit);
+        ui::print_info(
+            "Units",
+            &format!("{} -> {} (x{})", from.as_str(), unit.as_str(), factor),
+        );
+    }
 
-    let command = cli.command.unwrap_or(Commands::Stats);
+    let command = cli.command.unwrap_or(Commands::Stats {
+        check_normals: false,
+        by_group: false,
+    });
+    let compute_start = Instant::now();
+    let write_options = WriteOptions {
+        precision: cli.precision,
+        fan: cli.fan,
+    };
 
     match command {
         Commands::Diagonal => {
             let diagonal = calculate::diagonal(&mesh)?;
             ui::print_kv("Diagonal", format!("{:.4}", diagonal));
         }
-        Commands::Volume => {
-            let volume = calculate::volume(&mesh);
-            ui::print_kv("Volume", format!("{:.4}", volume));
+        Commands::Dimensions => {
+            let dimensions = calculate::dimensions(&mesh)?;
+            ui::print_kv("Dimensions", format_dimensions(dimensions));
+        }
+        Commands::Height { layer_height } => {
+            let (min_z, max_z) = calculate::z_range(&mesh)?;
+            ui::print_kv("Min Z", format!("{:.4}", min_z));
+            ui::print_kv("Max Z", format!("{:.4}", max_z));
+            ui::print_kv("Height", format!("{:.4}", max_z - min_z));
+
+            if let Some(layer_height) = layer_height {
+                if !(layer_height > 0.0 && layer_height.is_finite()) {
+                    ui::print_error(&format!("invalid layer height {}", layer_height));
+                    std::process::exit(1);
+                }
+                // the tolerance keeps rounding errors from adding a layer, e.g. 0.6 / 0.2
+                let layers = ((max_z - min_z) as f64 / layer_height as f64 - 1e-6).ceil();
+                ui::print_kv("Layers", layers as u64);
+            }
+        }
+        Commands::Volume { signed } => {
+            let checked = calculate::volume_checked(&mesh);
+            let volume = if signed {
+                calculate::signed_volume(&mesh)
+            } else {
+                checked.volume
+            };
+            ui::print_kv(volume_label(checked.watertight), format!("{:.4}", volume));
         }
         Commands::Triangles => {
             let triangles = mesh.triangle_count();
-            ui::print_success(&format!("Parsed {} triangles", triangles));
+            if ui::is_quiet() {
+                ui::print_kv("Triangles", triangles);
+            } else {
+                ui::print_success(&format!("Parsed {} triangles", triangles));
+            }
         }
-        Commands::Stats => {
-            let diagonal = calculate::diagonal(&mesh)?;
-            let volume = calculate::volume(&mesh);
-            let triangles = mesh.triangle_count();
+        Commands::Stats {
+            check_normals,
+            by_group,
+        } => {
+            // an empty mesh has no bounding box, its statistics are all zero
+            let calculate::Measurements {
+                dimensions,
+                diagonal,
+                signed_volume,
+                volume,
+                triangles,
+                ..
+            } = match calculate::measure(&mesh) {
+                Ok(measurements) => measurements,
+                Err(MeshError::Empty) => calculate::Measurements::default(),
+                Err(err) => return Err(err.into()),
+            };
 
             ui::print_section("Statistics");
             ui::print_kv("File", cli.input.display());
             ui::print_kv("Format", format!("{:?}", format));
             ui::print_kv("Triangles", triangles);
+            ui::print_kv("Vertices", mesh.vertices.len());
+            if let Some(unit) = mesh.unit {
+                ui::print_kv("Units", unit.as_str());
+            }
+            if duplicate_vertices > 0 {
+                ui::print_kv(
+                    "Duplicates",
+                    format!(
+                        "{} of {} stored vertices",
+                        duplicate_vertices,
+                        duplicate_vertices + mesh.vertices.len()
+                    ),
+                );
+            }
             ui::print_kv("Diagonal", format!("{:.4}", diagonal));
-            ui::print_kv("Volume", format!("{:.4}", volume));
+            ui::print_kv("Dimensions", format_dimensions(dimensions));
+            ui::print_kv(
+                volume_label(triangles == 0 || mesh.is_watertight()),
+                format!("{:.4}", volume),
+            );
+
+            if !mesh.materials.is_empty() {
+                ui::print_section("Materials");
+                for material in &mesh.materials {
+                    let [r, g, b] = material.diffuse;
+                    ui::print_kv(&material.name, format!("{:.3} {:.3} {:.3}", r, g, b));
+                }
+            }
+
+            if by_group {
+                for group in mesh.group_stats() {
+                    let kind = match group.kind {
+                        model::GroupKind::Object => "Object",
+                        model::GroupKind::Group => "Group",
+                    };
+                    ui::print_section(&format!("{}: {}", kind, group.name));
+                    ui::print_kv("Triangles", group.triangles);
+                    ui::print_kv("Area", format!("{:.4}", group.area));
+                    match group.volume {
+                        Some(volume) => ui::print_kv("Volume", format!("{:.4}", volume)),
+                        None => ui::print_kv("Volume", "not closed"),
+                    }
+                }
+            }
 
-            warn_topology(&mesh);
-            warn_units(cli.input.to_str().unwrap(), volume, diagonal);
+            if check_normals {
+                check_stored_normals(wrong_normals, triangles);
+            }
+
+            if triangles == 0 {
+                ui::print_newline();
+                ui::print_warn("the mesh is empty, it has no triangles.");
+            } else {
+                if signed_volume < 0.0 {
+                    ui::print_warn(
+                        "the signed volume is negative, the mesh may have inverted normals (try `flip`).",
+                    );
+                }
+                warn_topology(&mesh);
+                // the guess is only needed when the unit was not given
+                if mesh.unit.is_none() {
+                    warn_units(cli.input.to_str().unwrap(), volume, diagonal);
+                }
+            }
         }
         Commands::Scale {
             target_diagonal,
+            pivot,
             output,
         } => {
             let diagonal = calculate::diagonal(&mesh)?;
@@ -141,36 +923,1021 @@ fn main() -> anyhow::Result<()> {
                 &format!("{:.4} -> {:.4}", diagonal, target_diagonal),
             );
 
-            calculate::scale(&mut mesh, target_diagonal)?;
-
-            let output_path = match output {
-                Some(p) => p,
-                None => {
-                    let stem = cli
-                        .input
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("output");
-                    let ext = cli
-                        .input
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("stl");
-                    cli.input.with_file_name(format!("{}_scaled.{}", stem, ext))
+            calculate::scale_with_pivot(&mut mesh, target_diagonal, pivot.into())?;
+
+            ui::print_success("Scaled model processed.");
+            save(
+                &format,
+                &output_path(&cli.input, output, "scaled"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Convert {
+            output,
+            points,
+            triangulate,
+            color_components,
+        } => {
+            if triangulate {
+                triangulation::triangulate_mesh(&mut mesh);
+            }
+            if color_components {
+                let components = calculate::color_components(&mut mesh);
+                ui::print_info("Components", &components.to_string());
+            }
+
+            let is_xyz = output
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("xyz"));
+            if points || is_xyz {
+                save_with(&output, |writer| PointCloudCodec.write_to(writer, &mesh))?;
+            } else if output == Path::new("-") {
+                save(&format, &output, &mesh, write_options)?;
+            } else {
+                let target = output
+                    .to_str()
+                    .and_then(model::Format::from_name)
+                    .ok_or(MeshError::UnsupportedFormat)?;
+                save(&target, &output, &mesh, write_options)?;
+            }
+        }
+        Commands::Deintersect { output } => {
+            let interior: HashSet<usize> = mesh.interior_faces().into_iter().collect();
+            ui::print_info("Interior faces", &interior.len().to_string());
+
+            let mut index = 0;
+            mesh.retain_faces(|_| {
+                index += 1;
+                !interior.contains(&(index - 1))
+            });
+
+            save(
+                &format,
+                &output_path(&cli.input, output, "deintersected"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Repair {
+            tolerance,
+            max_hole_edges,
+            no_weld,
+            no_degenerate,
+            no_duplicates,
+            no_unused,
+            no_orient,
+            no_fill_holes,
+            output,
+        } => {
+            ui::print_section("Repair");
+            if !no_weld {
+                let tolerance = match tolerance {
+                    Some(tolerance) => tolerance,
+                    None => calculate::diagonal(&mesh)? * 1e-6,
+                };
+                let merged = repair::weld_vertices(&mut mesh, tolerance);
+                ui::print_kv("Welded vertices", merged);
+            }
+            if !no_degenerate {
+                let removed = repair::remove_degenerate_faces(&mut mesh);
+                ui::print_kv("Degenerate faces", removed);
+            }
+            if !no_duplicates {
+                let removed = repair::remove_duplicate_faces(&mut mesh);
+                ui::print_kv("Duplicate faces", removed);
+            }
+            if !no_unused {
+                let removed = repair::remove_unused_vertices(&mut mesh);
+                ui::print_kv("Unused vertices", removed);
+            }
+            if !no_orient {
+                let reversed = repair::orient_faces(&mut mesh);
+                ui::print_kv("Reversed faces", reversed);
+            }
+            if !no_fill_holes {
+                let filled = repair::fill_holes(&mut mesh, max_hole_edges);
+                ui::print_kv("Filled holes", filled);
+            }
+
+            if mesh.is_watertight() {
+                ui::print_success("The repaired mesh is watertight.");
+            } else {
+                ui::print_warn(&format!(
+                    "the repaired mesh is still not watertight ({} boundary edges, {} non-manifold edges).",
+                    mesh.boundary_edges().len(),
+                    mesh.non_manifold_edges().len()
+                ));
+            }
+            save(
+                &format,
+                &output_path(&cli.input, output, "repaired"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Simplify {
+            target,
+            ratio,
+            output,
+        } => {
+            let before = mesh.triangle_count();
+            mesh = match (target, ratio) {
+                (Some(target), _) => simplify(&mesh, target),
+                (None, Some(ratio)) => {
+                    if !(ratio > 0.0 && ratio <= 1.0) {
+                        ui::print_error(&format!("the ratio {} is not in 0..1", ratio));
+                        std::process::exit(1);
+                    }
+                    simplify_ratio(&mesh, ratio)
                 }
+                (None, None) => unreachable!("clap requires a target or a ratio"),
             };
 
-            ui::print_success("Scaled model processed.");
-            ui::print_info("Saving to", &format!("{:?}", output_path));
+            ui::print_info(
+                "Simplified",
+                &format!("{} -> {} triangles", before, mesh.triangle_count()),
+            );
+            save(
+                &format,
+                &output_path(&cli.input, output, "simplified"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::CrossSection { z } => {
+            let area = calculate::cross_section_area(&mesh, z);
+            ui::print_kv("Cross section area", format!("{:.4}", area));
+        }
+        Commands::Footprint { axis } => {
+            let area = calculate::projected_area(&mesh, axis.into());
+            ui::print_kv("Footprint area", format!("{:.4}", area));
+        }
+        Commands::ThinWalls { thickness } => {
+            if !(thickness > 0.0 && thickness.is_finite()) {
+                ui::print_error(&format!("invalid thickness {}", thickness));
+                std::process::exit(1);
+            }
+
+            let faces = calculate::thin_wall_faces(&mesh, thickness);
+            let areas = mesh.face_areas();
+            let area = faces.iter().fold(0.0, |area, &face| area + areas[face]);
+
+            ui::print_kv("Thin faces", faces.len());
+            ui::print_kv("Thin area", format!("{:.4}", area));
+            if !faces.is_empty() {
+                ui::print_warn(&format!(
+                    "{} faces are on walls thinner than {}.",
+                    faces.len(),
+                    thickness
+                ));
+            }
+        }
+        Commands::Thickness { output } => {
+            if output != Path::new("-")
+                && !matches!(
+                    output.to_str().and_then(model::Format::from_name),
+                    Some(model::Format::OBJ)
+                )
+            {
+                ui::print_error("only OBJ files store vertex colors, use an .obj output path.");
+                std::process::exit(1);
+            }
+            if !mesh.is_watertight() {
+                ui::print_warn("the mesh is not watertight, the thickness may be wrong.");
+            }
+
+            let thickness = calculate::wall_thickness_per_vertex(&mesh);
+            let finite: Vec<f32> = thickness
+                .iter()
+                .copied()
+                .filter(|t| t.is_finite())
+                .collect();
+            if let (Some(min), Some(max)) = (
+                finite.iter().copied().reduce(f32::min),
+                finite.iter().copied().reduce(f32::max),
+            ) {
+                let mean = finite.iter().fold(0.0, |sum, &t| sum + t as f64) / finite.len() as f64;
+                ui::print_kv("Min thickness", format!("{:.4}", min));
+                ui::print_kv("Max thickness", format!("{:.4}", max));
+                ui::print_kv("Mean thickness", format!("{:.4}", mean));
+            }
+            if finite.len() < thickness.len() {
+                ui::print_warn(&format!(
+                    "no opposite wall was found for {} vertices.",
+                    thickness.len() - finite.len()
+                ));
+            }
+
+            mesh.colors = calculate::thickness::thickness_colors(&thickness);
+            save(&model::Format::OBJ, &output, &mesh, write_options)?;
+        }
+        Commands::NormalsAudit { tolerance } => {
+            let deviations = mesh.normal_deviations();
+            match mesh.normal_deviation() {
+                Some((mean, max)) => {
+                    let within = deviations.iter().filter(|&&d| d <= tolerance).count();
+                    ui::print_kv("Stored normals", deviations.len());
+                    ui::print_kv(
+                        "Within tolerance",
+                        format!(
+                            "{:.1}% ({} of {})",
+                            within as f64 / deviations.len() as f64 * 100.0,
+                            within,
+                            deviations.len()
+                        ),
+                    );
+                    ui::print_kv("Mean deviation", format!("{:.2}°", mean));
+                    ui::print_kv("Max deviation", format!("{:.2}°", max));
+                }
+                None => ui::print_plain("no stored normals"),
+            }
+        }
+        Commands::Inertia { density } => {
+            let tensor = calculate::inertia_tensor(&mesh, density)?;
+
+            ui::print_section("Inertia Tensor");
+            for row in tensor.row_iter() {
+                ui::print_plain(&format!(
+                    "{:>14.6} {:>14.6} {:>14.6}",
+                    row[0], row[1], row[2]
+                ));
+            }
+
+            let mut moments: Vec<f64> = tensor.symmetric_eigenvalues().iter().copied().collect();
+            moments.sort_by(f64::total_cmp);
+            ui::print_section("Principal Moments");
+            for (i, moment) in moments.iter().enumerate() {
+                ui::print_kv(&format!("I{}", i + 1), format!("{:.6}", moment));
+            }
+        }
+        Commands::Batch { .. } => unreachable!("handled before loading the input"),
+        Commands::Validate { strict } => {
+            let code = validate(&cli.input, &mesh, strict);
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Edges { by_group } => {
+            ui::print_kv("Edge Length", format!("{:.4}", mesh.total_edge_length()));
+
+            if by_group {
+                let groups = mesh.edge_length_by_group();
+                if groups.is_empty() {
+                    ui::print_warn("the mesh has no groups.");
+                } else {
+                    ui::print_section("Groups");
+                    for (name, length) in groups {
+                        ui::print_kv(&name, format!("{:.4}", length));
+                    }
+                }
+            }
+        }
+        Commands::Fit { x, y, z, output } => {
+            let factor = calculate::fit_within(&mut mesh, model::Vec3(x, y, z))?;
+            if factor < 1.0 {
+                ui::print_success(&format!("Scaled model by {:.4} to fit.", factor));
+            } else {
+                ui::print_success("The model already fits, it was not scaled.");
+            }
+
+            save(
+                &format,
+                &output_path(&cli.input, output, "fitted"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Valence => {
+            let mut histogram: Vec<(u32, usize)> = mesh.valence_histogram().into_iter().collect();
+            histogram.sort_unstable();
+            let max = histogram.iter().map(|&(_, count)| count).max().unwrap_or(0);
+
+            ui::print_section("Vertex Valences");
+            for (valence, count) in histogram {
+                ui::print_bar(&format!("{:>4}", valence), count, max);
+            }
+            ui::print_kv("Irregular", mesh.irregular_vertex_count());
+        }
+        Commands::Index { output } => {
+            let indexed = mesh.to_indexed();
+
+            ui::print_section("Indexed Mesh");
+            ui::print_kv("Vertices", indexed.vertices.len());
+            ui::print_kv("Triangles", indexed.triangle_count());
+            ui::print_kv("Indices", indexed.indices.len() * 3);
+            ui::print_kv("Buffer size", format!("{} bytes", indexed.buffer_size()));
+
+            if let Some(output) = output {
+                let target = match output.to_str().and_then(model::Format::from_name) {
+                    Some(target) => target,
+                    None if output == Path::new("-") => format,
+                    None => return Err(MeshError::UnsupportedFormat.into()),
+                };
+                save_with(&output, |writer| match target {
+                    model::Format::OBJ => indexed.write_obj(writer),
+                    model::Format::STL => indexed.write_stl(writer),
+                    _ => Err(MeshError::UnsupportedFormat),
+                })?;
+            }
+        }
+        Commands::Histogram { bins } => {
+            let histogram = calculate::area_histogram(&mesh, bins);
+            let max = histogram
+                .iter()
+                .map(|&(_, _, count)| count)
+                .max()
+                .unwrap_or(0);
+
+            ui::print_section("Triangle Areas");
+            for (low, high, count) in histogram {
+                ui::print_bar(&format!("{:>12.6} - {:<12.6}", low, high), count, max);
+            }
+        }
+        Commands::Sdf { resolution, output } => {
+            let (samples, min, max) = sdf::sample_sdf(&mesh, resolution)?;
+            ui::print_kv("Grid", format!("{0} × {0} × {0}", resolution));
+            ui::print_kv("Min", format!("{:.4} {:.4} {:.4}", min.0, min.1, min.2));
+            ui::print_kv("Max", format!("{:.4} {:.4} {:.4}", max.0, max.1, max.2));
+
+            let is_npy = output
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("npy"));
+            if is_npy {
+                save_with(&output, |writer| {
+                    sdf::write_npy(writer, &samples, resolution)
+                })?;
+            } else {
+                save_with(&output, |writer| sdf::write_raw(writer, &samples))?;
+            }
+        }
+        Commands::Slice {
+            layer_height,
+            output,
+        } => {
+            let layers = slice::slice_layers(&mesh, layer_height)?;
+            let loops = layers.iter().fold(0, |sum, (_, loops)| sum + loops.len());
+            ui::print_kv("Layers", layers.len());
+            ui::print_kv("Contours", loops);
+            let empty = layers.iter().filter(|(_, loops)| loops.is_empty()).count();
+            if empty > 0 {
+                ui::print_warn(&format!(
+                    "{} layers have no closed contour, the mesh may have holes.",
+                    empty
+                ));
+            }
+
+            let is_svg = output
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+            if is_svg {
+                save_with(&output, |writer| {
+                    slice::write_svg(writer, &layers, cli.precision)
+                })?;
+            } else {
+                save_with(&output, |writer| {
+                    slice::write_polylines(writer, &layers, cli.precision)
+                })?;
+            }
+        }
+        Commands::Components => {
+            let watertight = mesh.component_watertightness();
+            let solid = watertight.iter().filter(|&&closed| closed).count();
+            ui::print_plain(&format!(
+                "{} {}: {} solid, {} open",
+                watertight.len(),
+                if watertight.len() == 1 {
+                    "component"
+                } else {
+                    "components"
+                },
+                solid,
+                watertight.len() - solid
+            ));
+        }
+        Commands::Voxelize { size } => {
+            let grid = voxel::voxelize(&mesh, size)?;
+            let [nx, ny, nz] = grid.dims;
+
+            ui::print_kv("Grid", format!("{} × {} × {}", nx, ny, nz));
+            ui::print_kv("Filled", grid.filled_count());
+            ui::print_kv("Volume", format!("{:.4}", grid.volume()));
+            ui::print_kv("Mesh Volume", format!("{:.4}", calculate::volume(&mesh)));
+        }
+        Commands::Sample {
+            count,
+            output,
+            seed,
+        } => {
+            let mut points = Mesh::new();
+            points.vertices = calculate::sample_surface(&mesh, count, seed);
+            if points.vertices.len() < count {
+                ui::print_warn("the mesh has no surface area to sample.");
+            }
+
+            save_with(&output, |writer| PointCloudCodec.write_to(writer, &points))?;
+        }
+        Commands::Quantize { grid, output } => {
+            let vertices = mesh.vertices.len();
+            mesh.quantize(grid)?;
+
+            ui::print_success(&format!(
+                "Quantized model to a grid of {}, {} -> {} vertices.",
+                grid,
+                vertices,
+                mesh.vertices.len()
+            ));
+            save(
+                &format,
+                &output_path(&cli.input, output, "quantized"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Watertight => {
+            if mesh.is_watertight() {
+                ui::print_success("The mesh is watertight.");
+            } else {
+                let boundary_edges = mesh.boundary_edges();
+                ui::print_error(&format!(
+                    "the mesh is not watertight ({} boundary edges).",
+                    boundary_edges.len()
+                ));
+                std::process::exit(1);
+            }
+        }
+        Commands::Center { ground, output } => {
+            if ground {
+                calculate::place_on_ground(&mut mesh)?;
+                ui::print_success("Centered model on X/Y and placed it on Z = 0.");
+            } else {
+                calculate::center(&mut mesh)?;
+                ui::print_success("Centered model at the origin.");
+            }
+
+            save(
+                &format,
+                &output_path(&cli.input, output, "centered"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Mirror { axis, output } => {
+            let axis = Axis::from(axis);
+            calculate::mirror(&mut mesh, axis);
+
+            ui::print_success(&format!("Mirrored model across the {:?} axis.", axis));
+            save(
+                &format,
+                &output_path(&cli.input, output, "mirrored"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::BoxMesh { output } => {
+            let bounding_box = calculate::bounding_box_mesh(&mesh)?;
+
+            // the format of the output path, the input format for stdout
+            let target = match output.to_str().and_then(model::Format::from_name) {
+                Some(target) => target,
+                None if output == Path::new("-") => format,
+                None => return Err(MeshError::UnsupportedFormat.into()),
+            };
+            ui::print_success("Built the bounding box of the model.");
+            save(&target, &output, &bounding_box, write_options)?;
+        }
+        Commands::Align { output } => {
+            calculate::align_to_principal_axes(&mut mesh)?;
+
+            ui::print_success("Aligned the principal axes of the model with X, Y and Z.");
+            save(
+                &format,
+                &output_path(&cli.input, output, "aligned"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::AutoOrient { output } => {
+            let footprint = calculate::projected_area(&mesh, Axis::Z);
+            let (angle_x, angle_y) = calculate::minimize_footprint(&mesh);
+            calculate::rotate(&mut mesh, angle_x, angle_y)?;
+
+            ui::print_info(
+                "Rotation",
+                &format!(
+                    "{:.0}° about X, {:.0}° about Y",
+                    angle_x.to_degrees(),
+                    angle_y.to_degrees()
+                ),
+            );
+            ui::print_info(
+                "Footprint",
+                &format!(
+                    "{:.4} -> {:.4}",
+                    footprint,
+                    calculate::projected_area(&mesh, Axis::Z)
+                ),
+            );
+            ui::print_success("Oriented the model for the smallest footprint.");
+            save(
+                &format,
+                &output_path(&cli.input, output, "oriented"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Flip { output } => {
+            mesh.flip();
+
+            ui::print_success("Flipped the orientation of the model.");
+            save(
+                &format,
+                &output_path(&cli.input, output, "flipped"),
+                &mesh,
+                write_options,
+            )?;
+        }
+        Commands::Diff {
+            other,
+            tolerance,
+            samples,
+        } => {
+            if !(tolerance >= 0.0 && tolerance.is_finite()) {
+                ui::print_error(&format!("Invalid tolerance: {}", tolerance));
+                std::process::exit(1);
+            }
+
+            let (_, other_mesh, _, _, _) = load(&other, cli.mmap, None, max_triangles, false)?;
+            if !diff(&other, &mesh, &other_mesh, tolerance, samples) {
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let compute_time = compute_start.elapsed();
+    ui::print_verbose("Computed", &format!("in {:.2?}", compute_time));
+    if cli.timings {
+        ui::print_info("Parse time", &format!("{:.2?}", report.parse_time));
+        ui::print_info("Weld time", &format!("{:.2?}", report.weld_time));
+        ui::print_info("Compute time", &format!("{:.2?}", compute_time));
+    }
+
+    anyhow::Ok(())
+}
+
+// same as `mesh_rs::load_with_report`, optionally memory-mapping the file or reading
+// stdin for `-` and parsing it as `forced` instead of detecting the format
+// binary STL files may declare up to `max_triangles`
+// returns the number of duplicate vertices merged by welding too, and with `check_normals`
+// the number of wrong normals stored in an STL file (see `stl::count_wrong_normals`)
+fn load(
+    path: &Path,
+    mmap: bool,
+    forced: Option<model::Format>,
+    max_triangles: u32,
+    check_normals: bool,
+) -> anyhow::Result<(model::Format, Mesh, usize, LoadReport, Option<usize>)> {
+    let read_start = Instant::now();
+    let mapped;
+    let mut owned = Vec::new();
+    let bytes: &[u8] = if path == Path::new("-") {
+        std::io::stdin().lock().read_to_end(&mut owned)?;
+        &owned
+    } else if mmap {
+        let file = OpenOptions::new().read(true).open(path)?;
+        // SAFETY: the mapping is read only and dropped once the mesh is parsed,
+        // the user is told not to modify the file while it is mapped
+        mapped = unsafe { Mmap::map(&file)? };
+        &mapped
+    } else {
+        OpenOptions::new()
+            .read(true)
+            .open(path)?
+            .read_to_end(&mut owned)?;
+        &owned
+    };
+    let read_time = read_start.elapsed();
+
+    let hint = path.to_str().and_then(model::Format::from_name);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let (mesh, format, mut report) =
+        mesh_rs::load_bytes_with_report(bytes, hint, forced, Some(base_dir), max_triangles)?;
+    report.parse_time += read_time;
+
+    let weld = report.weld;
+    ui::print_verbose(
+        "Welded",
+        &format!(
+            "{} -> {} vertices, {} faces remapped",
+            weld.vertices_before, weld.vertices_after, weld.faces_remapped
+        ),
+    );
+    // STL stores every corner of every triangle, but the binary parser welds while reading
+    let stored_vertices = match format {
+        model::Format::STL => mesh.faces.iter().map(|face| face.v.len()).sum(),
+        _ => weld.vertices_before,
+    };
+    let duplicate_vertices = stored_vertices.saturating_sub(mesh.vertices.len());
+
+    // the normals are read from the bytes already in memory, stdin can not be read twice
+    let wrong_normals = match format {
+        model::Format::STL if check_normals => Some(model::stl::count_wrong_normals(
+            &model::decompress(bytes)?,
+            &mesh,
+        )?),
+        _ => None,
+    };
+
+    anyhow::Ok((format, mesh, duplicate_vertices, report, wrong_normals))
+}
+
+// value parser of --format, takes the same names as the file extensions
+fn parse_format(name: &str) -> Result<model::Format, String> {
+    model::Format::from_name(name).ok_or_else(|| format!("unsupported format `{}`", name))
+}
+
+// the volume of a mesh with holes is only an approximation
+fn volume_label(watertight: bool) -> &'static str {
+    if watertight {
+        "Volume"
+    } else {
+        "Volume (approximate, mesh has holes)"
+    }
+}
+
+// width x height x depth
+fn format_dimensions(dimensions: model::Vec3) -> String {
+    format!(
+        "{:.4} × {:.4} × {:.4}",
+        dimensions.0, dimensions.1, dimensions.2
+    )
+}
+
+// share of wrong stored normals above which the exporter is considered broken
+const WRONG_NORMALS_WARN_RATIO: f64 = 0.01;
+
+// `mismatched` is the number of wrong stored normals, none for formats other than STL,
+// the only supported format storing normals next to the winding
+fn check_stored_normals(mismatched: Option<usize>, triangles: usize) {
+    let Some(mismatched) = mismatched else {
+        ui::print_warn("only STL files store facet normals, skipping the normal check.");
+        return;
+    };
+    ui::print_kv("Wrong Normals", mismatched);
+
+    if mismatched as f64 > triangles as f64 * WRONG_NORMALS_WARN_RATIO {
+        ui::print_warn(&format!(
+            "{} of {} stored normals disagree with the winding by more than {} degrees.",
+            mismatched,
+            triangles,
+            model::stl::NORMAL_TOLERANCE_DEGREES
+        ));
+        ui::print_warn("the exporter may have written bad normals.");
+    }
+}
+
+// exit codes of the validate command
+const EXIT_NON_MANIFOLD: i32 = 2;
+const EXIT_HOLES: i32 = 3;
+const EXIT_DEGENERATE: i32 = 4;
+const EXIT_WARNINGS: i32 = 5;
+
+// prints a validation summary and returns the exit code of the most severe failure
+fn validate(input: &Path, mesh: &Mesh, strict: bool) -> i32 {
+    let (mut non_manifold_edges, mut boundary_edges) = (0, 0);
+    for count in mesh.topology().into_values() {
+        match count {
+            1 => boundary_edges += 1,
+            2 => {}
+            _ => non_manifold_edges += 1,
+        }
+    }
+    let degenerate_faces = repair::count_degenerate_faces(mesh);
+    let duplicate_faces = repair::count_duplicate_faces(mesh);
+    let too_small = calculate::volume(mesh) <= MIN_MM_VALUE;
+
+    ui::print_section("Validation");
+    ui::print_kv("File", input.display());
+    ui::print_kv("Non-manifold", non_manifold_edges);
+    ui::print_kv("Boundary edges", boundary_edges);
+    ui::print_kv("Degenerate", degenerate_faces);
+    ui::print_kv("Duplicates", duplicate_faces);
+
+    let failures = [
+        (
+            non_manifold_edges > 0,
+            EXIT_NON_MANIFOLD,
+            "non-manifold edges",
+        ),
+        (boundary_edges > 0, EXIT_HOLES, "holes"),
+        (degenerate_faces > 0, EXIT_DEGENERATE, "degenerate faces"),
+    ];
+    if let Some((_, code, reason)) = failures.iter().find(|(failed, _, _)| *failed) {
+        ui::print_error(&format!("validation failed: the mesh has {}.", reason));
+        return *code;
+    }
+
+    let warnings = [
+        (duplicate_faces > 0, "the mesh has duplicate faces"),
+        (
+            too_small,
+            "the mesh is very small, it may not be in millimeters",
+        ),
+    ];
+    let mut warned = false;
+    for (_, warning) in warnings.iter().filter(|(warn, _)| *warn) {
+        warned = true;
+        if strict {
+            ui::print_error(&format!("validation failed: {}.", warning));
+        } else {
+            ui::print_warn(&format!("{}.", warning));
+        }
+    }
+    if strict && warned {
+        return EXIT_WARNINGS;
+    }
 
-            match format {
-                model::Format::STL => StlCodec.write(&output_path, &mesh)?,
-                model::Format::OBJ => ObjCodec.write(&output_path, &mesh)?,
+    ui::print_success("The mesh is valid.");
+    0
+}
+
+// prints how the two meshes differ and returns true when they are equal within the tolerance
+fn diff(other: &Path, mesh: &Mesh, other_mesh: &Mesh, tolerance: f32, samples: usize) -> bool {
+    let area = |mesh: &Mesh| mesh.face_areas().iter().fold(0.0, |sum, area| sum + area);
+
+    ui::print_section("Diff");
+    ui::print_kv("Other", other.display());
+    let counts = [
+        (
+            "Vertices",
+            "vertex",
+            mesh.vertices.len(),
+            other_mesh.vertices.len(),
+        ),
+        (
+            "Triangles",
+            "triangle",
+            mesh.triangle_count(),
+            other_mesh.triangle_count(),
+        ),
+    ];
+    for (label, noun, count, other_count) in counts {
+        ui::print_kv(label, format!("{} vs {}", count, other_count));
+        if count != other_count {
+            ui::print_warn(&format!("the {} counts differ.", noun));
+        }
+    }
+
+    let mut differences = Vec::new();
+
+    // largest distance between the corresponding corners of the bounding boxes
+    let bounds_distance = match (mesh.bounds(), other_mesh.bounds()) {
+        (Ok((min, max)), Ok((other_min, other_max))) => {
+            (min - other_min).length().max((max - other_max).length())
+        }
+        (Err(_), Err(_)) => 0.0,
+        _ => f32::INFINITY,
+    };
+    ui::print_kv("Bounds", format!("{:.6}", bounds_distance));
+    if bounds_distance > tolerance {
+        differences.push("bounds");
+    }
+
+    // a surface moved by the tolerance changes the volume by about the tolerance times its area
+    let (volume, other_volume) = (calculate::volume(mesh), calculate::volume(other_mesh));
+    let volume_difference = (volume - other_volume).abs();
+    ui::print_kv("Volume", format!("{:.4} vs {:.4}", volume, other_volume));
+    if volume_difference > tolerance as f64 * area(mesh).max(area(other_mesh)) {
+        differences.push("volume");
+    }
+
+    if samples > 0 {
+        let distance = query::hausdorff_distance(mesh, other_mesh, samples);
+        ui::print_kv("Hausdorff", format!("{:.6}", distance));
+        if distance > tolerance {
+            differences.push("surfaces");
+        }
+    }
+
+    if differences.is_empty() {
+        ui::print_success("The meshes are equal within the tolerance.");
+        true
+    } else {
+        ui::print_error(&format!(
+            "the meshes differ ({}) by more than {}.",
+            differences.join(", "),
+            tolerance
+        ));
+        false
+    }
+}
+
+struct BatchResult {
+    path: PathBuf,
+    format: model::Format,
+    triangles: usize,
+    diagonal: f32,
+    volume: f64,
+    watertight: bool,
+}
+
+fn batch(
+    dir: &Path,
+    recursive: bool,
+    json: bool,
+    mmap: bool,
+    forced: Option<model::Format>,
+    max_triangles: u32,
+) -> anyhow::Result<()> {
+    if !dir.is_dir() {
+        ui::print_error(&format!("Input is not a directory: {:?}", dir));
+        std::process::exit(1);
+    }
+
+    let mut files = Vec::new();
+    collect_mesh_files(dir, recursive, &mut files)?;
+    files.sort();
+
+    // files are independent, so they are loaded and measured in parallel
+    let results: Vec<BatchResult> = files
+        .into_par_iter()
+        .filter_map(|path| {
+            let result = load(&path, mmap, forced, max_triangles, false).and_then(
+                |(format, mesh, _, _, _)| {
+                    anyhow::Ok(BatchResult {
+                        triangles: mesh.triangle_count(),
+                        diagonal: calculate::diagonal(&mesh)?,
+                        volume: calculate::volume(&mesh),
+                        watertight: mesh.is_watertight(),
+                        path: path.clone(),
+                        format,
+                    })
+                },
+            );
+
+            result
+                .map_err(|err| ui::print_error(&format!("{}: {}", path.display(), err)))
+                .ok()
+        })
+        .collect();
+
+    if json {
+        let entries: Vec<serde_json::Value> = results
+            .iter()
+            .map(|result| {
+                serde_json::json!({
+                    "file": result.path.display().to_string(),
+                    "format": result.format.as_str(),
+                    "triangles": result.triangles,
+                    "diagonal": result.diagonal,
+                    "volume": result.volume,
+                    "watertight": result.watertight,
+                })
+            })
+            .collect();
+        ui::print_plain(&serde_json::to_string_pretty(&entries)?);
+
+        return anyhow::Ok(());
+    }
+
+    ui::print_section("Batch Statistics");
+    ui::print_bold(&format!(
+        "{:<40} {:<6} {:>10} {:>12} {:>14} {:>10}",
+        "File", "Format", "Triangles", "Diagonal", "Volume", "Watertight"
+    ));
+    for result in &results {
+        let file = result.path.strip_prefix(dir).unwrap_or(&result.path);
+        ui::print_plain(&format!(
+            "{:<40} {:<6} {:>10} {:>12.4} {:>14.4} {:>10}",
+            file.display(),
+            format!("{:?}", result.format),
+            result.triangles,
+            result.diagonal,
+            result.volume,
+            if result.watertight { "yes" } else { "no" }
+        ));
+    }
+
+    anyhow::Ok(())
+}
+
+// collects the files with a supported mesh extension
+fn collect_mesh_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                collect_mesh_files(&path, recursive, files)?;
             }
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(model::Format::from_name)
+            .is_some()
+        {
+            files.push(path);
+        }
+    }
+
+    anyhow::Ok(())
+}
+
+// default output path, <input_stem>_<suffix>.<ext> next to the input
+fn output_path(input: &Path, output: Option<PathBuf>, suffix: &str) -> PathBuf {
+    if let Some(output) = output {
+        return output;
+    }
 
-            ui::print_success("File saved successfully.");
+    // output is written uncompressed, model.stl.gz -> model_scaled.stl
+    let uncompressed = match input.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") => input.with_extension(""),
+        _ => input.to_path_buf(),
+    };
+    let stem = uncompressed
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let ext = uncompressed
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("stl");
+    input.with_file_name(format!("{}_{}.{}", stem, suffix, ext))
+}
+
+// how `save` writes meshes
+#[derive(Clone, Copy)]
+struct WriteOptions {
+    // number of decimals of text based formats, binary STL is always exact
+    precision: usize,
+    // split polygons written to STL into fans instead of ear clipping them
+    fan: bool,
+}
+
+fn save(
+    format: &model::Format,
+    path: &Path,
+    mesh: &Mesh,
+    options: WriteOptions,
+) -> anyhow::Result<()> {
+    // STL only stores triangles, polygons are split while writing
+    let fanned;
+    let mesh = match format {
+        model::Format::STL => {
+            let polygons = mesh.faces.iter().filter(|face| face.v.len() > 3).count();
+            if polygons > 0 {
+                ui::print_warn(&format!(
+                    "{} {} more than 3 vertices, STL only stores triangles so they are split {}.",
+                    polygons,
+                    if polygons == 1 {
+                        "face has"
+                    } else {
+                        "faces have"
+                    },
+                    if options.fan {
+                        "into fans (--fan)"
+                    } else {
+                        "with ear clipping"
+                    }
+                ));
+            }
+
+            if options.fan && polygons > 0 {
+                let mut copy = mesh.clone();
+                triangulation::fan_triangulate_mesh(&mut copy);
+                fanned = copy;
+                &fanned
+            } else {
+                mesh
+            }
         }
+        _ => mesh,
+    };
+
+    save_with(path, |writer| match format {
+        model::Format::OBJ => ObjCodec.write_to_with_precision(writer, mesh, options.precision),
+        _ => format.get_codec().write_to(writer, mesh),
+    })
+}
+
+// `-` writes the mesh to stdout so the output can be piped into other tools
+fn save_with(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), MeshError>,
+) -> anyhow::Result<()> {
+    if path == Path::new("-") {
+        // stdout is not translated on any platform, so binary STL is written as is
+        let mut writer = BufWriter::new(std::io::stdout().lock());
+        write(&mut writer)?;
+        writer.flush()?;
+
+        return anyhow::Ok(());
     }
 
+    ui::print_info("Saving to", &format!("{:?}", path));
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    ui::print_success("File saved successfully.");
+
     anyhow::Ok(())
 }
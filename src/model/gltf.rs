@@ -0,0 +1,294 @@
+// glTF 2.0 (JSON `.gltf` and binary `.glb`)
+// https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html
+//
+// only the geometry is imported: the POSITION accessor and the indices of every
+// TRIANGLES primitive, with each node's world transform applied.
+// writing is not supported yet.
+use std::{
+    io::Write,
+    path::{Component, Path},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use gltf::{Gltf, buffer::Source, mesh::Mode};
+use nalgebra::{Matrix4, Point3};
+
+use crate::model::{Face, Mesh, MeshCodec, MeshError, Vec3};
+
+pub const GLB_MAGIC: &[u8; 4] = b"glTF";
+
+pub struct GltfCodec;
+
+impl GltfCodec {
+    // same as `parse`, but buffers stored in separate files next to a `.gltf`
+    // are resolved relative to `base_dir`
+    pub fn parse_with_base(
+        &self,
+        bytes: &[u8],
+        base_dir: Option<&Path>,
+    ) -> Result<Mesh, MeshError> {
+        let mut gltf =
+            Gltf::from_slice(bytes).map_err(|err| MeshError::InvalidData(err.to_string()))?;
+        // the binary chunk of a `.glb`
+        let mut blob = gltf.blob.take();
+
+        let buffers = gltf
+            .buffers()
+            .map(|buffer| match buffer.source() {
+                Source::Bin => blob.take().ok_or(MeshError::Truncated),
+                Source::Uri(uri) => load_uri(uri, base_dir),
+            })
+            .collect::<Result<Vec<Vec<u8>>, MeshError>>()?;
+
+        let mut mesh = Mesh::default();
+        let mut scenes = gltf.scenes().peekable();
+        if scenes.peek().is_none() {
+            // without a scene graph there are no transforms to apply
+            for gltf_mesh in gltf.meshes() {
+                append_mesh(&mut mesh, &gltf_mesh, &buffers, &Matrix4::identity())?;
+            }
+        } else {
+            for scene in scenes {
+                for node in scene.nodes() {
+                    append_node(&mut mesh, &node, &buffers, &Matrix4::identity())?;
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+impl MeshCodec for GltfCodec {
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
+        self.parse_with_base(bytes, None)
+    }
+
+    fn write_to(&self, _writer: &mut dyn Write, _mesh: &Mesh) -> Result<(), MeshError> {
+        Err(MeshError::UnsupportedFormat)
+    }
+}
+
+// binary glTF starts with the `glTF` magic, JSON glTF has an `asset` object
+pub fn validate_bytes(bytes: &[u8]) -> bool {
+    if bytes.starts_with(GLB_MAGIC) {
+        return true;
+    }
+
+    let preview = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    preview.trim_start().starts_with('{') && preview.contains("\"asset\"")
+}
+
+fn append_node(
+    mesh: &mut Mesh,
+    node: &gltf::Node,
+    buffers: &[Vec<u8>],
+    parent: &Matrix4<f32>,
+) -> Result<(), MeshError> {
+    // column major, like nalgebra
+    let local = Matrix4::from(node.transform().matrix());
+    let world = parent * local;
+
+    if let Some(gltf_mesh) = node.mesh() {
+        append_mesh(mesh, &gltf_mesh, buffers, &world)?;
+    }
+
+    for child in node.children() {
+        append_node(mesh, &child, buffers, &world)?;
+    }
+
+    Ok(())
+}
+
+fn append_mesh(
+    mesh: &mut Mesh,
+    gltf_mesh: &gltf::Mesh,
+    buffers: &[Vec<u8>],
+    transform: &Matrix4<f32>,
+) -> Result<(), MeshError> {
+    for primitive in gltf_mesh.primitives() {
+        // points and lines have no surface
+        if primitive.mode() != Mode::Triangles {
+            continue;
+        }
+
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+        let Some(positions) = reader.read_positions() else {
+            continue;
+        };
+
+        let offset = mesh.vertices.len();
+        for [x, y, z] in positions {
+            let p = transform.transform_point(&Point3::new(x, y, z));
+            mesh.vertices.push(Vec3(p.x, p.y, p.z));
+        }
+        let count = mesh.vertices.len() - offset;
+
+        // non indexed primitives use every three consecutive vertices
+        let indices: Vec<usize> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+            None => (0..count).collect(),
+        };
+
+        for triangle in indices.chunks_exact(3) {
+            if let Some(&idx) = triangle.iter().find(|&&i| i >= count) {
+                return Err(MeshError::InvalidData(format!(
+                    "index {} out of range in mesh {}",
+                    idx,
+                    gltf_mesh.index()
+                )));
+            }
+
+            let mut face = Face::default();
+            face.v.extend(triangle.iter().map(|&i| offset + i));
+            mesh.faces.push(face);
+        }
+    }
+
+    Ok(())
+}
+
+// loads an embedded base64 data URI or a file relative to the `.gltf`
+// files outside the directory of the `.gltf` (absolute or through `..`) are refused
+fn load_uri(uri: &str, base_dir: Option<&Path>) -> Result<Vec<u8>, MeshError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| MeshError::InvalidData(String::from("unsupported data URI")))?;
+
+        return STANDARD
+            .decode(encoded)
+            .map_err(|err| MeshError::InvalidData(err.to_string()));
+    }
+
+    let base_dir = base_dir.ok_or_else(|| {
+        MeshError::InvalidData(format!("external buffer '{}' needs a base directory", uri))
+    })?;
+    let relative = Path::new(uri)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !relative {
+        return Err(MeshError::InvalidData(format!(
+            "external buffer '{}' is outside the directory of the file",
+            uri
+        )));
+    }
+
+    Ok(std::fs::read(base_dir.join(uri))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one triangle in the XY plane, moved up by its node
+    const JSON: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "translation": [0, 0, 5] }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }],
+        "buffers": [{ "byteLength": 44 BUFFER_URI }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 6 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+              "min": [0, 0, 0], "max": [1, 1, 0] },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ]
+    }"#;
+
+    fn buffer() -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for coordinate in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            buffer.extend(coordinate.to_le_bytes());
+        }
+        for index in [0u16, 1, 2] {
+            buffer.extend(index.to_le_bytes());
+        }
+        // chunks are padded to 4 bytes
+        buffer.resize(44, 0);
+        buffer
+    }
+
+    fn glb() -> Vec<u8> {
+        let mut json = JSON.replace(" BUFFER_URI", "").into_bytes();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let bin = buffer();
+
+        let mut glb = Vec::new();
+        glb.extend(GLB_MAGIC);
+        glb.extend(2u32.to_le_bytes());
+        glb.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(bin);
+        glb
+    }
+
+    fn assert_moved_triangle(mesh: &Mesh) {
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.faces[0].v.as_slice(), [0, 1, 2]);
+        assert_eq!(
+            mesh.vertices,
+            [
+                Vec3(0.0, 0.0, 5.0),
+                Vec3(1.0, 0.0, 5.0),
+                Vec3(0.0, 1.0, 5.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn glb_applies_the_node_transform() {
+        let glb = glb();
+        assert!(validate_bytes(&glb));
+        assert_moved_triangle(&GltfCodec.parse(&glb).unwrap());
+    }
+
+    #[test]
+    fn gltf_reads_an_embedded_buffer() {
+        let uri = format!(
+            r#", "uri": "data:application/octet-stream;base64,{}""#,
+            STANDARD.encode(buffer())
+        );
+        let gltf = JSON.replace(" BUFFER_URI", &uri);
+
+        assert!(validate_bytes(gltf.as_bytes()));
+        assert_moved_triangle(&GltfCodec.parse(gltf.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn external_buffer_needs_a_base_directory() {
+        let gltf = JSON.replace(" BUFFER_URI", r#", "uri": "triangle.bin""#);
+        assert!(matches!(
+            GltfCodec.parse(gltf.as_bytes()),
+            Err(MeshError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn external_buffer_outside_the_base_directory_is_refused() {
+        let dir = std::env::temp_dir().join(format!("mesh_rs-gltf-{}", std::process::id()));
+        let models = dir.join("models");
+        std::fs::create_dir_all(&models).unwrap();
+        std::fs::write(dir.join("triangle.bin"), buffer()).unwrap();
+
+        for uri in [
+            "../triangle.bin",
+            "models/../../triangle.bin",
+            "/etc/passwd",
+        ] {
+            let gltf = JSON.replace(" BUFFER_URI", &format!(r#", "uri": "{}""#, uri));
+            assert!(matches!(
+                GltfCodec.parse_with_base(gltf.as_bytes(), Some(&models)),
+                Err(MeshError::InvalidData(_))
+            ));
+        }
+    }
+}
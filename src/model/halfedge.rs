@@ -0,0 +1,272 @@
+// half-edge mesh for constant time adjacency queries
+// every triangle owns three half-edges, one per directed edge (in winding order).
+// the half-edge `3 * t + k` starts at the k-th corner of triangle t, so the next
+// half-edge of a triangle and its face never have to be stored.
+//
+// the mesh has to be an oriented manifold: every edge is used by at most two triangles
+// with opposite winding, and the triangles around a vertex form a single fan.
+// boundary edges (holes) are allowed, `opposite` returns None for them.
+use std::collections::HashMap;
+
+use crate::{
+    calculate::triangulation::triangulate_face,
+    model::{Mesh, MeshError},
+};
+
+#[derive(Debug, Clone)]
+pub struct HalfEdgeMesh {
+    // vertex indices of every triangle
+    triangles: Vec<[usize; 3]>,
+    // index of the mesh face every triangle was split from
+    source_faces: Vec<usize>,
+    // half-edge running the other way along the same edge, None on the boundary
+    opposites: Vec<Option<usize>>,
+    // one outgoing half-edge per vertex, None for unused vertices
+    // for vertices on the boundary it is the one without an opposite, so walking
+    // around the vertex from it visits every triangle
+    outgoing: Vec<Option<usize>>,
+}
+
+impl HalfEdgeMesh {
+    // splits the faces into triangles the same way `Mesh::triangles` does,
+    // triangles using a vertex more than once are skipped
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, MeshError> {
+        let mut triangles = Vec::with_capacity(mesh.triangle_count());
+        let mut source_faces = Vec::with_capacity(triangles.capacity());
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            for [a, b, c] in triangulate_face(&mesh.vertices, &face.v) {
+                if a == b || b == c || c == a {
+                    continue;
+                }
+                triangles.push([a, b, c]);
+                source_faces.push(face_index);
+            }
+        }
+
+        // directed edge -> half-edge
+        let mut edges = HashMap::<(usize, usize), usize>::with_capacity(triangles.len() * 3);
+        for (t, triangle) in triangles.iter().enumerate() {
+            for k in 0..3 {
+                let edge = (triangle[k], triangle[(k + 1) % 3]);
+                // a directed edge used twice means the edge has more than two triangles
+                // or its two triangles disagree on the winding
+                if edges.insert(edge, 3 * t + k).is_some() {
+                    return Err(MeshError::NonManifold(format!(
+                        "edge ({}, {}) is shared by more than two triangles or has inconsistent winding",
+                        edge.0, edge.1
+                    )));
+                }
+            }
+        }
+
+        let mut opposites = vec![None; triangles.len() * 3];
+        for (&(from, to), &h) in &edges {
+            opposites[h] = edges.get(&(to, from)).copied();
+        }
+
+        let mut outgoing: Vec<Option<usize>> = vec![None; mesh.vertices.len()];
+        // number of half-edges leaving every vertex
+        let mut valence = vec![0usize; mesh.vertices.len()];
+        for (h, &opposite) in opposites.iter().enumerate() {
+            let from = triangles[h / 3][h % 3];
+            valence[from] += 1;
+            if outgoing[from].is_none() || opposite.is_none() {
+                outgoing[from] = Some(h);
+            }
+        }
+
+        let halfedges = Self {
+            triangles,
+            source_faces,
+            opposites,
+            outgoing,
+        };
+
+        // a single fan around every vertex reaches all of its half-edges,
+        // two fans touching at a vertex (a bow tie) leave some of them out
+        for (v, &count) in valence.iter().enumerate() {
+            if count > 0 && halfedges.outgoing_halfedges(v).count() != count {
+                return Err(MeshError::NonManifold(format!(
+                    "vertex {} joins separate fans of triangles",
+                    v
+                )));
+            }
+        }
+
+        Ok(halfedges)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn halfedge_count(&self) -> usize {
+        self.opposites.len()
+    }
+
+    // vertex indices of the triangle
+    pub fn triangle(&self, triangle: usize) -> [usize; 3] {
+        self.triangles[triangle]
+    }
+
+    // index of the mesh face the triangle was split from
+    pub fn source_face(&self, triangle: usize) -> usize {
+        self.source_faces[triangle]
+    }
+
+    // triangle the half-edge belongs to
+    pub fn face(&self, halfedge: usize) -> usize {
+        halfedge / 3
+    }
+
+    // vertex the half-edge starts at
+    pub fn origin(&self, halfedge: usize) -> usize {
+        self.triangles[halfedge / 3][halfedge % 3]
+    }
+
+    // vertex the half-edge points to
+    pub fn target(&self, halfedge: usize) -> usize {
+        self.origin(self.next(halfedge))
+    }
+
+    // next half-edge of the same triangle
+    pub fn next(&self, halfedge: usize) -> usize {
+        halfedge - halfedge % 3 + (halfedge + 1) % 3
+    }
+
+    // previous half-edge of the same triangle
+    pub fn prev(&self, halfedge: usize) -> usize {
+        halfedge - halfedge % 3 + (halfedge + 2) % 3
+    }
+
+    // half-edge of the neighboring triangle running the other way, None on the boundary
+    pub fn opposite(&self, halfedge: usize) -> Option<usize> {
+        self.opposites[halfedge]
+    }
+
+    // half-edge going from `from` to `to`, if the edge exists in that direction
+    pub fn find_halfedge(&self, from: usize, to: usize) -> Option<usize> {
+        self.outgoing_halfedges(from)
+            .find(|&h| self.target(h) == to)
+    }
+
+    // half-edges leaving the vertex, in fan order
+    pub fn outgoing_halfedges(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.outgoing.get(vertex).copied().flatten();
+        let mut current = start;
+
+        std::iter::from_fn(move || {
+            let h = current?;
+            // the edge coming into the vertex, seen from the neighboring triangle,
+            // is the next edge leaving it
+            current = self
+                .opposite(self.prev(h))
+                .filter(|&next| Some(next) != start);
+            Some(h)
+        })
+    }
+
+    // triangles using the vertex, in fan order
+    pub fn faces_around_vertex(&self, vertex: usize) -> Vec<usize> {
+        self.outgoing_halfedges(vertex)
+            .map(|h| self.face(h))
+            .collect()
+    }
+
+    // vertices sharing an edge with the vertex (its one-ring), in fan order
+    pub fn vertices_around_vertex(&self, vertex: usize) -> Vec<usize> {
+        let mut last = None;
+        let mut ring: Vec<usize> = self
+            .outgoing_halfedges(vertex)
+            .inspect(|&h| last = Some(h))
+            .map(|h| self.target(h))
+            .collect();
+
+        // on the boundary the fan is open, the last triangle adds one more neighbor
+        if let Some(h) = last
+            && self.opposite(self.prev(h)).is_none()
+        {
+            ring.push(self.origin(self.prev(h)));
+        }
+
+        ring
+    }
+
+    // triangles sharing an edge with the triangle, boundary edges are left out
+    pub fn face_neighbors(&self, triangle: usize) -> Vec<usize> {
+        (3 * triangle..3 * triangle + 3)
+            .filter_map(|h| self.opposite(h))
+            .map(|h| self.face(h))
+            .collect()
+    }
+
+    // true when the vertex lies on a boundary edge
+    pub fn is_boundary_vertex(&self, vertex: usize) -> bool {
+        self.outgoing
+            .get(vertex)
+            .copied()
+            .flatten()
+            .is_some_and(|h| self.opposite(h).is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn cube_corner_has_a_closed_one_ring() {
+        let halfedges = HalfEdgeMesh::from_mesh(&fixtures::cube()).unwrap();
+        assert_eq!(halfedges.triangle_count(), 12);
+
+        let faces = halfedges.faces_around_vertex(0);
+        let ring = halfedges.vertices_around_vertex(0);
+        // a closed fan has as many triangles as neighbors
+        assert_eq!(faces.len(), ring.len());
+        let sources: BTreeSet<usize> = faces.iter().map(|&t| halfedges.source_face(t)).collect();
+        assert_eq!(sources, BTreeSet::from([0, 2, 5]));
+        // the three cube edges, plus the diagonals of the quads split at the corner
+        let ring: BTreeSet<usize> = ring.into_iter().collect();
+        assert!(ring.is_superset(&BTreeSet::from([1, 3, 4])));
+        assert!(ring.is_subset(&BTreeSet::from([1, 2, 3, 4, 5, 7])));
+        assert!(!halfedges.is_boundary_vertex(0));
+    }
+
+    #[test]
+    fn every_cube_triangle_has_three_neighbors() {
+        let halfedges = HalfEdgeMesh::from_mesh(&fixtures::cube()).unwrap();
+        for t in 0..halfedges.triangle_count() {
+            let neighbors = halfedges.face_neighbors(t);
+            assert_eq!(neighbors.len(), 3);
+            assert!(!neighbors.contains(&t));
+        }
+        for h in 0..halfedges.halfedge_count() {
+            let opposite = halfedges.opposite(h).unwrap();
+            assert_eq!(halfedges.opposite(opposite), Some(h));
+            assert_eq!(halfedges.origin(opposite), halfedges.target(h));
+        }
+    }
+
+    #[test]
+    fn open_fan_ends_at_the_boundary() {
+        let halfedges = HalfEdgeMesh::from_mesh(&fixtures::open_cube()).unwrap();
+
+        assert!(halfedges.is_boundary_vertex(4));
+        let ring = halfedges.vertices_around_vertex(4);
+        // one more neighbor than triangles around the open fan
+        assert_eq!(ring.len(), halfedges.faces_around_vertex(4).len() + 1);
+        assert!(ring.contains(&5) && ring.contains(&7));
+    }
+
+    #[test]
+    fn non_manifold_edge_is_rejected() {
+        let fin = fixtures::obj(&format!("{}v 0.5 -1 -1\nf 1 2 9\n", fixtures::CUBE));
+        assert!(matches!(
+            HalfEdgeMesh::from_mesh(&fin),
+            Err(MeshError::NonManifold(_))
+        ));
+    }
+}
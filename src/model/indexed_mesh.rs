@@ -0,0 +1,168 @@
+// compact triangle mesh
+// unlike `Mesh` it only keeps positions and triangle indices,
+// which is the layout renderers and GPU buffers expect
+use std::{collections::HashMap, io::Write};
+
+use crate::{
+    calculate::triangulation::triangulate_face,
+    model::{
+        Face, Mesh, MeshCodec, MeshError, Vec3, obj::ObjCodec, stl::StlCodec, vertex_bounds,
+        vertex_diagonal,
+    },
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexedMesh {
+    // list of all vertices
+    pub vertices: Vec<Vec3>,
+    // vertex indices of every triangle
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl IndexedMesh {
+    // splits every face of the mesh into triangles, the same way `Mesh::triangles` does
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        let indices = mesh
+            .faces
+            .iter()
+            .flat_map(|face| triangulate_face(&mesh.vertices, &face.v))
+            .map(|[a, b, c]| [a as u32, b as u32, c as u32])
+            .collect();
+
+        Self {
+            vertices: mesh.vertices.clone(),
+            indices,
+        }
+    }
+
+    // same as `from_mesh`, keeping only the positions the triangles use, each once
+    // (vertices with bit identical coordinates are merged)
+    pub fn compact(mesh: &Mesh) -> Self {
+        let mut map: HashMap<(u32, u32, u32), u32> = HashMap::new();
+        let mut vertices = Vec::new();
+        // lookup table: mesh vertex -> compact vertex, filled as the triangles use them
+        let mut remap: Vec<Option<u32>> = vec![None; mesh.vertices.len()];
+
+        let mut index = |i: usize| {
+            *remap[i].get_or_insert_with(|| {
+                let v = mesh.vertices[i];
+                *map.entry((v.0.to_bits(), v.1.to_bits(), v.2.to_bits()))
+                    .or_insert_with(|| {
+                        vertices.push(v);
+                        (vertices.len() - 1) as u32
+                    })
+            })
+        };
+        let indices = mesh
+            .faces
+            .iter()
+            .flat_map(|face| triangulate_face(&mesh.vertices, &face.v))
+            .map(|[a, b, c]| [index(a), index(b), index(c)])
+            .collect();
+
+        Self { vertices, indices }
+    }
+
+    // a `Mesh` with a triangle face per index triple, e.g., for the writers
+    pub fn to_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.vertices = self.vertices.clone();
+        mesh.faces = self
+            .indices
+            .iter()
+            .map(|triangle| Face {
+                v: triangle.iter().map(|&i| i as usize).collect(),
+                ..Face::default()
+            })
+            .collect();
+
+        mesh
+    }
+
+    pub fn write_obj(&self, writer: &mut dyn Write) -> Result<(), MeshError> {
+        ObjCodec.write_to(writer, &self.to_mesh())
+    }
+
+    // binary STL, which stores every corner again so the indices are lost
+    pub fn write_stl(&self, writer: &mut dyn Write) -> Result<(), MeshError> {
+        StlCodec::default().write_to(writer, &self.to_mesh())
+    }
+
+    // size in bytes of the positions and the indices,
+    // i.e. of the vertex and index buffers uploaded to a GPU
+    pub fn buffer_size(&self) -> usize {
+        self.vertices.len() * std::mem::size_of::<Vec3>()
+            + self.indices.len() * std::mem::size_of::<[u32; 3]>()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn bounds(&self) -> Result<(Vec3, Vec3), MeshError> {
+        vertex_bounds(&self.vertices)
+    }
+
+    pub fn diagonal(&self) -> Result<f32, MeshError> {
+        vertex_diagonal(&self.vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn from_mesh_keeps_the_bounds_of_a_cube() {
+        let indexed = IndexedMesh::from_mesh(&fixtures::cube());
+
+        assert_eq!(indexed.triangle_count(), 12);
+        assert_eq!(
+            indexed.bounds().unwrap(),
+            (Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0))
+        );
+        assert!((indexed.diagonal().unwrap() - 3f32.sqrt()).abs() < 1e-6);
+        assert!(indexed.indices.iter().flatten().all(|&i| i < 8));
+    }
+
+    #[test]
+    fn unwelded_cube_becomes_8_vertices_and_12_triangles() {
+        // every corner stored once per triangle, plus a vertex no face uses
+        let mut soup = Mesh::new();
+        for triangle in fixtures::cube().triangles() {
+            let start = soup.vertices.len();
+            soup.vertices.extend(triangle.vertices);
+            soup.faces.push(Face {
+                v: (start..start + 3).collect(),
+                ..Face::default()
+            });
+        }
+        soup.vertices.push(Vec3(5.0, 5.0, 5.0));
+
+        let indexed = soup.to_indexed();
+        assert_eq!(indexed.vertices.len(), 8);
+        assert_eq!(indexed.triangle_count(), 12);
+        assert_eq!(
+            indexed.buffer_size(),
+            8 * std::mem::size_of::<Vec3>() + 12 * std::mem::size_of::<[u32; 3]>()
+        );
+    }
+
+    #[test]
+    fn writers_emit_every_triangle() {
+        let indexed = fixtures::cube().to_indexed();
+
+        let mut obj = Vec::new();
+        indexed.write_obj(&mut obj).unwrap();
+        let parsed = ObjCodec.parse(&obj).unwrap();
+        assert_eq!((parsed.vertices.len(), parsed.faces.len()), (8, 12));
+
+        let mut stl = Vec::new();
+        indexed.write_stl(&mut stl).unwrap();
+        assert_eq!(
+            StlCodec::default().parse(&stl).unwrap().triangle_count(),
+            12
+        );
+    }
+}
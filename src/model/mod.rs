@@ -1,20 +1,164 @@
+#[cfg(feature = "gltf")]
+pub mod gltf;
+pub mod halfedge;
+pub mod indexed_mesh;
+pub mod mtl;
 pub mod obj;
+pub mod off;
 pub mod stl;
-
-use std::{collections::HashMap, ops::Range};
+#[cfg(feature = "threemf")]
+pub mod threemf;
+pub mod xyz;
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    ops::{Add, AddAssign, Mul, Neg, Range, Sub},
+    path::Path,
+    sync::OnceLock,
+};
+
+use flate2::read::GzDecoder;
 
 use nalgebra::Vector3;
-use rayon::iter::{IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{
+    IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 use smallvec::SmallVec;
 
-use crate::ui;
+use crate::{
+    calculate::triangulation::{compute_polygon_normal, triangulate_face},
+    model::{indexed_mesh::IndexedMesh, mtl::Material},
+    query::Bvh,
+    ui,
+};
 
+// default limit on the number of triangles a binary STL may declare,
+// guards against files claiming a huge count to force large allocations
+// raise it with `StlCodec { max_triangles }` and the `_with_limit` functions
 pub const MAX_TRIANGLES: u32 = 1_000_000;
 
+// digits after the decimal point used by the text based writers
+pub const DEFAULT_PRECISION: usize = 6;
+
 #[derive(Debug)]
+pub enum MeshError {
+    // the mesh (or the input) has no vertices to work with
+    Empty,
+    // the bounding box of the mesh collapses to a single point
+    ZeroDimensions,
+    // the input does not match any of the supported formats
+    UnsupportedFormat,
+    // a vertex on the given line (1-based) has a malformed coordinate
+    InvalidVertex { line: usize, token: String },
+    // a face on the given line (1-based) could not be used
+    InvalidFace { line: usize, token: String },
+    // the input ended before all the declared data could be read
+    Truncated,
+    // a text based format contained invalid UTF-8
+    InvalidEncoding,
+    // a polygon could not be triangulated
+    InvalidPolygon(&'static str),
+    // the surface does not enclose a volume
+    NotClosed,
+    // an edge or vertex is shared in a way a manifold surface can not be, with a description
+    NonManifold(String),
+    // the input is structurally invalid, with a description of the problem
+    InvalidData(String),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "mesh has no vertices"),
+            Self::ZeroDimensions => write!(f, "mesh has 0 dimensions"),
+            Self::UnsupportedFormat => write!(f, "unsupported file format"),
+            Self::InvalidVertex { line, token } => {
+                write!(f, "invalid vertex on line {}: '{}'", line, token)
+            }
+            Self::InvalidFace { line, token } => {
+                write!(f, "invalid face on line {}: '{}'", line, token)
+            }
+            Self::Truncated => write!(f, "file is truncated"),
+            Self::InvalidEncoding => write!(f, "file is not valid UTF-8"),
+            Self::InvalidPolygon(reason) => write!(f, "invalid polygon: {}", reason),
+            Self::NotClosed => write!(f, "mesh is not closed"),
+            Self::NonManifold(reason) => write!(f, "mesh is not manifold: {}", reason),
+            Self::InvalidData(reason) => write!(f, "invalid data: {}", reason),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MeshError {
+    fn from(err: std::io::Error) -> Self {
+        // running out of bytes mid record means the file was cut short
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(err)
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for MeshError {
+    fn from(_: std::str::Utf8Error) -> Self {
+        Self::InvalidEncoding
+    }
+}
+
+// length unit of the coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Mm,
+    Cm,
+    M,
+    In,
+}
+
+impl Unit {
+    // length of one unit in millimeters
+    pub fn millimeters(self) -> f32 {
+        match self {
+            Unit::Mm => 1.0,
+            Unit::Cm => 10.0,
+            Unit::M => 1000.0,
+            Unit::In => 25.4,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::M => "m",
+            Unit::In => "in",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Format {
     STL,
     OBJ,
+    OFF,
+    #[cfg(feature = "gltf")]
+    GLTF,
+    #[cfg(feature = "threemf")]
+    ThreeMF,
 }
 
 impl Format {
@@ -28,32 +172,68 @@ impl Format {
         {
             Some(Format::OBJ)
         } else {
+            // model/gltf+json and model/gltf-binary
+            #[cfg(feature = "gltf")]
+            if content_type.contains("model/gltf") {
+                return Some(Format::GLTF);
+            }
+            #[cfg(feature = "threemf")]
+            if content_type.contains("model/3mf") {
+                return Some(Format::ThreeMF);
+            }
+
             None
         }
     }
 
     pub fn from_name(name: &str) -> Option<Self> {
-        match name.to_lowercase().rsplit('.').next()? {
+        let name = name.to_lowercase();
+        // model.stl.gz is detected as model.stl
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        match name.rsplit('.').next()? {
             "stl" => Some(Format::STL),
             "obj" => Some(Format::OBJ),
+            "off" => Some(Format::OFF),
+            #[cfg(feature = "gltf")]
+            "gltf" | "glb" => Some(Format::GLTF),
+            #[cfg(feature = "threemf")]
+            "3mf" => Some(Format::ThreeMF),
             _ => None,
         }
     }
 
     pub fn from_magic_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_magic_bytes_with_limit(bytes, MAX_TRIANGLES)
+    }
+
+    // same as `from_magic_bytes`, binary STL files may declare up to `max_triangles`
+    pub fn from_magic_bytes_with_limit(bytes: &[u8], max_triangles: u32) -> Option<Self> {
         if bytes.is_empty() {
             return None;
         }
 
+        #[cfg(feature = "gltf")]
+        if bytes.starts_with(gltf::GLB_MAGIC) {
+            return Some(Format::GLTF);
+        }
+
+        #[cfg(feature = "threemf")]
+        if threemf::validate_bytes(bytes) {
+            return Some(Format::ThreeMF);
+        }
+
         // STL file detection
         // binary STL files detection
+        // some exporters append junk after the last triangle, a file with more than a little
+        // slack is only taken as STL when its first and last declared triangles look like geometry
         if bytes.len() >= 84 {
             let traingle_count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
             if traingle_count > 0
-                && traingle_count <= MAX_TRIANGLES
+                && traingle_count <= max_triangles
                 && let Some(expected_size) = 84usize.checked_add(traingle_count as usize * 50)
                 && bytes.len() >= expected_size
-                && bytes.len() <= expected_size + 80
+                && (bytes.len() <= expected_size + 80
+                    || stl::plausible_triangles(bytes, traingle_count as usize))
             {
                 return Some(Format::STL);
             }
@@ -70,6 +250,11 @@ impl Format {
             }
         }
 
+        // OFF file detection
+        if off::validate_bytes(bytes) {
+            return Some(Format::OFF);
+        }
+
         // OBJ file detection
         let preview = &bytes[..bytes.len().min(4096)];
         if let Ok(content) = std::str::from_utf8(preview) {
@@ -100,10 +285,64 @@ impl Format {
         None
     }
 
+    // the format to parse `bytes` as: an explicit `format` always wins, otherwise the
+    // extension of `path` is preferred when the content looks like another format
+    // (e.g., an OBJ file whose first line is `solid ...`), with a warning
+    pub fn detect(bytes: &[u8], path: Option<&Path>, format: Option<Self>) -> Option<Self> {
+        let extension = path.and_then(Path::to_str).and_then(Self::from_name);
+        Self::detect_with_hint(bytes, extension, format, MAX_TRIANGLES)
+    }
+
+    // same as `detect` with the format of the file name already known
+    pub(crate) fn detect_with_hint(
+        bytes: &[u8],
+        hint: Option<Self>,
+        format: Option<Self>,
+        max_triangles: u32,
+    ) -> Option<Self> {
+        let content = Self::from_magic_bytes_with_limit(bytes, max_triangles);
+        if let Some(format) = format {
+            if let Some(content) = content.filter(|&content| content != format) {
+                ui::print_verbose(
+                    "Format",
+                    &format!(
+                        "reading as {} as requested, the content looks like {}",
+                        format.as_str().to_uppercase(),
+                        content.as_str().to_uppercase()
+                    ),
+                );
+            }
+            return Some(format);
+        }
+
+        match (content, hint) {
+            (Some(content), Some(extension)) if content != extension => {
+                ui::print_warn(&format!(
+                    "the content looks like {} but the extension is .{}, reading it as {}.",
+                    content.as_str().to_uppercase(),
+                    extension.as_str(),
+                    extension.as_str().to_uppercase()
+                ));
+                Some(extension)
+            }
+            (content, extension) => content.or(extension),
+        }
+    }
+
     pub fn validate_bytes(&self, bytes: &[u8]) -> bool {
+        self.validate_bytes_with_limit(bytes, MAX_TRIANGLES)
+    }
+
+    // same as `validate_bytes`, binary STL files may declare up to `max_triangles`
+    pub fn validate_bytes_with_limit(&self, bytes: &[u8], max_triangles: u32) -> bool {
         match self {
-            Self::STL => stl::validate_bytes(bytes),
+            Self::STL => stl::validate_bytes_with_limit(bytes, max_triangles),
             Self::OBJ => obj::validate_bytes(bytes),
+            Self::OFF => off::validate_bytes(bytes),
+            #[cfg(feature = "gltf")]
+            Self::GLTF => gltf::validate_bytes(bytes),
+            #[cfg(feature = "threemf")]
+            Self::ThreeMF => threemf::validate_bytes(bytes),
         }
     }
 
@@ -111,21 +350,69 @@ impl Format {
         match self {
             Self::STL => "stl",
             Self::OBJ => "obj",
+            Self::OFF => "off",
+            #[cfg(feature = "gltf")]
+            Self::GLTF => "gltf",
+            #[cfg(feature = "threemf")]
+            Self::ThreeMF => "3mf",
         }
     }
 
     pub fn get_codec(&self) -> Box<dyn MeshCodec> {
         match self {
-            Self::STL => Box::new(stl::StlCodec),
+            Self::STL => Box::new(stl::StlCodec::default()),
             Self::OBJ => Box::new(obj::ObjCodec),
+            Self::OFF => Box::new(off::OffCodec),
+            #[cfg(feature = "gltf")]
+            Self::GLTF => Box::new(gltf::GltfCodec),
+            #[cfg(feature = "threemf")]
+            Self::ThreeMF => Box::new(threemf::ThreeMfCodec),
         }
     }
 }
 
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+// returns the decompressed bytes for gzip compressed input
+// and the input untouched otherwise
+pub fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, MeshError> {
+    if !is_gzip(bytes) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+
+    let mut decoder = GzDecoder::new(bytes);
+    let mut buffer = Vec::new();
+    decoder.read_to_end(&mut buffer)?;
+
+    Ok(Cow::Owned(buffer))
+}
+
+// what `Mesh::weld` merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeldStats {
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+    // faces with at least one vertex index changed
+    pub faces_remapped: usize,
+}
+
+// build meshes with `Mesh::new()` or `Mesh::default()` and fill the public fields,
+// the private face area cache rules out struct literals outside the crate
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Mesh {
     // list of all vertices
     pub vertices: Vec<Vec3>,
+    // per vertex RGB colors, parallel to `vertices` when present
+    // empty when the source file has no vertex colors
+    pub colors: Vec<[u8; 3]>,
+    // per vertex weights of rational geometry (the `w` of OBJ `v x y z w`), parallel to
+    // `vertices` when present. empty when every vertex has the default weight of 1
+    pub weights: Vec<f32>,
 
     // list of all vertex normals
     // used to define smooth shading (how light interacts with the surface)
@@ -133,9 +420,19 @@ pub struct Mesh {
     // list of all vertex texture coordinates
     // used to map 2D images to the 3D surface
     pub textures: Vec<Vec2>,
+    // optional third texture coordinate (the `w` of OBJ `vt u v w`), parallel to
+    // `textures` when present. empty when every texture coordinate has the default of 0
+    pub texture_w: Vec<f32>,
 
     // list of all the faces
     pub faces: Vec<Face>,
+    // per face RGB colors, parallel to `faces` when present
+    // read from and written to the attribute word of binary STL files
+    pub face_colors: Vec<[u8; 3]>,
+    // polylines (OBJ `l`) and points (OBJ `p`) as vertex indices, e.g., construction
+    // geometry of CAD exports. kept for round trips, they are not part of the surface
+    pub lines: Vec<Vec<usize>>,
+    pub points: Vec<usize>,
     // groups of faces
     // used to organize the mesh into logical sections
     // e.g., wheels of a car
@@ -143,46 +440,228 @@ pub struct Mesh {
 
     // material libraries associated with the mesh
     pub matlibs: Vec<String>,
+    // materials parsed from the material libraries
+    // only populated after calling `load_materials`
+    pub materials: Vec<Material>,
+
+    // 80 byte STL header, kept so round trips preserve slicer metadata
+    // for ASCII STL files this holds the solid name
+    pub header: Option<[u8; 80]>,
+
+    // unit of the coordinates when it is known (none of the formats store it)
+    // None when unknown, the checks for 3D printing assume millimeters
+    pub unit: Option<Unit>,
+
+    // area of every face, computed on the first call to `face_areas`
+    // every function of the crate changing `vertices` or `faces` (transforms, welding,
+    // repairs, triangulation, merging) clears it, code changing them directly has to
+    // call `invalidate_face_areas`
+    face_areas: OnceLock<Vec<f64>>,
 }
 
 impl Mesh {
-    pub fn weld(&mut self) {
+    // merges vertices with bit identical coordinates and remaps `Face::v` to the merged vertices
+    // only positions are merged: `Face::vt` and `Face::vn` index their own arrays and are
+    // kept per face corner, so texturing and shading survive
+    // the welded vertices stay in order of first occurrence, which is the fastest option
+    // but depends on the order they were parsed in, see `weld_sorted` for a stable order
+    pub fn weld(&mut self) -> WeldStats {
         let mut map: HashMap<(u32, u32, u32), usize> = HashMap::new();
 
         let mut new_vertices: Vec<Vec3> = Vec::with_capacity(self.vertices.len());
         // lookup table: old index -> new index
         let mut remap: Vec<usize> = vec![0; self.vertices.len()];
 
+        // welded vertices keep the color and weight of their first occurrence
+        let has_colors = self.colors.len() == self.vertices.len();
+        let mut new_colors: Vec<[u8; 3]> = Vec::new();
+        let has_weights = self.weights.len() == self.vertices.len();
+        let mut new_weights: Vec<f32> = Vec::new();
+
         for (old_index, vertex) in self.vertices.iter().enumerate() {
             let key = (vertex.0.to_bits(), vertex.1.to_bits(), vertex.2.to_bits());
 
             let idx = *map.entry(key).or_insert_with(|| {
                 let idx = new_vertices.len();
                 new_vertices.push(*vertex);
+                if has_colors {
+                    new_colors.push(self.colors[old_index]);
+                }
+                if has_weights {
+                    new_weights.push(self.weights[old_index]);
+                }
                 idx
             });
 
             remap[old_index] = idx;
         }
 
-        if new_vertices.len() != self.vertices.len() {
-            ui::print_newline();
-            ui::print_warn("welding vertices: ");
-            ui::print_kv("before", self.vertices.len());
-            ui::print_kv("after", new_vertices.len());
-            ui::print_newline();
-        }
+        let mut stats = WeldStats {
+            vertices_before: self.vertices.len(),
+            vertices_after: new_vertices.len(),
+            faces_remapped: 0,
+        };
 
         self.vertices = new_vertices;
+        if has_colors {
+            self.colors = new_colors;
+        }
+        if has_weights {
+            self.weights = new_weights;
+        }
         for face in &mut self.faces {
+            let mut remapped = false;
             for i in 0..face.v.len() {
                 let old_index = face.v[i];
                 face.v[i] = remap[old_index];
+                remapped |= face.v[i] != old_index;
+            }
+            if remapped {
+                stats.faces_remapped += 1;
+            }
+        }
+        self.remap_lines_and_points(&remap);
+        self.invalidate_face_areas();
+
+        stats
+    }
+
+    // number of vertices with the exact same coordinates as an earlier vertex,
+    // i.e. the vertices `weld` would merge
+    pub fn duplicate_vertex_count(&self) -> usize {
+        let mut seen = HashSet::with_capacity(self.vertices.len());
+        self.vertices
+            .iter()
+            .filter(|v| !seen.insert((v.0.to_bits(), v.1.to_bits(), v.2.to_bits())))
+            .count()
+    }
+
+    // same as `weld`, then sorts the vertices by the bit pattern of their coordinates
+    // and remaps the faces, so the same geometry always ends up with the same vertex order
+    // (e.g., for comparing written files against golden files)
+    pub fn weld_sorted(&mut self) {
+        self.weld();
+
+        let key = |v: &Vec3| (v.0.to_bits(), v.1.to_bits(), v.2.to_bits());
+        let mut order: Vec<usize> = (0..self.vertices.len()).collect();
+        // the keys are unique after welding, so an unstable sort is deterministic
+        order.sort_unstable_by_key(|&i| key(&self.vertices[i]));
+
+        // lookup table: old index -> new index
+        let mut remap: Vec<usize> = vec![0; self.vertices.len()];
+        for (new_index, &old_index) in order.iter().enumerate() {
+            remap[old_index] = new_index;
+        }
+
+        self.vertices = order.iter().map(|&i| self.vertices[i]).collect();
+        if self.colors.len() == order.len() {
+            self.colors = order.iter().map(|&i| self.colors[i]).collect();
+        }
+        if self.weights.len() == order.len() {
+            self.weights = order.iter().map(|&i| self.weights[i]).collect();
+        }
+        for face in &mut self.faces {
+            for idx in face.v.iter_mut() {
+                *idx = remap[*idx];
             }
         }
+        self.remap_lines_and_points(&remap);
+        self.invalidate_face_areas();
     }
 
-    pub fn scale(&mut self, target_diagonal: f32) -> anyhow::Result<()> {
+    // updates the vertex indices of the lines and points after the vertices were
+    // reordered or merged, `remap` maps every old index to its new index
+    pub(crate) fn remap_lines_and_points(&mut self, remap: &[usize]) {
+        for line in &mut self.lines {
+            for idx in line.iter_mut() {
+                *idx = remap[*idx];
+            }
+        }
+        for idx in &mut self.points {
+            *idx = remap[*idx];
+        }
+    }
+
+    // snaps every vertex to the nearest multiple of `grid` and welds the vertices
+    // ending up in the same place, remapping the faces
+    pub fn quantize(&mut self, grid: f32) -> Result<(), MeshError> {
+        if !(grid > 0.0 && grid.is_finite()) {
+            return Err(MeshError::InvalidData(format!(
+                "invalid grid size {}",
+                grid
+            )));
+        }
+
+        // a coarse grid collapses the details of the mesh
+        if let Ok(diagonal) = self.diagonal()
+            && grid > diagonal * 0.01
+        {
+            ui::print_warn(&format!(
+                "the grid size {} is more than 1% of the diagonal ({:.4}), details will be lost.",
+                grid, diagonal
+            ));
+        }
+
+        let snap = |value: f32| (value / grid).round() * grid;
+        self.vertices.par_iter_mut().for_each(|v| {
+            *v = Vec3(snap(v.0), snap(v.1), snap(v.2));
+        });
+        self.invalidate_face_areas();
+        self.weld();
+
+        Ok(())
+    }
+
+    // removes the vertices with a NaN or infinite coordinate (e.g., from a corrupt file)
+    // and the faces using them, returns the number of faces removed
+    pub fn sanitize(&mut self) -> usize {
+        let finite: Vec<bool> = self.vertices.par_iter().map(|v| v.is_finite()).collect();
+        if finite.iter().all(|&finite| finite) {
+            return 0;
+        }
+
+        let removed = self.retain_faces(|face| face.v.iter().all(|&idx| finite[idx]));
+        // lines lose their broken vertices, and are dropped when less than 2 remain
+        self.points.retain(|&idx| finite[idx]);
+        for line in &mut self.lines {
+            line.retain(|&idx| finite[idx]);
+        }
+        self.lines.retain(|line| line.len() >= 2);
+
+        // lookup table: old index -> new index
+        let mut remap: Vec<usize> = vec![0; self.vertices.len()];
+        let mut next = 0;
+        for (old_index, &finite) in finite.iter().enumerate() {
+            if finite {
+                remap[old_index] = next;
+                next += 1;
+            }
+        }
+
+        let has_colors = self.colors.len() == self.vertices.len();
+        let has_weights = self.weights.len() == self.vertices.len();
+        let mut keep = finite.iter();
+        self.vertices.retain(|_| *keep.next().unwrap_or(&false));
+        if has_colors {
+            let mut keep = finite.iter();
+            self.colors.retain(|_| *keep.next().unwrap_or(&false));
+        }
+        if has_weights {
+            let mut keep = finite.iter();
+            self.weights.retain(|_| *keep.next().unwrap_or(&false));
+        }
+        for face in &mut self.faces {
+            for idx in face.v.iter_mut() {
+                *idx = remap[*idx];
+            }
+        }
+        self.remap_lines_and_points(&remap);
+        self.invalidate_face_areas();
+
+        removed
+    }
+
+    pub fn scale(&mut self, target_diagonal: f32) -> Result<(), MeshError> {
         let (min_vertex, max_vertex) = self.bounds()?;
 
         let dx = max_vertex.0 - min_vertex.0;
@@ -191,7 +670,7 @@ impl Mesh {
 
         let current_diagonal = (dx * dx + dy * dy + dz * dz).sqrt();
         if current_diagonal == 0.0 {
-            return Err(anyhow::anyhow!("mesh has 0 dimensions"));
+            return Err(MeshError::ZeroDimensions);
         }
 
         let center_x = (min_vertex.0 + max_vertex.0) / 2.0;
@@ -205,10 +684,241 @@ impl Mesh {
             vertex.1 = (vertex.1 - center_y) * scale_factor + center_y;
             vertex.2 = (vertex.2 - center_z) * scale_factor + center_z;
         });
+        self.invalidate_face_areas();
 
         Ok(())
     }
 
+    // turns the mesh inside out: reverses the winding of every face and negates the
+    // vertex normals. unlike fixing inconsistent faces, every face is flipped
+    pub fn flip(&mut self) {
+        self.faces.par_iter_mut().for_each(Face::reverse);
+        self.normals.par_iter_mut().for_each(|n| *n = -*n);
+        self.invalidate_face_areas();
+    }
+
+    // appends the vertices, faces, groups and attributes of `other`, offsetting its indices
+    // shared positions stay duplicated until the mesh is welded
+    pub fn merge(&mut self, other: &Mesh) {
+        let (vertex_offset, texture_offset, normal_offset, face_offset) = (
+            self.vertices.len(),
+            self.textures.len(),
+            self.normals.len(),
+            self.faces.len(),
+        );
+
+        // per vertex, texture and face attributes stay parallel when only one mesh has them
+        if !self.colors.is_empty() || !other.colors.is_empty() {
+            self.colors.resize(vertex_offset, [255, 255, 255]);
+            self.colors.extend(&other.colors);
+            self.colors
+                .resize(vertex_offset + other.vertices.len(), [255, 255, 255]);
+        }
+        if !self.weights.is_empty() || !other.weights.is_empty() {
+            self.weights.resize(vertex_offset, 1.0);
+            self.weights.extend(&other.weights);
+            self.weights
+                .resize(vertex_offset + other.vertices.len(), 1.0);
+        }
+        if !self.texture_w.is_empty() || !other.texture_w.is_empty() {
+            self.texture_w.resize(texture_offset, 0.0);
+            self.texture_w.extend(&other.texture_w);
+            self.texture_w
+                .resize(texture_offset + other.textures.len(), 0.0);
+        }
+        if !self.face_colors.is_empty() || !other.face_colors.is_empty() {
+            self.face_colors.resize(face_offset, [255, 255, 255]);
+            self.face_colors.extend(&other.face_colors);
+            self.face_colors
+                .resize(face_offset + other.faces.len(), [255, 255, 255]);
+        }
+
+        self.vertices.extend(&other.vertices);
+        self.textures.extend(&other.textures);
+        self.normals.extend(&other.normals);
+        self.faces.extend(other.faces.iter().map(|face| {
+            Face {
+                v: face.v.iter().map(|&i| i + vertex_offset).collect(),
+                vn: face
+                    .vn
+                    .iter()
+                    .map(|i| i.map(|i| i + normal_offset))
+                    .collect(),
+                vt: face
+                    .vt
+                    .iter()
+                    .map(|i| i.map(|i| i + texture_offset))
+                    .collect(),
+                smoothing_group: face.smoothing_group,
+            }
+        }));
+        self.lines.extend(
+            other
+                .lines
+                .iter()
+                .map(|line| line.iter().map(|&i| i + vertex_offset).collect()),
+        );
+        self.points
+            .extend(other.points.iter().map(|&i| i + vertex_offset));
+        self.groups.extend(other.groups.iter().map(|group| Group {
+            face_range: group.face_range.start + face_offset..group.face_range.end + face_offset,
+            ..group.clone()
+        }));
+
+        for matlib in &other.matlibs {
+            if !self.matlibs.contains(matlib) {
+                self.matlibs.push(matlib.clone());
+            }
+        }
+        for material in &other.materials {
+            if !self
+                .materials
+                .iter()
+                .any(|known| known.name == material.name)
+            {
+                self.materials.push(material.clone());
+            }
+        }
+        self.invalidate_face_areas();
+    }
+
+    // keeps only the faces matching the predicate, shrinking the group
+    // face ranges so they keep pointing at the same faces
+    // returns the number of faces removed
+    pub fn retain_faces<F: FnMut(&Face) -> bool>(&mut self, mut keep: F) -> usize {
+        let before = self.faces.len();
+
+        // lookup table: old index -> number of faces kept before it
+        let mut kept_before: Vec<usize> = Vec::with_capacity(before + 1);
+        let mut kept = 0;
+        let mut kept_faces: Vec<bool> = Vec::with_capacity(before);
+        self.faces.retain(|face| {
+            kept_before.push(kept);
+            let keep = keep(face);
+            if keep {
+                kept += 1;
+            }
+            kept_faces.push(keep);
+            keep
+        });
+        kept_before.push(kept);
+        if self.face_colors.len() == before {
+            let mut keep = kept_faces.iter();
+            self.face_colors.retain(|_| *keep.next().unwrap_or(&false));
+        }
+        self.invalidate_face_areas();
+
+        for group in &mut self.groups {
+            let start = kept_before[group.face_range.start.min(before)];
+            let end = kept_before[group.face_range.end.min(before)];
+            group.face_range = start..end;
+        }
+
+        before - self.faces.len()
+    }
+
+    // removes faces using the same vertices as an earlier face, keeping the first one
+    // with `ignore_winding` a face with the reversed winding of an earlier face is a duplicate too,
+    // otherwise only faces with the same orientation (up to rotation) are
+    // returns the number of faces removed
+    pub fn remove_duplicate_faces(&mut self, ignore_winding: bool) -> usize {
+        let mut seen: HashSet<SmallVec<[usize; 4]>> = HashSet::with_capacity(self.faces.len());
+
+        self.retain_faces(|face| {
+            if face.v.len() < 3 {
+                return true;
+            }
+
+            let mut key = face.v.clone();
+            if ignore_winding {
+                key.sort_unstable();
+            } else {
+                // rotate the smallest index to the front, which keeps the orientation
+                let first = key
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, v)| **v)
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                key.rotate_left(first);
+            }
+
+            seen.insert(key)
+        })
+    }
+
+    // faces that lie inside the solid, e.g., walls left over from merging two solids
+    // a ray is cast from the centroid of every face along its normal. exterior faces look
+    // outwards, so the first surface the ray hits is seen from the front (or nothing is hit),
+    // a ray from an interior face hits the inside (back) of the enclosing surface first.
+    // this is a heuristic: it needs consistent outward winding, and an interior face whose ray
+    // first reaches another interior face may be classified either way
+    pub fn interior_faces(&self) -> Vec<usize> {
+        let bvh = Bvh::new(self);
+        let diagonal = self.diagonal().unwrap_or(0.0);
+        // move the ray start off the face, relative to the size of the mesh
+        let offset = diagonal * 1e-6;
+        // the normal of a (nearly) degenerate face is noise
+        let min_area = diagonal * diagonal * f32::EPSILON;
+
+        (0..self.faces.len())
+            .into_par_iter()
+            .filter(|&index| {
+                let face = &self.faces[index];
+                if face.v.len() < 3 {
+                    return false;
+                }
+
+                // Newell's normal is twice the area of the face
+                let normal = compute_polygon_normal(&self.vertices, &face.v);
+                if normal.length() / 2.0 <= min_area {
+                    return false;
+                }
+                let normal = normal.normalize();
+
+                let n = face.v.len() as f32;
+                let centroid = face.v.iter().fold(Vec3(0.0, 0.0, 0.0), |sum, &i| {
+                    let v = self.vertices[i];
+                    Vec3(sum.0 + v.0 / n, sum.1 + v.1 / n, sum.2 + v.2 / n)
+                });
+                let origin = Vec3(
+                    centroid.0 + normal.0 * offset,
+                    centroid.1 + normal.1 * offset,
+                    centroid.2 + normal.2 * offset,
+                );
+
+                bvh.cast_ray(origin, normal, Some(index))
+                    .is_some_and(|hit| hit.triangle.normal().dot(normal) > 0.0)
+            })
+            .collect()
+    }
+
+    // every face split into triangles, the same way the STL writer does
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.faces
+            .iter()
+            .flat_map(|face| face.triangles(&self.vertices))
+    }
+
+    // compact triangle mesh of the unique positions the faces use and `u32` indices
+    pub fn to_indexed(&self) -> IndexedMesh {
+        IndexedMesh::compact(self)
+    }
+
+    // same as `triangles`, paired with the index of the face each triangle came from
+    pub fn triangles_with_face_index(&self) -> impl Iterator<Item = (usize, Triangle)> + '_ {
+        self.faces
+            .iter()
+            .enumerate()
+            .flat_map(|(face_index, face)| {
+                face.triangles(&self.vertices)
+                    .map(move |triangle| (face_index, triangle))
+            })
+    }
+
+    // number of triangles `triangles` yields (and the STL writer emits)
+    // ear clipping and the fan fallback both split a polygon of n vertices into n - 2
+    // triangles, faces with less than 3 vertices yield none
     pub fn triangle_count(&self) -> usize {
         self.faces
             .iter()
@@ -216,106 +926,490 @@ impl Mesh {
             .sum()
     }
 
-    pub fn topology(&self) -> HashMap<(usize, usize), usize> {
-        let mut map = HashMap::<(usize, usize), usize>::new();
+    // number of faces using each edge
+    // edges are keyed by their (min, max) vertex index pair, so both directions share an entry.
+    // a count of 2 is a manifold edge, 1 a boundary edge (the rim of a hole)
+    // and more than 2 a non-manifold edge. edges of faces with less than 3 vertices
+    // and edges connecting a vertex to itself are not counted
+    pub fn topology(&self) -> HashMap<(usize, usize), u32> {
+        let mut map = HashMap::<(usize, usize), u32>::new();
 
         for face in &self.faces {
-            // skip faces with less than 3 vertices
-            if face.v.len() < 3 {
-                continue;
+            for edge in face_edges(face) {
+                *map.entry(edge).or_insert(0) += 1;
+            }
+        }
+
+        map
+    }
+
+    // faces using each edge, keyed the same way as `topology`
+    pub(crate) fn edge_faces(&self) -> HashMap<(usize, usize), Vec<usize>> {
+        let mut map = HashMap::<(usize, usize), Vec<usize>>::new();
+
+        for (face_index, face) in self.faces.iter().enumerate() {
+            for edge in face_edges(face) {
+                map.entry(edge).or_default().push(face_index);
+            }
+        }
+
+        map
+    }
+
+    // angle in degrees between the normals of the two faces of every manifold edge
+    // 0 for coplanar faces, 90 for the edges of a cube
+    pub fn dihedral_angles(&self) -> Vec<f32> {
+        self.manifold_edge_angles()
+            .into_iter()
+            .map(|(_, angle)| angle)
+            .collect()
+    }
+
+    // manifold edges whose dihedral angle is above the threshold
+    pub fn sharp_edges(&self, threshold_degrees: f32) -> Vec<(usize, usize)> {
+        self.manifold_edge_angles()
+            .into_iter()
+            .filter(|&(_, angle)| angle > threshold_degrees)
+            .map(|(edge, _)| edge)
+            .collect()
+    }
+
+    // angle in degrees between every stored normal a face corner references (through `vn`)
+    // and the area weighted normal of its vertex computed from the geometry.
+    // flat shaded meshes store face normals, which differ from the vertex normals at corners
+    pub fn normal_deviations(&self) -> Vec<f32> {
+        // newell normals are scaled by twice the area of the face
+        let mut computed = vec![Vec3(0.0, 0.0, 0.0); self.vertices.len()];
+        for face in &self.faces {
+            let normal = compute_polygon_normal(&self.vertices, &face.v);
+            for &i in &face.v {
+                computed[i] += normal;
             }
+        }
+        let computed: Vec<Vec3> = computed.into_iter().map(Vec3::normalize).collect();
+
+        let zero = Vec3(0.0, 0.0, 0.0);
+        self.faces
+            .iter()
+            .flat_map(|face| face.v.iter().zip(&face.vn))
+            .filter_map(|(&v, &vn)| {
+                let stored = self.normals.get(vn?)?.normalize();
+                // zero normals are left for the reader to compute
+                (stored != zero && computed[v] != zero)
+                    .then(|| stored.dot(computed[v]).clamp(-1.0, 1.0).acos().to_degrees())
+            })
+            .collect()
+    }
+
+    // mean and max of `normal_deviations`, None when no face references a stored normal
+    pub fn normal_deviation(&self) -> Option<(f32, f32)> {
+        let deviations = self.normal_deviations();
+        if deviations.is_empty() {
+            return None;
+        }
+
+        let sum = deviations.iter().fold(0.0, |sum, &d| sum + d as f64);
+        let max = deviations.iter().copied().fold(0.0, f32::max);
+        Some(((sum / deviations.len() as f64) as f32, max))
+    }
+
+    // (edge, dihedral angle) for every edge shared by exactly two faces, sorted by edge
+    fn manifold_edge_angles(&self) -> Vec<((usize, usize), f32)> {
+        let normals: Vec<Vec3> = self
+            .faces
+            .iter()
+            .map(|face| face.normal(&self.vertices))
+            .collect();
+
+        let mut angles: Vec<((usize, usize), f32)> = self
+            .edge_faces()
+            .into_iter()
+            .filter(|(_, faces)| faces.len() == 2)
+            .map(|(edge, faces)| {
+                let cos = normals[faces[0]].dot(normals[faces[1]]).clamp(-1.0, 1.0);
+                (edge, cos.acos().to_degrees())
+            })
+            .collect();
+        angles.sort_unstable_by_key(|&(edge, _)| edge);
+
+        angles
+    }
+
+    // true when every edge is shared by exactly two faces,
+    // meaning the surface is closed and encloses a volume
+    pub fn is_watertight(&self) -> bool {
+        let map = self.topology();
+        !map.is_empty() && map.values().all(|&count| count == 2)
+    }
+
+    // edges used by a single face, i.e. the rims of the holes in the mesh
+    // as (min, max) vertex index pairs
+    pub fn boundary_edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = self
+            .topology()
+            .into_iter()
+            .filter(|&(_, count)| count == 1)
+            .map(|(edge, _)| edge)
+            .collect();
+        edges.sort_unstable();
+
+        edges
+    }
+
+    // the rims of the holes of the mesh, as loops of vertex indices following the winding
+    // of the faces around them. the first vertex is not repeated at the end
+    // a rim touching itself at a vertex may be split into several loops
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let topology = self.topology();
 
+        // boundary edges, in the direction of the face using them
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for face in self.faces.iter().filter(|face| face.v.len() >= 3) {
             for i in 0..face.v.len() {
-                let v0 = face.v[i];
-                let v1 = face.v[(i + 1) % face.v.len()];
+                let (v0, v1) = (face.v[i], face.v[(i + 1) % face.v.len()]);
+                let key = if v0 < v1 { (v0, v1) } else { (v1, v0) };
+                if v0 != v1 && topology.get(&key) == Some(&1) {
+                    edges.push((v0, v1));
+                }
+            }
+        }
+
+        // boundary edges using each vertex
+        let mut vertex_edges = HashMap::<usize, SmallVec<[usize; 2]>>::new();
+        for (i, &(v0, v1)) in edges.iter().enumerate() {
+            vertex_edges.entry(v0).or_default().push(i);
+            vertex_edges.entry(v1).or_default().push(i);
+        }
+
+        let mut used = vec![false; edges.len()];
+        let mut loops = Vec::new();
+        for start in 0..edges.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+
+            let (first, mut current) = edges[start];
+            let mut boundary = vec![first];
+            while current != first {
+                boundary.push(current);
+
+                // a chain that can not be closed (e.g., inconsistent winding) ends here
+                let Some(next) = vertex_edges[&current].iter().copied().find(|&e| !used[e]) else {
+                    break;
+                };
+                used[next] = true;
+                let (v0, v1) = edges[next];
+                current = if v0 == current { v1 } else { v0 };
+            }
 
-                if v0 == v1 {
-                    continue;
+            loops.push(boundary);
+        }
+
+        loops
+    }
+
+    // number of holes, i.e. the number of boundary loops
+    pub fn hole_count(&self) -> usize {
+        self.boundary_loops().len()
+    }
+
+    // connected component of every face, faces sharing a vertex are connected
+    // components are numbered from 0 in order of their first face, returns the labels
+    // (parallel to `faces`) and the number of components
+    pub fn face_components(&self) -> (Vec<usize>, usize) {
+        // union find over the vertices, with path halving
+        let mut parent: Vec<usize> = (0..self.vertices.len()).collect();
+        fn find(parent: &mut [usize], mut v: usize) -> usize {
+            while parent[v] != v {
+                parent[v] = parent[parent[v]];
+                v = parent[v];
+            }
+            v
+        }
+
+        for face in &self.faces {
+            if let Some((&first, rest)) = face.v.split_first() {
+                let root = find(&mut parent, first);
+                for &v in rest {
+                    let other = find(&mut parent, v);
+                    parent[other] = root;
                 }
+            }
+        }
 
-                let edge = if v0 < v1 { (v0, v1) } else { (v1, v0) };
-                *map.entry(edge).or_insert(0) += 1;
+        let mut count = 0;
+        let mut new_label = || {
+            count += 1;
+            count - 1
+        };
+        let mut label_of_root: HashMap<usize, usize> = HashMap::new();
+        let labels = self
+            .faces
+            .iter()
+            .map(|face| match face.v.first() {
+                Some(&v) => *label_of_root
+                    .entry(find(&mut parent, v))
+                    .or_insert_with(&mut new_label),
+                // a face without vertices is a component of its own
+                None => new_label(),
+            })
+            .collect();
+
+        (labels, count)
+    }
+
+    // whether every connected component (numbered as by `face_components`) is watertight,
+    // i.e. every edge of its faces is shared by exactly two of them
+    pub fn component_watertightness(&self) -> Vec<bool> {
+        let (labels, count) = self.face_components();
+        // components without edges (e.g., a face without vertices) are not closed
+        let mut has_edges = vec![false; count];
+        let mut open = vec![false; count];
+        for faces in self.edge_faces().into_values() {
+            let component = labels[faces[0]];
+            has_edges[component] = true;
+            if faces.len() != 2 {
+                open[component] = true;
             }
         }
 
-        map
+        has_edges
+            .into_iter()
+            .zip(open)
+            .map(|(has_edges, open)| has_edges && !open)
+            .collect()
     }
+
+    // number of edges meeting at every vertex, 0 for vertices no face uses
+    pub fn vertex_valences(&self) -> Vec<u32> {
+        let mut valences = vec![0u32; self.vertices.len()];
+        for (v0, v1) in self.topology().into_keys() {
+            valences[v0] += 1;
+            valences[v1] += 1;
+        }
+
+        valences
+    }
+
+    // number of vertices with every valence, unused vertices are left out
+    pub fn valence_histogram(&self) -> HashMap<u32, usize> {
+        let mut histogram = HashMap::new();
+        for valence in self.vertex_valences() {
+            if valence > 0 {
+                *histogram.entry(valence).or_insert(0) += 1;
+            }
+        }
+
+        histogram
+    }
+
+    // number of interior vertices with a valence other than the regular one: 4 when most
+    // faces are quads, 6 otherwise (a regular triangle mesh). vertices on the boundary
+    // are not counted
+    pub fn irregular_vertex_count(&self) -> usize {
+        let quads = self.faces.iter().filter(|face| face.v.len() == 4).count();
+        let regular = if quads * 2 > self.faces.len() { 4 } else { 6 };
+
+        let mut on_boundary = vec![false; self.vertices.len()];
+        for (v0, v1) in self.boundary_edges() {
+            on_boundary[v0] = true;
+            on_boundary[v1] = true;
+        }
+
+        self.vertex_valences()
+            .iter()
+            .zip(&on_boundary)
+            .filter(|&(&valence, &on_boundary)| valence > 0 && valence != regular && !on_boundary)
+            .count()
+    }
+
+    // edges used by more than two faces, as sorted (min, max) vertex index pairs
+    pub fn non_manifold_edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = self
+            .topology()
+            .into_iter()
+            .filter(|&(_, count)| count > 2)
+            .map(|(edge, _)| edge)
+            .collect();
+        edges.sort_unstable();
+
+        edges
+    }
+
+    // summed length of every unique edge
+    pub fn total_edge_length(&self) -> f64 {
+        self.topology()
+            .into_keys()
+            .map(|edge| self.edge_length(edge))
+            .sum()
+    }
+
+    // summed length of the unique edges of every group, edges on the border of two groups
+    // count for both
+    pub fn edge_length_by_group(&self) -> Vec<(String, f64)> {
+        self.faces_by_group()
+            .into_iter()
+            .map(|(name, _, faces)| {
+                let edges: HashSet<(usize, usize)> =
+                    faces.into_iter().flat_map(face_edges).collect();
+                let length = edges.into_iter().map(|edge| self.edge_length(edge)).sum();
+                (name.to_string(), length)
+            })
+            .collect()
+    }
+
+    // triangle count, surface area and volume of every group, merged and filtered
+    // the same way as `edge_length_by_group`
+    pub fn group_stats(&self) -> Vec<GroupStat> {
+        self.faces_by_group()
+            .into_iter()
+            .map(|(name, kind, faces)| {
+                let triangles: Vec<Triangle> = faces
+                    .iter()
+                    .flat_map(|face| face.triangles(&self.vertices))
+                    .collect();
+
+                let mut edges = HashMap::<(usize, usize), u32>::new();
+                for edge in faces.iter().flat_map(|face| face_edges(face)) {
+                    *edges.entry(edge).or_insert(0) += 1;
+                }
+                // only a closed group encloses a volume
+                let closed = !edges.is_empty() && edges.values().all(|&count| count == 2);
+
+                GroupStat {
+                    name: name.to_string(),
+                    kind,
+                    triangles: triangles.len(),
+                    area: triangles.iter().map(Triangle::area).sum(),
+                    volume: closed.then(|| {
+                        triangles
+                            .iter()
+                            .map(Triangle::signed_volume)
+                            .sum::<f64>()
+                            .abs()
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    // faces and kind of every group by name, in order of appearance
+    // groups sharing a name (e.g., split by `usemtl`) are merged and empty groups are left out
+    fn faces_by_group(&self) -> Vec<(&str, GroupKind, Vec<&Face>)> {
+        let mut groups: Vec<(&str, GroupKind, Vec<&Face>)> = Vec::new();
+
+        for group in &self.groups {
+            let faces = self.faces.get(group.face_range.clone()).unwrap_or_default();
+            if faces.is_empty() {
+                continue;
+            }
+
+            match groups.iter_mut().find(|(name, _, _)| *name == group.name) {
+                Some((_, _, group_faces)) => group_faces.extend(faces),
+                None => groups.push((&group.name, group.kind, faces.iter().collect())),
+            }
+        }
+
+        groups
+    }
+
+    fn edge_length(&self, (v0, v1): (usize, usize)) -> f64 {
+        let (a, b) = (self.vertices[v0], self.vertices[v1]);
+        let (dx, dy, dz) = (
+            a.0 as f64 - b.0 as f64,
+            a.1 as f64 - b.1 as f64,
+            a.2 as f64 - b.2 as f64,
+        );
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+}
+
+// edges of the face as (min, max) vertex index pairs
+// faces with less than 3 vertices have no edges and edges connecting a vertex to itself are skipped
+fn face_edges(face: &Face) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let n = if face.v.len() < 3 { 0 } else { face.v.len() };
+
+    (0..n).filter_map(move |i| {
+        let (v0, v1) = (face.v[i], face.v[(i + 1) % n]);
+        match v0.cmp(&v1) {
+            std::cmp::Ordering::Less => Some((v0, v1)),
+            std::cmp::Ordering::Greater => Some((v1, v0)),
+            std::cmp::Ordering::Equal => None,
+        }
+    })
 }
 
 impl Mesh {
+    // empty mesh, the same as `Mesh::default()`
     pub fn new() -> Self {
         Self {
             vertices: Vec::new(),
+            colors: Vec::new(),
+            weights: Vec::new(),
             normals: Vec::new(),
             textures: Vec::new(),
+            texture_w: Vec::new(),
             faces: Vec::new(),
+            face_colors: Vec::new(),
+            lines: Vec::new(),
+            points: Vec::new(),
             groups: Vec::new(),
             matlibs: Vec::new(),
+            materials: Vec::new(),
+            header: None,
+            unit: None,
+            face_areas: OnceLock::new(),
         }
     }
 
-    #[inline]
-    pub fn bounds(&self) -> anyhow::Result<(Vec3, Vec3), anyhow::Error> {
-        if self.vertices.is_empty() {
-            return Err(anyhow::anyhow!("mesh has no vertices"));
-        }
-
-        let (min_vertex, max_vertex) = self
-            .vertices
-            .par_iter()
-            .fold(
-                || {
-                    (
-                        Vec3(f32::MAX, f32::MAX, f32::MAX),
-                        Vec3(f32::MIN, f32::MIN, f32::MIN),
-                    )
-                },
-                |acc, vertex| {
-                    (
-                        Vec3(
-                            acc.0.0.min(vertex.0),
-                            acc.0.1.min(vertex.1),
-                            acc.0.2.min(vertex.2),
-                        ),
-                        Vec3(
-                            acc.1.0.max(vertex.0),
-                            acc.1.1.max(vertex.1),
-                            acc.1.2.max(vertex.2),
-                        ),
-                    )
-                },
-            )
-            .reduce(
-                || {
-                    (
-                        Vec3(f32::MAX, f32::MAX, f32::MAX),
-                        Vec3(f32::MIN, f32::MIN, f32::MIN),
-                    )
-                },
-                |a, b| {
-                    (
-                        Vec3(a.0.0.min(b.0.0), a.0.1.min(b.0.1), a.0.2.min(b.0.2)),
-                        Vec3(a.1.0.max(b.1.0), a.1.1.max(b.1.1), a.1.2.max(b.1.2)),
-                    )
-                },
-            );
-
-        Ok((min_vertex, max_vertex))
-    }
-
-    pub fn diagonal(&self) -> anyhow::Result<f32, anyhow::Error> {
-        let (min_vertex, max_vertex) = self.bounds()?;
+    // area of every face (the sum of its triangles), parallel to `faces`
+    // computed once and reused until the cache is invalidated
+    pub fn face_areas(&self) -> &[f64] {
+        self.face_areas.get_or_init(|| {
+            self.faces
+                .par_iter()
+                .map(|face| face.triangles(&self.vertices).map(|t| t.area()).sum())
+                .collect()
+        })
+    }
 
-        let dx = max_vertex.0 - min_vertex.0;
-        let dy = max_vertex.1 - min_vertex.1;
-        let dz = max_vertex.2 - min_vertex.2;
+    // clears the cached face areas, to be called after changing `vertices` or `faces`
+    pub fn invalidate_face_areas(&mut self) {
+        self.face_areas.take();
+    }
 
-        let current_diagonal = (dx * dx + dy * dy + dz * dz).sqrt();
-        if current_diagonal == 0.0 {
-            return Err(anyhow::anyhow!("mesh has 0 dimensions"));
+    // reads every material library referenced by the mesh
+    // `base_dir` is the directory of the OBJ file, since `mtllib` paths are relative to it
+    // materials are merged by name: a material defined again (in the same or a later
+    // library) replaces the earlier definition but keeps its place in the list
+    pub fn load_materials(&mut self, base_dir: &Path) -> Result<(), MeshError> {
+        let mut materials: Vec<Material> = Vec::new();
+        let mut positions = HashMap::new();
+        for matlib in &self.matlibs {
+            let bytes = std::fs::read(base_dir.join(matlib))?;
+            for material in mtl::parse_mtl(&bytes)? {
+                match positions.get(&material.name) {
+                    Some(&position) => materials[position] = material,
+                    None => {
+                        positions.insert(material.name.clone(), materials.len());
+                        materials.push(material);
+                    }
+                }
+            }
         }
 
-        Ok(current_diagonal)
+        self.materials = materials;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> Result<(Vec3, Vec3), MeshError> {
+        vertex_bounds(&self.vertices)
+    }
+
+    pub fn diagonal(&self) -> Result<f32, MeshError> {
+        vertex_diagonal(&self.vertices)
     }
 }
 
@@ -325,16 +1419,116 @@ impl Default for Mesh {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+// axis aligned bounding box of the vertices as (min, max) corners
+pub(crate) fn vertex_bounds(vertices: &[Vec3]) -> Result<(Vec3, Vec3), MeshError> {
+    // non finite vertices would poison the bounds, they are skipped
+    if !vertices.par_iter().any(|v| v.is_finite()) {
+        return Err(MeshError::Empty);
+    }
+
+    let (min_vertex, max_vertex) = vertices
+        .par_iter()
+        .filter(|v| v.is_finite())
+        .fold(
+            || {
+                (
+                    Vec3(f32::MAX, f32::MAX, f32::MAX),
+                    Vec3(f32::MIN, f32::MIN, f32::MIN),
+                )
+            },
+            |acc, vertex| {
+                (
+                    Vec3(
+                        acc.0.0.min(vertex.0),
+                        acc.0.1.min(vertex.1),
+                        acc.0.2.min(vertex.2),
+                    ),
+                    Vec3(
+                        acc.1.0.max(vertex.0),
+                        acc.1.1.max(vertex.1),
+                        acc.1.2.max(vertex.2),
+                    ),
+                )
+            },
+        )
+        .reduce(
+            || {
+                (
+                    Vec3(f32::MAX, f32::MAX, f32::MAX),
+                    Vec3(f32::MIN, f32::MIN, f32::MIN),
+                )
+            },
+            |a, b| {
+                (
+                    Vec3(a.0.0.min(b.0.0), a.0.1.min(b.0.1), a.0.2.min(b.0.2)),
+                    Vec3(a.1.0.max(b.1.0), a.1.1.max(b.1.1), a.1.2.max(b.1.2)),
+                )
+            },
+        );
+
+    Ok((min_vertex, max_vertex))
+}
+
+pub(crate) fn vertex_diagonal(vertices: &[Vec3]) -> Result<f32, MeshError> {
+    let (min_vertex, max_vertex) = vertex_bounds(vertices)?;
+
+    let dx = max_vertex.0 - min_vertex.0;
+    let dy = max_vertex.1 - min_vertex.1;
+    let dz = max_vertex.2 - min_vertex.2;
+
+    let current_diagonal = (dx * dx + dy * dy + dz * dz).sqrt();
+    if current_diagonal == 0.0 {
+        return Err(MeshError::ZeroDimensions);
+    }
+
+    Ok(current_diagonal)
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 // a face can be a triangle, quad or polygon with more than 4 vertices
 // 4 is choosen as the inline size for SmallVec to optimize for common cases
 pub struct Face {
     // vertex indices
     pub v: SmallVec<[usize; 4]>,
-    // vertex normal indices
-    pub vn: SmallVec<[usize; 4]>,
-    // vertex texture indices
-    pub vt: SmallVec<[usize; 4]>,
+    // vertex normal and texture indices of every corner, parallel to `v` when present
+    // empty when no corner has one, None for the corners without one
+    // (OBJ allows mixing corners such as `1/2/3` and `4//5` in a face)
+    pub vn: SmallVec<[Option<usize>; 4]>,
+    pub vt: SmallVec<[Option<usize>; 4]>,
+    // smoothing group this face belongs to
+    // None when smoothing is turned off (`s off` or `s 0` in OBJ)
+    pub smoothing_group: Option<u32>,
+}
+
+impl Face {
+    // reverses the winding of the face, flipping its normal
+    pub fn reverse(&mut self) {
+        self.v.reverse();
+        self.vt.reverse();
+        self.vn.reverse();
+    }
+
+    // unit normal of the face, using Newell's method so non planar polygons are handled
+    pub fn normal(&self, vertices: &[Vec3]) -> Vec3 {
+        compute_polygon_normal(vertices, &self.v).normalize()
+    }
+
+    // splits the face into triangles, using ear clipping for faces with more than 3 vertices
+    pub fn triangles<'a>(&self, vertices: &'a [Vec3]) -> impl Iterator<Item = Triangle> + 'a {
+        triangulate_face(vertices, &self.v)
+            .into_iter()
+            .map(|[a, b, c]| Triangle {
+                vertices: [vertices[a], vertices[b], vertices[c]],
+            })
+    }
+}
+
+// the OBJ directive that started a group, `o` for an object and `g` for a group
+// formats without the distinction use `Object` for their named parts (e.g., STL solids)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    Object,
+    Group,
 }
 
 #[derive(Debug, Clone)]
@@ -343,6 +1537,8 @@ pub struct Group {
     // e.g., "wheel", "door"
     pub name: String,
 
+    pub kind: GroupKind,
+
     // material name used by this group
     pub material: Option<String>,
 
@@ -350,9 +1546,31 @@ pub struct Group {
     pub face_range: Range<usize>,
 }
 
+// statistics of a single group, computed over its faces only
+#[derive(Debug, Clone)]
+pub struct GroupStat {
+    pub name: String,
+    pub kind: GroupKind,
+    pub triangles: usize,
+    pub area: f64,
+    // None when the faces of the group do not form a closed surface
+    pub volume: Option<f64>,
+}
+
 pub trait MeshCodec {
-    fn parse(&self, bytes: &[u8]) -> anyhow::Result<Mesh>;
-    fn write(&self, path: &std::path::Path, mesh: &Mesh) -> anyhow::Result<()>;
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError>;
+
+    // writes the mesh to any writer (a file, a buffer, stdout, ...)
+    // writes are not buffered, callers should wrap unbuffered writers in a `BufWriter`
+    fn write_to(&self, writer: &mut dyn Write, mesh: &Mesh) -> Result<(), MeshError>;
+
+    fn write(&self, path: &Path, mesh: &Mesh) -> Result<(), MeshError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer, mesh)?;
+
+        writer.flush()?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -369,14 +1587,31 @@ impl Triangle {
 
         (a.dot(&b.cross(&c))) / 6.0
     }
+
+    #[inline]
+    pub fn area(&self) -> f64 {
+        let a: Vector3<f64> = self.vertices[0].into();
+        let b: Vector3<f64> = self.vertices[1].into();
+        let c: Vector3<f64> = self.vertices[2].into();
+
+        (b - a).cross(&(c - a)).norm() / 2.0
+    }
+
+    // unit normal following the right hand rule, (0, 0, 0) for degenerate triangles
+    #[inline]
+    pub fn normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        (v1 - v0).cross(v2 - v0).normalize()
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Vec3(pub f32, pub f32, pub f32);
 
 impl Vec3 {
+    #[deprecated(note = "use the `-` operator")]
     pub fn substraction(self, other: Vec3) -> Vec3 {
-        Vec3(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+        self - other
     }
 
     pub fn cross(self, other: Vec3) -> Vec3 {
@@ -410,6 +1645,44 @@ impl Vec3 {
     }
 }
 
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Vec3) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, factor: f32) -> Vec3 {
+        Vec3(self.0 * factor, self.1 * factor, self.2 * factor)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3(-self.0, -self.1, -self.2)
+    }
+}
+
 impl From<[f32; 3]> for Vec3 {
     fn from(arr: [f32; 3]) -> Self {
         Vec3(arr[0], arr[1], arr[2])
@@ -426,8 +1699,9 @@ impl From<Vec3> for Vector3<f64> {
 pub struct Vec2(pub f32, pub f32);
 
 impl Vec2 {
+    #[deprecated(note = "use the `-` operator")]
     pub fn substraction(self, other: Vec2) -> Self {
-        Vec2(self.0 - other.0, self.1 - other.1)
+        self - other
     }
 
     pub fn cross(self, other: Vec2) -> f32 {
@@ -438,3 +1712,702 @@ impl Vec2 {
         self.0 * other.0 + self.1 * other.1
     }
 }
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: f32) -> Vec2 {
+        Vec2(self.0 * factor, self.1 * factor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2(-self.0, -self.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn gzip_compressed_stl_has_the_same_triangles() {
+        let mut stl = Vec::new();
+        stl::StlCodec::default()
+            .write_to(&mut stl, &fixtures::cube())
+            .unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&stl).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(is_gzip(&compressed));
+        assert_eq!(decompress(&compressed).unwrap().as_ref(), stl.as_slice());
+        let (plain, _) = crate::load_bytes(&stl, None).unwrap();
+        let (unpacked, format) = crate::load_bytes(&compressed, None).unwrap();
+        assert_eq!(format, Format::STL);
+        assert_eq!(unpacked.triangle_count(), plain.triangle_count());
+        assert_eq!(unpacked.vertices, plain.vertices);
+    }
+
+    #[test]
+    fn codecs_write_to_a_buffer_they_can_parse() {
+        let cube = fixtures::cube();
+        let codecs: [&dyn MeshCodec; 3] =
+            [&stl::StlCodec::default(), &obj::ObjCodec, &off::OffCodec];
+        for codec in codecs {
+            let mut buffer = Vec::new();
+            codec.write_to(&mut buffer, &cube).unwrap();
+
+            let mut parsed = codec.parse(&buffer).unwrap();
+            parsed.weld();
+            assert_eq!(parsed.triangle_count(), cube.triangle_count());
+            assert_eq!(parsed.bounds().unwrap(), cube.bounds().unwrap());
+        }
+    }
+
+    #[test]
+    fn gz_extension_is_stripped() {
+        assert_eq!(Format::from_name("part.stl.gz"), Some(Format::STL));
+        assert_eq!(Format::from_name("PART.OBJ.GZ"), Some(Format::OBJ));
+        assert_eq!(Format::from_name("part.gz"), None);
+    }
+
+    #[test]
+    fn triangles_match_the_triangle_count() {
+        let mut mesh = fixtures::cube();
+        // a pentagon and a line segment, which yields no triangle
+        mesh.vertices.push(Vec3(0.5, 0.5, 2.0));
+        mesh.faces.push(Face {
+            v: [4, 5, 8, 6, 7].into_iter().collect(),
+            ..Face::default()
+        });
+        mesh.faces.push(Face {
+            v: [0, 1].into_iter().collect(),
+            ..Face::default()
+        });
+
+        assert_eq!(mesh.triangle_count(), 6 * 2 + 3);
+        assert_eq!(mesh.triangles().count(), mesh.triangle_count());
+
+        let faces: Vec<usize> = mesh.triangles_with_face_index().map(|(f, _)| f).collect();
+        assert_eq!(faces.len(), mesh.triangle_count());
+        assert_eq!(&faces[10..], &[5, 5, 6, 6, 6]);
+    }
+
+    #[test]
+    fn closed_cube_is_watertight() {
+        let cube = fixtures::cube();
+        assert!(cube.is_watertight());
+        assert!(cube.boundary_edges().is_empty());
+    }
+
+    #[test]
+    fn open_cube_reports_the_rim_of_its_hole() {
+        let open = fixtures::open_cube();
+        assert!(!open.is_watertight());
+
+        let mut edges = open.boundary_edges();
+        edges.sort_unstable();
+        // the top face was 5 6 7 8 (1-based)
+        assert_eq!(edges, [(4, 5), (4, 7), (5, 6), (6, 7)]);
+    }
+
+    #[test]
+    fn empty_mesh_is_not_watertight() {
+        assert!(!Mesh::new().is_watertight());
+    }
+
+    #[test]
+    fn duplicate_faces_are_removed() {
+        let mut doubled = fixtures::obj(fixtures::TETRAHEDRON);
+        let volume = crate::calculate::volume(&doubled);
+        let faces = doubled.faces.clone();
+        // the same triangles starting at another corner
+        doubled.faces.extend(faces.iter().map(|face| {
+            let mut face = face.clone();
+            face.v.rotate_left(1);
+            face
+        }));
+        assert!((crate::calculate::volume(&doubled) - 2.0 * volume).abs() < 1e-9);
+
+        assert_eq!(doubled.remove_duplicate_faces(false), 4);
+        assert_eq!(doubled.faces, faces);
+        assert!((crate::calculate::volume(&doubled) - volume).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reversed_duplicates_only_count_when_ignoring_the_winding() {
+        let mut mesh = fixtures::obj(fixtures::TETRAHEDRON);
+        let mut reversed = mesh.faces[0].clone();
+        reversed.v.reverse();
+        mesh.faces.push(reversed);
+
+        assert_eq!(mesh.remove_duplicate_faces(false), 0);
+        assert_eq!(mesh.remove_duplicate_faces(true), 1);
+        assert_eq!(mesh.faces.len(), 4);
+    }
+
+    #[test]
+    fn diagonal_wall_inside_a_cube_is_interior() {
+        let mut walled = fixtures::obj(&format!("{}f 1 3 7 5\n", fixtures::CUBE));
+        assert_eq!(walled.interior_faces(), [6]);
+
+        // with either winding
+        walled.faces[6].v.reverse();
+        assert_eq!(walled.interior_faces(), [6]);
+    }
+
+    #[test]
+    fn closed_cube_has_no_interior_faces() {
+        assert!(fixtures::cube().interior_faces().is_empty());
+    }
+
+    #[test]
+    fn closed_cube_uses_every_edge_twice() {
+        let topology = fixtures::cube().topology();
+        assert_eq!(topology.len(), 12);
+        assert!(topology.values().all(|&count| count == 2));
+        assert!(topology.keys().all(|&(a, b)| a < b));
+    }
+
+    #[test]
+    fn open_cube_has_four_boundary_edges() {
+        let topology = fixtures::open_cube().topology();
+        let mut boundary: Vec<(usize, usize)> = topology
+            .iter()
+            .filter(|&(_, &count)| count == 1)
+            .map(|(&edge, _)| edge)
+            .collect();
+        boundary.sort_unstable();
+
+        assert_eq!(topology.len(), 12);
+        assert_eq!(boundary, [(4, 5), (4, 7), (5, 6), (6, 7)]);
+        assert!(fixtures::open_cube().non_manifold_edges().is_empty());
+    }
+
+    #[test]
+    fn fin_on_an_edge_is_non_manifold() {
+        let fin = fixtures::obj(&format!("{}v 0.5 -1 -1\nf 1 2 9\n", fixtures::CUBE));
+        assert_eq!(fin.topology()[&(0, 1)], 3);
+        assert_eq!(fin.non_manifold_edges(), [(0, 1)]);
+    }
+
+    #[test]
+    fn flipping_reverses_the_winding_and_the_volume() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nvn 0 0 1\nvt 0 0\nvt 1 0\nvt 0 1\n\
+            f 1/1/1 3/3/1 2/2/1\nf 1 2 4\nf 1 4 3\nf 2 3 4\n";
+        let mut tetrahedron = fixtures::obj(source);
+        let volume = crate::calculate::signed_volume(&tetrahedron);
+        assert!(volume > 0.0);
+
+        tetrahedron.flip();
+        assert!((crate::calculate::signed_volume(&tetrahedron) + volume).abs() < 1e-9);
+        let face = &tetrahedron.faces[0];
+        assert_eq!(face.v.as_slice(), [1, 2, 0]);
+        assert_eq!(face.vt.as_slice(), [Some(1), Some(2), Some(0)]);
+        assert_eq!(tetrahedron.normals, [Vec3(0.0, 0.0, -1.0)]);
+
+        tetrahedron.flip();
+        assert!((crate::calculate::signed_volume(&tetrahedron) - volume).abs() < 1e-9);
+    }
+
+    // the unit cube split into a `bottom` group and a `sides` group, which also holds the top
+    fn grouped_cube() -> Mesh {
+        let source = fixtures::CUBE.replacen("f ", "g bottom\nf ", 1).replacen(
+            "f 5 6 7 8",
+            "g sides\nf 5 6 7 8",
+            1,
+        );
+        fixtures::obj(&source)
+    }
+
+    #[test]
+    fn cube_edges_add_up_to_twelve() {
+        assert!((fixtures::cube().total_edge_length() - 12.0).abs() < 1e-9);
+
+        let by_group = grouped_cube().edge_length_by_group();
+        assert_eq!(by_group.len(), 2);
+        assert_eq!(by_group[0].0, "bottom");
+        assert!((by_group[0].1 - 4.0).abs() < 1e-9);
+        assert_eq!(by_group[1].0, "sides");
+        assert!((by_group[1].1 - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn group_stats_cover_every_group() {
+        // the cube and a separate closed box as a second group
+        let mut mesh = fixtures::cube();
+        mesh.groups = vec![Group {
+            name: String::from("body"),
+            kind: GroupKind::Group,
+            material: None,
+            face_range: 0..6,
+        }];
+        let mut wheel = fixtures::box_mesh(Vec3(2.0, 0.0, 0.0), Vec3(4.0, 1.0, 1.0));
+        wheel.groups = vec![Group {
+            name: String::from("wheel"),
+            kind: GroupKind::Object,
+            material: None,
+            face_range: 0..12,
+        }];
+        mesh.merge(&wheel);
+
+        let stats = mesh.group_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].name.as_str(), stats[0].triangles), ("body", 12));
+        assert!((stats[0].area - 6.0).abs() < 1e-6);
+        assert!((stats[0].volume.unwrap() - 1.0).abs() < 1e-6);
+        assert_eq!(
+            (stats[1].name.as_str(), stats[1].kind),
+            ("wheel", GroupKind::Object)
+        );
+        assert_eq!(stats[1].triangles, 12);
+        assert!((stats[1].area - 10.0).abs() < 1e-6);
+        assert!((stats[1].volume.unwrap() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn open_group_has_no_volume() {
+        let stats = grouped_cube().group_stats();
+        assert_eq!(stats[0].name, "bottom");
+        assert_eq!(stats[0].triangles, 2);
+        assert_eq!(stats[0].volume, None);
+        assert_eq!(stats[1].volume, None);
+    }
+
+    #[test]
+    fn sorted_weld_does_not_depend_on_the_vertex_order() {
+        let obj = |mesh: &Mesh| {
+            let mut bytes = Vec::new();
+            obj::ObjCodec.write_to(&mut bytes, mesh).unwrap();
+            bytes
+        };
+        let mut stl = Vec::new();
+        stl::StlCodec::default()
+            .write_to(&mut stl, &fixtures::cube())
+            .unwrap();
+        let parse = || {
+            let mut mesh = stl::StlCodec::default().parse(&stl).unwrap();
+            mesh.weld_sorted();
+            mesh
+        };
+        assert_eq!(obj(&parse()), obj(&parse()));
+
+        // the cube with its vertices listed in reverse
+        let mut cube = fixtures::cube();
+        let mut reversed = cube.clone();
+        reversed.vertices.reverse();
+        for face in &mut reversed.faces {
+            for idx in face.v.iter_mut() {
+                *idx = 7 - *idx;
+            }
+        }
+        assert_ne!(obj(&cube), obj(&reversed));
+
+        cube.weld_sorted();
+        reversed.weld_sorted();
+        assert_eq!(obj(&cube), obj(&reversed));
+    }
+
+    #[test]
+    fn sanitized_mesh_keeps_the_bounds_of_the_finite_geometry() {
+        let mut mesh = fixtures::cube();
+        mesh.vertices.push(Vec3(f32::NAN, 0.0, 0.0));
+        mesh.faces.push(Face {
+            v: [0, 1, 8].into_iter().collect(),
+            ..Face::default()
+        });
+
+        assert_eq!(mesh.sanitize(), 1);
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.faces.len(), 6);
+        assert_eq!(
+            mesh.bounds().unwrap(),
+            (Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0))
+        );
+        assert_eq!(mesh.sanitize(), 0);
+    }
+
+    #[test]
+    fn unwelded_triangles_repeat_the_cube_corners() {
+        // every corner of every triangle stored separately, as STL does
+        let mut raw = Mesh::new();
+        for triangle in fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1.0)).triangles() {
+            let start = raw.vertices.len();
+            raw.vertices.extend(triangle.vertices);
+            raw.faces.push(Face {
+                v: (start..start + 3).collect(),
+                ..Face::default()
+            });
+        }
+
+        assert_eq!(raw.vertices.len(), 36);
+        assert_eq!(raw.duplicate_vertex_count(), 28);
+        let stats = raw.weld();
+        // the first triangle keeps its vertices, every other one is remapped
+        assert_eq!(
+            stats,
+            WeldStats {
+                vertices_before: 36,
+                vertices_after: 8,
+                faces_remapped: 11,
+            }
+        );
+        assert_eq!(raw.vertices.len(), 8);
+        assert_eq!(raw.duplicate_vertex_count(), 0);
+    }
+
+    #[test]
+    fn face_areas_are_recomputed_after_a_scale() {
+        let mut mesh = fixtures::cube();
+        assert_eq!(mesh.face_areas(), [1.0; 6]);
+
+        // twice the diagonal, four times the area
+        mesh.scale(2.0 * 3.0f32.sqrt()).unwrap();
+        for area in mesh.face_areas() {
+            assert!((area - 4.0).abs() < 1e-5, "{}", area);
+        }
+    }
+
+    #[test]
+    fn parallel_face_areas_match_a_serial_pass() {
+        let sphere = fixtures::uv_sphere(2.0, 32, 64);
+        let serial: Vec<f64> = sphere
+            .faces
+            .iter()
+            .map(|face| face.triangles(&sphere.vertices).map(|t| t.area()).sum())
+            .collect();
+        assert_eq!(sphere.face_areas(), serial.as_slice());
+    }
+
+    #[test]
+    fn opposite_missing_faces_are_two_holes() {
+        let mut mesh = fixtures::cube();
+        // the top, then the bottom
+        mesh.faces.remove(1);
+        mesh.faces.remove(0);
+        mesh.groups.clear();
+
+        assert_eq!(mesh.hole_count(), 2);
+        let mut loops = mesh.boundary_loops();
+        for boundary in &mut loops {
+            boundary.sort_unstable();
+        }
+        loops.sort();
+        assert_eq!(loops, [vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+    }
+
+    #[test]
+    fn closed_cube_has_no_holes() {
+        assert_eq!(fixtures::cube().hole_count(), 0);
+        assert_eq!(fixtures::open_cube().hole_count(), 1);
+    }
+
+    #[test]
+    fn quantizing_merges_vertices_in_the_same_cell() {
+        // two triangles whose shared corner is stored twice, 0.001 apart
+        let mut mesh = fixtures::obj(
+            "v 0 0 0\nv 10 0 0\nv 10 10 0\nv 0.001 0.001 0\nv 0 10 0\nf 1 2 3\nf 4 3 5\n",
+        );
+        assert_eq!(mesh.vertices.len(), 5);
+
+        mesh.quantize(0.01).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.faces[1].v.as_slice(), [0, 2, 3]);
+        assert_eq!(mesh.vertices[0], Vec3(0.0, 0.0, 0.0));
+        assert!(mesh.quantize(0.0).is_err());
+    }
+
+    #[test]
+    fn material_libraries_are_merged_by_name() {
+        let dir = std::env::temp_dir().join(format!("mesh_rs-mtllib-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("a.mtl"),
+            "newmtl red\nKd 1 0 0\nnewmtl shared\nKd 1 1 1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("b.mtl"),
+            "newmtl shared\nKd 0 1 0\nnewmtl blue\nKd 0 0 1\n",
+        )
+        .unwrap();
+
+        let source = format!(
+            "mtllib a.mtl\nmtllib b.mtl\nmtllib a.mtl\n{}",
+            fixtures::CUBE
+        );
+        let mut mesh = fixtures::obj(&source);
+        assert_eq!(mesh.matlibs, ["a.mtl", "b.mtl"]);
+
+        mesh.load_materials(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let materials: Vec<(&str, [f32; 3])> = mesh
+            .materials
+            .iter()
+            .map(|material| (material.name.as_str(), material.diffuse))
+            .collect();
+        // a later definition wins but keeps the place of the first one
+        assert_eq!(
+            materials,
+            [
+                ("red", [1.0, 0.0, 0.0]),
+                ("shared", [0.0, 1.0, 0.0]),
+                ("blue", [0.0, 0.0, 1.0])
+            ]
+        );
+    }
+
+    #[test]
+    fn triangle_count_matches_the_written_stl() {
+        // a triangle, a quad, a concave hexagon, a line segment and a lone vertex
+        let mut mesh = fixtures::obj(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\nv 1 2 0\n\
+            f 1 2 3\nf 1 2 3 4\nf 1 5 6 8 3 4\nf 1 2\n",
+        );
+        mesh.faces.push(Face {
+            v: [6].into_iter().collect(),
+            ..Face::default()
+        });
+        assert_eq!(mesh.triangle_count(), 1 + 2 + 4);
+
+        let mut stl = Vec::new();
+        stl::StlCodec::default().write_to(&mut stl, &mesh).unwrap();
+        let declared = u32::from_le_bytes([stl[80], stl[81], stl[82], stl[83]]) as usize;
+        assert_eq!(declared, mesh.triangle_count());
+        assert_eq!(stl.len(), 84 + 50 * declared);
+    }
+
+    // `size` x `size` squares in the XY plane, as quads or split along the same diagonal
+    fn grid(size: usize, triangles: bool) -> Mesh {
+        let mut mesh = Mesh::new();
+        for y in 0..=size {
+            for x in 0..=size {
+                mesh.vertices.push(Vec3(x as f32, y as f32, 0.0));
+            }
+        }
+
+        let index = |x: usize, y: usize| y * (size + 1) + x;
+        for y in 0..size {
+            for x in 0..size {
+                let corners = [
+                    index(x, y),
+                    index(x + 1, y),
+                    index(x + 1, y + 1),
+                    index(x, y + 1),
+                ];
+                let faces: Vec<Vec<usize>> = if triangles {
+                    vec![
+                        vec![corners[0], corners[1], corners[2]],
+                        vec![corners[0], corners[2], corners[3]],
+                    ]
+                } else {
+                    vec![corners.to_vec()]
+                };
+                mesh.faces.extend(faces.into_iter().map(|v| Face {
+                    v: v.into_iter().collect(),
+                    ..Face::default()
+                }));
+            }
+        }
+
+        mesh
+    }
+
+    #[test]
+    fn regular_triangle_grid_has_no_irregular_vertices() {
+        let mesh = grid(4, true);
+        // the 9 interior vertices
+        assert_eq!(mesh.valence_histogram()[&6], 9);
+        assert_eq!(mesh.irregular_vertex_count(), 0);
+    }
+
+    #[test]
+    fn regular_quad_grid_has_no_irregular_vertices() {
+        let mesh = grid(4, false);
+        assert_eq!(mesh.valence_histogram()[&4], 9);
+        assert_eq!(mesh.irregular_vertex_count(), 0);
+        // the corners of a quad cube meet 3 edges
+        assert_eq!(fixtures::cube().irregular_vertex_count(), 8);
+    }
+
+    #[test]
+    fn icosphere_has_12_valence_5_vertices() {
+        for subdivisions in [1, 2] {
+            let mesh = fixtures::icosphere(1.0, subdivisions);
+            assert!(mesh.is_watertight());
+            assert!(crate::calculate::volume(&mesh) > 0.0);
+
+            // the corners of the icosahedron, every other vertex is regular
+            let histogram = mesh.valence_histogram();
+            assert_eq!(histogram[&5], 12);
+            assert_eq!(histogram[&6], mesh.vertices.len() - 12);
+            assert_eq!(histogram.len(), 2);
+            assert_eq!(mesh.irregular_vertex_count(), 12);
+        }
+    }
+
+    #[test]
+    fn welding_merged_cubes_keeps_the_corner_normals() {
+        // the cube with a flat normal per face
+        let source = "\
+v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1
+vn 0 0 -1\nvn 0 0 1\nvn 0 -1 0\nvn 1 0 0\nvn 0 1 0\nvn -1 0 0
+f 1//1 4//1 3//1 2//1\nf 5//2 6//2 7//2 8//2\nf 1//3 2//3 6//3 5//3
+f 2//4 3//4 7//4 6//4\nf 3//5 4//5 8//5 7//5\nf 4//6 1//6 5//6 8//6
+";
+        let cube = obj::ObjCodec.parse(source.as_bytes()).unwrap();
+        let normal_of = |mesh: &Mesh, face: usize| -> Vec<Vec3> {
+            mesh.faces[face]
+                .vn
+                .iter()
+                .map(|vn| mesh.normals[vn.unwrap()])
+                .collect()
+        };
+
+        let mut merged = cube.clone();
+        merged.merge(&cube);
+        assert_eq!(merged.vertices.len(), 16);
+        let stats = merged.weld();
+        assert_eq!(stats.vertices_after, 8);
+
+        assert_eq!(merged.faces.len(), 12);
+        for face in 0..12 {
+            assert_eq!(merged.faces[face].v, cube.faces[face % 6].v);
+            assert_eq!(normal_of(&merged, face), normal_of(&cube, face % 6));
+        }
+    }
+
+    #[test]
+    fn vec3_operators_match_hand_computed_results() {
+        let (a, b) = (Vec3(1.0, -2.0, 3.0), Vec3(0.5, 4.0, -1.0));
+        assert_eq!(a + b, Vec3(1.5, 2.0, 2.0));
+        assert_eq!(a - b, Vec3(0.5, -6.0, 4.0));
+        assert_eq!(a * 2.0, Vec3(2.0, -4.0, 6.0));
+        assert_eq!(-a, Vec3(-1.0, 2.0, -3.0));
+
+        let mut sum = a;
+        sum += b;
+        assert_eq!(sum, a + b);
+        #[allow(deprecated)]
+        let difference = a.substraction(b);
+        assert_eq!(difference, a - b);
+    }
+
+    #[test]
+    fn vec2_operators_match_hand_computed_results() {
+        let (a, b) = (Vec2(1.0, -2.0), Vec2(0.5, 4.0));
+        assert_eq!(a + b, Vec2(1.5, 2.0));
+        assert_eq!(a - b, Vec2(0.5, -6.0));
+        assert_eq!(a * 2.0, Vec2(2.0, -4.0));
+        assert_eq!(-a, Vec2(-1.0, 2.0));
+
+        let mut sum = a;
+        sum += b;
+        assert_eq!(sum, a + b);
+        #[allow(deprecated)]
+        let difference = a.substraction(b);
+        assert_eq!(difference, a - b);
+    }
+
+    #[test]
+    fn solid_prefixed_obj_is_read_by_its_extension() {
+        // an unknown statement to OBJ, but the start of an ASCII STL
+        let source = format!("solid facet and vertex data\n{}", fixtures::CUBE);
+        let bytes = source.as_bytes();
+        assert_eq!(Format::from_magic_bytes(bytes), Some(Format::STL));
+
+        let path = Path::new("model.obj");
+        assert_eq!(Format::detect(bytes, Some(path), None), Some(Format::OBJ));
+        // an explicit format wins over both
+        assert_eq!(
+            Format::detect(bytes, Some(path), Some(Format::STL)),
+            Some(Format::STL)
+        );
+        // without a name the content decides
+        assert_eq!(Format::detect(bytes, None, None), Some(Format::STL));
+    }
+
+    // a unit square facing +Z, with stored normals off by 0, 0, 90 and 180 degrees
+    const WRONG_NORMALS: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+        vn 0 0 1\nvn 1 0 0\nvn 0 0 -1\nf 1//1 2//1 3//2 4//3\n";
+
+    #[test]
+    fn wrong_normals_deviate_from_the_geometry() {
+        let mesh = fixtures::obj(WRONG_NORMALS);
+
+        let deviations = mesh.normal_deviations();
+        let expected = [0.0, 0.0, 90.0, 180.0];
+        assert_eq!(deviations.len(), 4);
+        for (deviation, expected) in deviations.iter().zip(expected) {
+            assert!((deviation - expected).abs() < 1e-3, "{:?}", deviations);
+        }
+        let (mean, max) = mesh.normal_deviation().unwrap();
+        assert!((mean - 67.5).abs() < 1e-3);
+        assert!((max - 180.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn meshes_without_stored_normals_have_no_deviation() {
+        assert_eq!(fixtures::cube().normal_deviation(), None);
+    }
+
+    #[test]
+    fn closed_and_open_shells_are_told_apart() {
+        let mut mesh = fixtures::cube();
+        let mut open = fixtures::open_cube();
+        crate::calculate::translate(&mut open, Vec3(3.0, 0.0, 0.0));
+        mesh.merge(&open);
+
+        assert_eq!(mesh.face_components().1, 2);
+        assert_eq!(mesh.component_watertightness(), [true, false]);
+        assert!(!mesh.is_watertight());
+    }
+
+    #[test]
+    fn cube_edges_are_right_angles() {
+        let cube = fixtures::cube();
+
+        let angles = cube.dihedral_angles();
+        assert_eq!(angles.len(), 12);
+        assert!(angles.iter().all(|angle| (angle - 90.0).abs() < 1e-3));
+        assert_eq!(cube.sharp_edges(45.0).len(), 12);
+        assert!(cube.sharp_edges(91.0).is_empty());
+    }
+
+    #[test]
+    fn coplanar_triangles_have_no_dihedral_angle() {
+        let square = fixtures::obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3\nf 1 3 4\n");
+        assert_eq!(square.dihedral_angles(), [0.0]);
+    }
+}
@@ -0,0 +1,102 @@
+// MTL file format
+// MTL files are ASCII text files referenced by OBJ files through `mtllib`
+// newmtl name      | start a new material
+// Ka r g b         | ambient color
+// Kd r g b         | diffuse color
+// Ks r g b         | specular color
+// map_Ka file      | ambient texture map
+// map_Kd file      | diffuse texture map
+// map_Ks file      | specular texture map
+// # comment        | comment line
+use crate::model::MeshError;
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    // material name, referenced by `usemtl` in the OBJ file
+    pub name: String,
+
+    // colors in the 0..1 range
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+
+    // texture map paths, relative to the MTL file
+    pub ambient_map: Option<String>,
+    pub diffuse_map: Option<String>,
+    pub specular_map: Option<String>,
+}
+
+impl Material {
+    pub fn new(name: &str) -> Self {
+        // defaults as defined by the MTL specification
+        Self {
+            name: name.to_string(),
+            ambient: [0.2, 0.2, 0.2],
+            diffuse: [0.8, 0.8, 0.8],
+            specular: [1.0, 1.0, 1.0],
+            ambient_map: None,
+            diffuse_map: None,
+            specular_map: None,
+        }
+    }
+}
+
+pub fn parse_mtl(bytes: &[u8]) -> Result<Vec<Material>, MeshError> {
+    let content = std::str::from_utf8(bytes)?;
+    let mut materials: Vec<Material> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((keyword, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let rest = rest.trim();
+
+        if keyword == "newmtl" {
+            materials.push(Material::new(rest));
+            continue;
+        }
+
+        // every other statement belongs to the last declared material
+        let Some(material) = materials.last_mut() else {
+            continue;
+        };
+
+        match keyword {
+            "Ka" => material.ambient = parse_color(rest).unwrap_or(material.ambient),
+            "Kd" => material.diffuse = parse_color(rest).unwrap_or(material.diffuse),
+            "Ks" => material.specular = parse_color(rest).unwrap_or(material.specular),
+            "map_Ka" => material.ambient_map = parse_map(rest),
+            "map_Kd" => material.diffuse_map = parse_map(rest),
+            "map_Ks" => material.specular_map = parse_map(rest),
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+fn parse_color(value: &str) -> Option<[f32; 3]> {
+    let parts: Vec<f32> = value
+        .split_whitespace()
+        .map(|part| part.parse::<f32>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+
+    match parts.as_slice() {
+        // a single value applies to all the channels
+        [v] => Some([*v, *v, *v]),
+        [r, g, b, ..] => Some([*r, *g, *b]),
+        _ => None,
+    }
+}
+
+fn parse_map(value: &str) -> Option<String> {
+    // map statements may carry options (e.g., `-s 1 1 1 file.png`)
+    // the file name is always the last token
+    value.split_whitespace().last().map(|file| file.to_string())
+}
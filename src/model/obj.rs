@@ -1,18 +1,26 @@
 // file format
 // OBJ files are ASCII text files with the following line types:
 // v x y z          | vertex position
-// vt u v           | texture coordinate
+// v x y z r g b    | vertex position with color (0..1)
+// v x y z w        | vertex position with a weight (rational curves and surfaces)
+// vt u v [w]       | texture coordinate, with an optional depth
 // vn x y z         | vertex normal
 // f v1 v2 v3       | face (triangle) - can reference v/vt/vn indices (starts at 1)
 // f v1/vt1 v2/vt2 v3/vt3           | face with texture coords
 // f v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3 | face with texture and normals
 // f v1//vn1 v2//vn2 v3//vn3        | face with normals only
+// f -3 -2 -1       | face with indices relative to the end of the vertex list
 // # comment        | comment line
 // o name           | object name
 // g name           | group name
 // mtllib file      | material library
 // usemtl name      | use material
-use crate::model::{Face, Group, Mesh, MeshCodec, Vec2, Vec3};
+// s n | s off      | smoothing group for the following faces
+// l v1 v2 v3       | polyline through the vertices, can reference v/vt indices
+// p v1 v2          | points at the vertices
+use crate::model::{
+    DEFAULT_PRECISION, Face, Group, GroupKind, Mesh, MeshCodec, MeshError, Vec2, Vec3,
+};
 use std::{
     fs::File,
     io::{BufRead, BufWriter, Cursor, Write},
@@ -21,137 +29,33 @@ use std::{
 
 pub struct ObjCodec;
 
-impl MeshCodec for ObjCodec {
-    fn parse(&self, bytes: &[u8]) -> anyhow::Result<Mesh> {
-        let mut mesh = Mesh::default();
-        let mut cursor = Cursor::new(bytes);
-        let mut line_buf = String::new();
-
-        let mut current_name = String::from("mesh_rs");
-        let mut current_material: Option<String> = None;
-
-        mesh.groups.push(Group {
-            name: current_name.clone(),
-            material: current_material.clone(),
-            face_range: 0..0,
-        });
-
-        while cursor.read_line(&mut line_buf)? > 0 {
-            let line = line_buf.trim();
-
-            if line.is_empty() {
-                line_buf.clear();
-                continue;
-            }
-
-            if line.starts_with("v ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 4
-                    && let (Ok(x), Ok(y), Ok(z)) = (
-                        parts[1].parse::<f32>(),
-                        parts[2].parse::<f32>(),
-                        parts[3].parse::<f32>(),
-                    )
-                {
-                    mesh.vertices.push(Vec3(x, y, z));
-                }
-            } else if line.starts_with("vt ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 3
-                    && let (Ok(u), Ok(v)) = (parts[1].parse::<f32>(), parts[2].parse::<f32>())
-                {
-                    mesh.textures.push(Vec2(u, v));
-                }
-            } else if line.starts_with("vn ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 4
-                    && let (Ok(x), Ok(y), Ok(z)) = (
-                        parts[1].parse::<f32>(),
-                        parts[2].parse::<f32>(),
-                        parts[3].parse::<f32>(),
-                    )
-                {
-                    mesh.normals.push(Vec3(x, y, z));
-                }
-            // Face parsing
-            // v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3 # face with texture and normals
-            // v1//vn1 v2//vn2 v3//vn3 # face with normals only
-            // v1/vt1 v2/vt2 v3/vt3 # face with only texture index
-            } else if line.starts_with("f ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                let mut face = Face::default();
-
-                for part in parts.iter().skip(1) {
-                    let segemnt: Vec<&str> = part.split('/').collect();
-
-                    if let Ok(idx) = segemnt[0].parse::<u32>() {
-                        face.v.push((idx - 1) as usize); // OBJ indices are 1-based
-                    } else {
-                        // vertex index is required to process the face
-                        continue;
-                    }
-
-                    // texture index (optional)
-                    if segemnt.len() > 1
-                        && !segemnt[1].is_empty()
-                        && let Ok(idx) = segemnt[1].parse::<u32>()
-                    {
-                        face.vt.push((idx - 1) as usize);
-                    }
-
-                    // normal index (optional)
-                    if segemnt.len() > 2
-                        && !segemnt[2].is_empty()
-                        && let Ok(idx) = segemnt[2].parse::<u32>()
-                    {
-                        face.vn.push((idx - 1) as usize);
-                    }
-                }
-
-                mesh.faces.push(face);
-            } else if let Some(matlib) = line.strip_prefix("mtllib ") {
-                mesh.matlibs.push(matlib.trim().to_string());
-            } else if line.starts_with("o ")
-                || line.starts_with("g ")
-                || line.starts_with("usemtl ")
-            {
-                // close the range of the previous group
-                if let Some(last_group) = mesh.groups.last_mut() {
-                    last_group.face_range.end = mesh.faces.len();
-                }
-
-                match line.starts_with("usemtl ") {
-                    true => {
-                        current_material = Some(line[7..].trim().to_string());
-                    }
-                    false => {
-                        // trim the "o " or "g "
-                        current_name = line[2..].trim().to_string();
-                    }
-                }
-
-                mesh.groups.push(Group {
-                    name: current_name.clone(),
-                    material: current_material.clone(),
-                    face_range: mesh.faces.len()..mesh.faces.len(),
-                });
-            }
-
-            line_buf.clear();
-        }
+impl ObjCodec {
+    // same as `parse`, but instead of skipping malformed lines it fails
+    // with the 1-based line number and the offending token
+    pub fn parse_strict(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
+        parse_obj(bytes, true)
+    }
 
-        // close the range of the last group
-        if let Some(last_group) = mesh.groups.last_mut() {
-            last_group.face_range.end = mesh.faces.len();
-        }
+    // same as `write`, with `decimals` digits after the decimal point instead of the default 6
+    pub fn write_with_precision(
+        &self,
+        path: &Path,
+        mesh: &Mesh,
+        decimals: usize,
+    ) -> Result<(), MeshError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to_with_precision(&mut writer, mesh, decimals)?;
 
-        Ok(mesh)
+        writer.flush()?;
+        Ok(())
     }
 
-    fn write(&self, path: &Path, mesh: &Mesh) -> anyhow::Result<()> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-
+    pub fn write_to_with_precision(
+        &self,
+        writer: &mut dyn Write,
+        mesh: &Mesh,
+        decimals: usize,
+    ) -> Result<(), MeshError> {
         writeln!(writer, "# created by mesh_rs")?;
 
         // write material libraries
@@ -160,63 +64,491 @@ impl MeshCodec for ObjCodec {
         }
 
         // write data arrays
-        for v in &mesh.vertices {
-            writeln!(writer, "v {:.6} {:.6} {:.6}", v.0, v.1, v.2)?;
+        if mesh.colors.len() == mesh.vertices.len() {
+            for (v, [r, g, b]) in mesh.vertices.iter().zip(&mesh.colors) {
+                writeln!(
+                    writer,
+                    "v {:.p$} {:.p$} {:.p$} {:.p$} {:.p$} {:.p$}",
+                    v.0,
+                    v.1,
+                    v.2,
+                    *r as f32 / 255.0,
+                    *g as f32 / 255.0,
+                    *b as f32 / 255.0,
+                    p = decimals
+                )?;
+            }
+        } else {
+            for (i, v) in mesh.vertices.iter().enumerate() {
+                write!(
+                    writer,
+                    "v {:.p$} {:.p$} {:.p$}",
+                    v.0,
+                    v.1,
+                    v.2,
+                    p = decimals
+                )?;
+                // the weight is only written when it differs from the default of 1
+                match mesh.weights.get(i) {
+                    Some(&w) if w != 1.0 => writeln!(writer, " {:.p$}", w, p = decimals)?,
+                    _ => writeln!(writer)?,
+                }
+            }
         }
-        for vt in &mesh.textures {
-            writeln!(writer, "vt {:.6} {:.6}", vt.0, vt.1)?;
+        for (i, vt) in mesh.textures.iter().enumerate() {
+            write!(writer, "vt {:.p$} {:.p$}", vt.0, vt.1, p = decimals)?;
+            match mesh.texture_w.get(i) {
+                Some(&w) if w != 0.0 => writeln!(writer, " {:.p$}", w, p = decimals)?,
+                _ => writeln!(writer)?,
+            }
         }
         for vn in &mesh.normals {
-            writeln!(writer, "vn {:.6} {:.6} {:.6}", vn.0, vn.1, vn.2)?;
+            writeln!(
+                writer,
+                "vn {:.p$} {:.p$} {:.p$}",
+                vn.0,
+                vn.1,
+                vn.2,
+                p = decimals
+            )?;
         }
 
+        // lines and points come before the faces, so they do not end up in a face group
+        for line in &mesh.lines {
+            write!(writer, "l")?;
+            for idx in line {
+                write!(writer, " {}", idx + 1)?;
+            }
+            writeln!(writer)?;
+        }
+        if !mesh.points.is_empty() {
+            write!(writer, "p")?;
+            for idx in &mesh.points {
+                write!(writer, " {}", idx + 1)?;
+            }
+            writeln!(writer)?;
+        }
+
+        // smoothing is off until the first `s` statement
+        let mut current_smoothing_group: Option<u32> = None;
+        // every face is written once, even when group ranges overlap
+        let mut written = vec![false; mesh.faces.len()];
+
         // write faces, grouped by groups
         for group in &mesh.groups {
+            let range = group.face_range.start.min(mesh.faces.len())
+                ..group.face_range.end.min(mesh.faces.len());
             // skip emtpy or default groups created during parsing
-            if group.face_range.start >= group.face_range.end && group.name == "mesh_rs" {
+            if range.is_empty() && group.name == "mesh_rs" {
                 continue;
             }
 
-            writeln!(writer, "g {}", group.name)?;
+            let directive = match group.kind {
+                GroupKind::Object => "o",
+                GroupKind::Group => "g",
+            };
+            writeln!(writer, "{} {}", directive, group.name)?;
 
             if let Some(material) = &group.material {
                 writeln!(writer, "usemtl {}", material)?;
             }
 
-            for i in group.face_range.clone() {
-                if i >= mesh.faces.len() {
-                    break;
+            for i in range {
+                if !std::mem::replace(&mut written[i], true) {
+                    write_face(writer, &mesh.faces[i], &mut current_smoothing_group)?;
                 }
+            }
+        }
+
+        // faces no group covers (e.g., of meshes built in code) go to a default group
+        if written.contains(&false) {
+            writeln!(writer, "g mesh_rs")?;
+            for (face, _) in mesh
+                .faces
+                .iter()
+                .zip(&written)
+                .filter(|(_, written)| !**written)
+            {
+                write_face(writer, face, &mut current_smoothing_group)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MeshCodec for ObjCodec {
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
+        parse_obj(bytes, false)
+    }
+
+    fn write_to(&self, writer: &mut dyn Write, mesh: &Mesh) -> Result<(), MeshError> {
+        self.write_to_with_precision(writer, mesh, DEFAULT_PRECISION)
+    }
+}
+
+// writes an `f` line, preceded by an `s` line when the smoothing group changes
+fn write_face(
+    writer: &mut dyn Write,
+    face: &Face,
+    current_smoothing_group: &mut Option<u32>,
+) -> Result<(), MeshError> {
+    if face.smoothing_group != *current_smoothing_group {
+        match face.smoothing_group {
+            Some(group) => writeln!(writer, "s {}", group)?,
+            None => writeln!(writer, "s off")?,
+        }
+        *current_smoothing_group = face.smoothing_group;
+    }
+
+    write!(writer, "f")?;
 
-                let face = &mesh.faces[i];
-                write!(writer, "f")?;
+    for j in 0..face.v.len() {
+        // write vertex index (1-based)
+        write!(writer, " {}", face.v[j] + 1)?;
 
-                for j in 0..face.v.len() {
-                    // write vertex index (1-based)
-                    write!(writer, " {}", face.v[j] + 1)?;
+        // only the indices this corner has, e.g., `4//5` for a normal without texture
+        let vt = face.vt.get(j).copied().flatten();
+        let vn = face.vn.get(j).copied().flatten();
 
-                    let has_vt = j < face.vt.len();
-                    let has_vn = j < face.vn.len();
+        if vt.is_some() || vn.is_some() {
+            write!(writer, "/")?;
+            if let Some(vt) = vt {
+                write!(writer, "{}", vt + 1)?;
+            }
+        }
 
-                    if has_vt || has_vn {
-                        write!(writer, "/")?;
-                        if has_vt {
-                            write!(writer, "{}", face.vt[j] + 1)?;
+        if let Some(vn) = vn {
+            write!(writer, "/{}", vn + 1)?;
+        }
+    }
+
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn parse_obj(bytes: &[u8], strict: bool) -> Result<Mesh, MeshError> {
+    let mut mesh = Mesh::default();
+    let mut cursor = Cursor::new(bytes);
+    let mut line_buf = String::new();
+    let mut line_number = 0;
+    // line of every face, used to report out-of-range indices in strict mode
+    let mut face_lines: Vec<usize> = Vec::new();
+    // the same for every polyline and point
+    let mut polyline_lines: Vec<usize> = Vec::new();
+    let mut point_lines: Vec<usize> = Vec::new();
+
+    let mut current_name = String::from("mesh_rs");
+    let mut current_kind = GroupKind::Group;
+    let mut current_material: Option<String> = None;
+    let mut current_smoothing_group: Option<u32> = None;
+
+    mesh.groups.push(Group {
+        name: current_name.clone(),
+        kind: current_kind,
+        material: current_material.clone(),
+        face_range: 0..0,
+    });
+
+    while cursor.read_line(&mut line_buf)? > 0 {
+        line_number += 1;
+        let line = line_buf.trim();
+
+        if line.is_empty() {
+            line_buf.clear();
+            continue;
+        }
+
+        if line.starts_with("v ") {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            // extended form written by MeshLab and scanners: v x y z r g b
+            // and the rational form of NURBS exporters: v x y z w
+            let parsed = match parts.len() {
+                7 => parse_floats::<6>(&parts)
+                    .map(|[x, y, z, r, g, b]| (Vec3(x, y, z), Some([r, g, b]), None)),
+                5 => parse_floats::<4>(&parts).map(|[x, y, z, w]| (Vec3(x, y, z), None, Some(w))),
+                _ => parse_floats::<3>(&parts).map(|[x, y, z]| (Vec3(x, y, z), None, None)),
+            };
+
+            match parsed {
+                Ok((vertex, color, weight)) => {
+                    mesh.vertices.push(vertex);
+                    if let Some(color) = color {
+                        // vertices without a color before this one default to white
+                        mesh.colors.resize(mesh.vertices.len() - 1, [255, 255, 255]);
+                        mesh.colors
+                            .push(color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+                    }
+                    if let Some(weight) = weight {
+                        // vertices without a weight before this one default to 1
+                        mesh.weights.resize(mesh.vertices.len() - 1, 1.0);
+                        mesh.weights.push(weight);
+                    }
+                }
+                Err(token) if strict => {
+                    return Err(MeshError::InvalidVertex {
+                        line: line_number,
+                        token: token.to_string(),
+                    });
+                }
+                Err(_) => {}
+            }
+        } else if line.starts_with("vt ") {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match parts.len() {
+                4 => parse_floats::<3>(&parts).map(|[u, v, w]| (Vec2(u, v), Some(w))),
+                _ => parse_floats::<2>(&parts).map(|[u, v]| (Vec2(u, v), None)),
+            };
+            match parsed {
+                Ok((texture, w)) => {
+                    mesh.textures.push(texture);
+                    if let Some(w) = w {
+                        // texture coordinates without a depth before this one default to 0
+                        mesh.texture_w.resize(mesh.textures.len() - 1, 0.0);
+                        mesh.texture_w.push(w);
+                    }
+                }
+                Err(token) if strict => {
+                    return Err(MeshError::InvalidVertex {
+                        line: line_number,
+                        token: token.to_string(),
+                    });
+                }
+                Err(_) => {}
+            }
+        } else if line.starts_with("vn ") {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parse_floats::<3>(&parts) {
+                Ok([x, y, z]) => mesh.normals.push(Vec3(x, y, z)),
+                Err(token) if strict => {
+                    return Err(MeshError::InvalidVertex {
+                        line: line_number,
+                        token: token.to_string(),
+                    });
+                }
+                Err(_) => {}
+            }
+        // Face parsing
+        // v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3 # face with texture and normals
+        // v1//vn1 v2//vn2 v3//vn3 # face with normals only
+        // v1/vt1 v2/vt2 v3/vt3 # face with only texture index
+        } else if line.starts_with("f ") {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let mut face = Face {
+                smoothing_group: current_smoothing_group,
+                ..Face::default()
+            };
+
+            for part in parts.iter().skip(1) {
+                let segemnt: Vec<&str> = part.split('/').collect();
+
+                if let Some(idx) = parse_index(segemnt[0], mesh.vertices.len()) {
+                    face.v.push(idx);
+                } else if strict {
+                    return Err(MeshError::InvalidFace {
+                        line: line_number,
+                        token: part.to_string(),
+                    });
+                } else if segemnt[0].starts_with('-') {
+                    // a relative index before the first vertex is out of range,
+                    // the face is dropped with the other out of range faces below
+                    face.v.push(usize::MAX);
+                } else {
+                    // vertex index is required to process the face
+                    continue;
+                }
+
+                // texture and normal index (optional), recorded for every corner
+                // so corners with and without them can be mixed
+                for (position, count, indices) in [
+                    (1, mesh.textures.len(), &mut face.vt),
+                    (2, mesh.normals.len(), &mut face.vn),
+                ] {
+                    let index = match segemnt.get(position) {
+                        Some(token) if !token.is_empty() => parse_index(token, count),
+                        _ => {
+                            indices.push(None);
+                            continue;
                         }
+                    };
+                    if index.is_none() && strict {
+                        return Err(MeshError::InvalidFace {
+                            line: line_number,
+                            token: part.to_string(),
+                        });
                     }
+                    indices.push(index);
+                }
+            }
+
+            // faces without any texture or normal index keep the arrays empty
+            for indices in [&mut face.vt, &mut face.vn] {
+                if indices.iter().all(Option::is_none) {
+                    indices.clear();
+                }
+            }
 
-                    if has_vn {
-                        write!(writer, "/{}", face.vn[j] + 1)?;
+            mesh.faces.push(face);
+            face_lines.push(line_number);
+        } else if line.starts_with("l ") || line.starts_with("p ") {
+            let mut indices = Vec::new();
+            for part in line.split_whitespace().skip(1) {
+                // polylines may also reference texture coordinates, only the vertex is kept
+                let vertex = part.split('/').next().unwrap_or_default();
+                match parse_index(vertex, mesh.vertices.len()) {
+                    Some(idx) => indices.push(idx),
+                    None if strict => {
+                        return Err(MeshError::InvalidFace {
+                            line: line_number,
+                            token: part.to_string(),
+                        });
                     }
+                    None => {}
                 }
+            }
+
+            if line.starts_with("p ") {
+                point_lines.resize(point_lines.len() + indices.len(), line_number);
+                mesh.points.extend(indices);
+            } else if indices.len() >= 2 {
+                mesh.lines.push(indices);
+                polyline_lines.push(line_number);
+            }
+        } else if let Some(group) = line.strip_prefix("s ") {
+            // `s off` and `s 0` both turn smoothing off
+            current_smoothing_group = group.trim().parse::<u32>().ok().filter(|&g| g > 0);
+        } else if let Some(matlib) = line.strip_prefix("mtllib ") {
+            // exporters sometimes reference the same library more than once
+            let matlib = matlib.trim();
+            if !mesh.matlibs.iter().any(|known| known == matlib) {
+                mesh.matlibs.push(matlib.to_string());
+            }
+        } else if line.starts_with("o ") || line.starts_with("g ") || line.starts_with("usemtl ") {
+            // close the range of the previous group
+            if let Some(last_group) = mesh.groups.last_mut() {
+                last_group.face_range.end = mesh.faces.len();
+            }
 
-                writeln!(writer)?;
+            match line.starts_with("usemtl ") {
+                true => {
+                    current_material = Some(line[7..].trim().to_string());
+                }
+                false => {
+                    // trim the "o " or "g "
+                    current_name = line[2..].trim().to_string();
+                    current_kind = if line.starts_with("o ") {
+                        GroupKind::Object
+                    } else {
+                        GroupKind::Group
+                    };
+                }
             }
+
+            mesh.groups.push(Group {
+                name: current_name.clone(),
+                kind: current_kind,
+                material: current_material.clone(),
+                face_range: mesh.faces.len()..mesh.faces.len(),
+            });
         }
 
-        writer.flush()?;
-        Ok(())
+        line_buf.clear();
+    }
+
+    // close the range of the last group
+    if let Some(last_group) = mesh.groups.last_mut() {
+        last_group.face_range.end = mesh.faces.len();
+    }
+
+    // keep the colors parallel to the vertices when only some of them had one,
+    // the same for the weights and texture depths
+    if !mesh.colors.is_empty() {
+        mesh.colors.resize(mesh.vertices.len(), [255, 255, 255]);
+    }
+    if !mesh.weights.is_empty() {
+        mesh.weights.resize(mesh.vertices.len(), 1.0);
+    }
+    if !mesh.texture_w.is_empty() {
+        mesh.texture_w.resize(mesh.textures.len(), 0.0);
     }
+
+    // OBJ allows forward references, so indices can only be checked
+    // once every vertex has been read
+    let (vertex_count, texture_count, normal_count) =
+        (mesh.vertices.len(), mesh.textures.len(), mesh.normals.len());
+    let out_of_range = |face: &Face| {
+        // corners without a texture or normal index are skipped
+        let find = |indices: &[Option<usize>], count: usize| {
+            indices.iter().flatten().copied().find(|&i| i >= count)
+        };
+        face.v
+            .iter()
+            .copied()
+            .find(|&i| i >= vertex_count)
+            .or_else(|| find(&face.vt, texture_count))
+            .or_else(|| find(&face.vn, normal_count))
+    };
+
+    if strict {
+        for (face, &line) in mesh.faces.iter().zip(&face_lines) {
+            if let Some(idx) = out_of_range(face) {
+                return Err(MeshError::InvalidFace {
+                    line,
+                    token: (idx + 1).to_string(),
+                });
+            }
+        }
+
+        let polylines = mesh.lines.iter().map(|l| l.as_slice()).zip(&polyline_lines);
+        let points = mesh.points.chunks(1).zip(&point_lines);
+        for (indices, &line) in polylines.chain(points) {
+            if let Some(&idx) = indices.iter().find(|&&idx| idx >= vertex_count) {
+                return Err(MeshError::InvalidFace {
+                    line,
+                    token: (idx + 1).to_string(),
+                });
+            }
+        }
+    } else {
+        // drop faces that would reference data that does not exist
+        mesh.retain_faces(|face| out_of_range(face).is_none());
+
+        // and the vertices of lines and points that do not exist
+        mesh.points.retain(|&idx| idx < vertex_count);
+        for line in &mut mesh.lines {
+            line.retain(|&idx| idx < vertex_count);
+        }
+        mesh.lines.retain(|line| line.len() >= 2);
+    }
+
+    Ok(mesh)
+}
+
+// parses the `N` numbers following the line keyword
+// returns the offending token when one of them is missing or not a number
+fn parse_floats<'a, const N: usize>(parts: &[&'a str]) -> Result<[f32; N], &'a str> {
+    let mut values = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        let Some(token) = parts.get(i + 1) else {
+            return Err(parts[0]);
+        };
+        *value = token.parse::<f32>().map_err(|_| *token)?;
+    }
+
+    Ok(values)
+}
+
+// OBJ indices are 1-based, 0 is not a valid index
+// negative indices are relative to the end of the elements read so far
+// e.g., -1 refers to the last vertex
+fn parse_index(token: &str, count: usize) -> Option<usize> {
+    if let Some(relative) = token.strip_prefix('-') {
+        let n = relative.parse::<usize>().ok()?;
+        return (n > 0).then(|| count.checked_sub(n)).flatten();
+    }
+
+    let idx = token.parse::<u32>().ok()?;
+    (idx > 0).then(|| (idx - 1) as usize)
 }
 
 pub fn validate_bytes(bytes: &[u8]) -> bool {
@@ -246,3 +578,255 @@ pub fn validate_bytes(bytes: &[u8]) -> bool {
 
     has_vertices
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn strict_parse_reports_the_line_of_a_bad_vertex() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\n# comment\nv 0 zero 1\nf 1 2 3\n";
+
+        let err = ObjCodec.parse_strict(source.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 5"), "{}", err);
+        assert!(err.to_string().contains("zero"), "{}", err);
+        // the lenient parser skips the line
+        assert_eq!(ObjCodec.parse(source.as_bytes()).unwrap().vertices.len(), 3);
+    }
+
+    #[test]
+    fn strict_parse_reports_the_line_of_an_out_of_range_face() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+
+        let err = ObjCodec.parse_strict(source.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 4"), "{}", err);
+    }
+
+    #[test]
+    fn face_past_the_vertex_count_is_dropped_instead_of_panicking() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 999999 1 2\nf 1 3 2\n";
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(crate::calculate::volume(&mesh), 0.0);
+        assert!(mesh.bounds().is_ok());
+
+        let err = ObjCodec.parse_strict(source.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("999999"), "{}", err);
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_the_elements_read_so_far() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf -3/-3/-1 -2/-2/-1 -1/-1/-1\n";
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.faces.len(), 1);
+        let face = &mesh.faces[0];
+        assert_eq!(face.v.as_slice(), &[0, 1, 2]);
+        assert_eq!(face.vt.as_slice(), &[Some(0), Some(1), Some(2)]);
+        assert_eq!(face.vn.as_slice(), &[Some(0), Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn negative_index_before_the_start_is_rejected() {
+        let source = "v 0 0 0\nv 1 0 0\nf -3 -2 -1\n";
+
+        // the lenient parser drops the face, as it does for indices past the end
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert!(mesh.faces.is_empty());
+        assert!(ObjCodec.parse_strict(source.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn precision_sets_the_decimals_of_every_coordinate() {
+        let mesh = ObjCodec
+            .parse(b"v 0.123456789 1 -2\nvt 0.5 0.25\nf 1 1 1\n")
+            .unwrap();
+        let written = |decimals| {
+            let mut bytes = Vec::new();
+            ObjCodec
+                .write_to_with_precision(&mut bytes, &mesh, decimals)
+                .unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+
+        let three = written(3);
+        assert!(three.contains("v 0.123 1.000 -2.000\n"));
+        assert!(three.contains("vt 0.500 0.250\n"));
+        assert!(written(9).contains("v 0.123456791 1.000000000 -2.000000000\n"));
+        assert_eq!(write(&mesh), written(DEFAULT_PRECISION));
+    }
+
+    #[test]
+    fn vertex_and_texture_weights_round_trip() {
+        let source =
+            "v 0 0 0 0.5\nv 1 0 0\nv 0 1 0 2\nvt 0 0 0.25\nvt 1 0\nvt 0 1\nf 1/1 2/2 3/3\n";
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.weights, [0.5, 1.0, 2.0]);
+        assert_eq!(mesh.texture_w, [0.25, 0.0, 0.0]);
+
+        let written = write(&mesh);
+        // the defaults are left out
+        assert!(written.contains("v 0.000000 0.000000 0.000000 0.500000\n"));
+        assert!(written.contains("v 1.000000 0.000000 0.000000\n"));
+        assert!(written.contains("vt 0.000000 0.000000 0.250000\n"));
+        assert!(written.contains("vt 1.000000 0.000000\n"));
+
+        let parsed = ObjCodec.parse(written.as_bytes()).unwrap();
+        assert_eq!(parsed.weights, mesh.weights);
+        assert_eq!(parsed.texture_w, mesh.texture_w);
+    }
+
+    #[test]
+    fn files_without_weights_store_none() {
+        let mesh = ObjCodec.parse(fixtures::CUBE.as_bytes()).unwrap();
+        assert!(mesh.weights.is_empty());
+        assert!(mesh.texture_w.is_empty());
+    }
+
+    fn write(mesh: &Mesh) -> String {
+        let mut bytes = Vec::new();
+        ObjCodec.write_to(&mut bytes, mesh).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn smoothing_groups_round_trip() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\n\
+            f 1 3 2\ns 1\nf 1 2 4\nf 2 3 4\ns off\nf 1 4 3\ns 2\nf 1 2 3\n";
+        let expected = [None, Some(1), Some(1), None, Some(2)];
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        let groups: Vec<Option<u32>> = mesh.faces.iter().map(|f| f.smoothing_group).collect();
+        assert_eq!(groups, expected);
+
+        let written = write(&mesh);
+        // one `s` line per change, none before the first face since smoothing starts off
+        let statements: Vec<&str> = written.lines().filter(|l| l.starts_with("s ")).collect();
+        assert_eq!(statements, ["s 1", "s off", "s 2"]);
+
+        let parsed = ObjCodec.parse(written.as_bytes()).unwrap();
+        let groups: Vec<Option<u32>> = parsed.faces.iter().map(|f| f.smoothing_group).collect();
+        assert_eq!(groups, expected);
+    }
+
+    #[test]
+    fn vertex_colors_round_trip() {
+        let source = "v 0 0 0 1 0 0\nv 1 0 0 0 1 0\nv 0 1 0 0 0 0.5\nf 1 2 3\n";
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.colors, [[255, 0, 0], [0, 255, 0], [0, 0, 128]]);
+
+        let written = write(&mesh);
+        assert!(written.contains("v 0.000000 0.000000 0.000000 1.000000 0.000000 0.000000"));
+        assert_eq!(
+            ObjCodec.parse(written.as_bytes()).unwrap().colors,
+            mesh.colors
+        );
+    }
+
+    #[test]
+    fn plain_vertices_have_no_colors() {
+        let mesh = ObjCodec
+            .parse(b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+            .unwrap();
+        assert!(mesh.colors.is_empty());
+        assert!(write(&mesh).contains("v 1.000000 0.000000 0.000000\n"));
+    }
+
+    #[test]
+    fn ungrouped_faces_round_trip() {
+        let parsed = ObjCodec.parse(fixtures::CUBE.as_bytes()).unwrap();
+        assert_eq!(round_trip_faces(&parsed), 6);
+
+        // built in code, without any group
+        let mut built = fixtures::cube();
+        built.groups.clear();
+        assert_eq!(round_trip_faces(&built), 6);
+        assert!(write(&built).contains("g mesh_rs\n"));
+    }
+
+    #[test]
+    fn faces_of_overlapping_groups_are_written_once() {
+        let mut mesh = fixtures::cube();
+        let group = |name: &str, face_range| Group {
+            name: name.to_string(),
+            kind: GroupKind::Group,
+            material: None,
+            face_range,
+        };
+        mesh.groups = vec![group("a", 0..4), group("b", 2..6)];
+        assert_eq!(round_trip_faces(&mesh), 6);
+    }
+
+    fn round_trip_faces(mesh: &Mesh) -> usize {
+        fixtures::round_trip(&ObjCodec, mesh).faces.len()
+    }
+
+    #[test]
+    fn lines_and_points_round_trip_without_touching_the_surface() {
+        let source = format!("{}v 2 2 2\nl 1 2 3\nl 5 6\np 9\n", fixtures::CUBE);
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.lines, [vec![0, 1, 2], vec![4, 5]]);
+        assert_eq!(mesh.points, [8]);
+        // only the faces make up the surface
+        assert!(mesh.is_watertight());
+        assert!((crate::calculate::volume(&mesh) - 1.0).abs() < 1e-9);
+
+        let parsed = fixtures::round_trip(&ObjCodec, &mesh);
+        assert_eq!(parsed.lines, mesh.lines);
+        assert_eq!(parsed.points, mesh.points);
+        assert_eq!(parsed.faces.len(), 6);
+    }
+
+    #[test]
+    fn mixed_corner_indices_round_trip() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvn 0 0 1\nvn 0 0 -1\n\
+            f 1 2/1 3//1 4/2/2\n";
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        let face = &mesh.faces[0];
+        assert_eq!(face.vt.as_slice(), &[None, Some(0), None, Some(1)]);
+        assert_eq!(face.vn.as_slice(), &[None, None, Some(0), Some(1)]);
+
+        let written = write(&mesh);
+        assert!(written.contains("f 1 2/1 3//1 4/2/2\n"), "{}", written);
+        let parsed = ObjCodec.parse(written.as_bytes()).unwrap();
+        assert_eq!(parsed.faces, mesh.faces);
+    }
+
+    #[test]
+    fn objects_and_groups_keep_their_keyword() {
+        let source = "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\n\
+            g b\nf 1 2 4\nf 2 3 4\no c\nf 1 4 3\n";
+        let sections = |mesh: &Mesh| -> Vec<(String, GroupKind, std::ops::Range<usize>)> {
+            mesh.groups
+                .iter()
+                .filter(|group| !group.face_range.is_empty())
+                .map(|group| (group.name.clone(), group.kind, group.face_range.clone()))
+                .collect()
+        };
+        let expected = vec![
+            ("a".to_string(), GroupKind::Object, 0..1),
+            ("b".to_string(), GroupKind::Group, 1..3),
+            ("c".to_string(), GroupKind::Object, 3..4),
+        ];
+
+        let mesh = ObjCodec.parse(source.as_bytes()).unwrap();
+        assert_eq!(sections(&mesh), expected);
+
+        let written = write(&mesh);
+        let keywords: Vec<&str> = written
+            .lines()
+            .filter(|line| line.starts_with("o ") || line.starts_with("g "))
+            .collect();
+        assert_eq!(keywords, ["o a", "g b", "o c"]);
+        assert_eq!(
+            sections(&ObjCodec.parse(written.as_bytes()).unwrap()),
+            expected
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// OFF (Geomview Object File Format)
+// line          | description
+// --------------|----------------
+// OFF           | header keyword
+// V F E         | number of vertices, faces and edges (edges are ignored)
+// x y z         | V vertex lines
+// n i0 i1 ...   | F face lines, n 0-based vertex indices (optionally followed by a color)
+// # ...         | comment, may appear anywhere
+use crate::model::{Face, Mesh, MeshCodec, MeshError, Vec3};
+use std::io::Write;
+
+pub struct OffCodec;
+
+impl MeshCodec for OffCodec {
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
+        let content = std::str::from_utf8(bytes)?;
+
+        // (1-based line number, tokens) of every line with content
+        let mut lines = content.lines().enumerate().filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            (!line.is_empty()).then(|| (i + 1, line.split_whitespace().collect::<Vec<_>>()))
+        });
+
+        let (header_line, mut header) = lines.next().ok_or(MeshError::Empty)?;
+        if header.first() != Some(&"OFF") {
+            return Err(MeshError::UnsupportedFormat);
+        }
+
+        // the counts may follow the keyword on the same line
+        header.remove(0);
+        let (counts_line, counts) = if header.is_empty() {
+            lines.next().ok_or(MeshError::Truncated)?
+        } else {
+            (header_line, header)
+        };
+
+        let count = |i: usize| -> Result<usize, MeshError> {
+            let token = counts.get(i).copied().unwrap_or("");
+            token.parse().map_err(|_| MeshError::InvalidVertex {
+                line: counts_line,
+                token: token.to_string(),
+            })
+        };
+        let (vertex_count, face_count) = (count(0)?, count(1)?);
+
+        let mut mesh = Mesh::default();
+        // the declared counts are untrusted, never reserve more than the input can hold
+        mesh.vertices.reserve(vertex_count.min(bytes.len() / 6));
+        mesh.faces.reserve(face_count.min(bytes.len() / 8));
+
+        for _ in 0..vertex_count {
+            let (line, parts) = lines.next().ok_or(MeshError::Truncated)?;
+            let mut coords = [0.0f32; 3];
+            for (i, coord) in coords.iter_mut().enumerate() {
+                let token = parts.get(i).copied().unwrap_or("");
+                *coord = token.parse().map_err(|_| MeshError::InvalidVertex {
+                    line,
+                    token: token.to_string(),
+                })?;
+            }
+            mesh.vertices.push(Vec3(coords[0], coords[1], coords[2]));
+        }
+
+        for _ in 0..face_count {
+            let (line, parts) = lines.next().ok_or(MeshError::Truncated)?;
+            let invalid = |token: &str| MeshError::InvalidFace {
+                line,
+                token: token.to_string(),
+            };
+
+            let n: usize = parts[0].parse().map_err(|_| invalid(parts[0]))?;
+            // a face needs at least 3 vertices, all of them on the line
+            if n < 3 || parts.len() <= n {
+                return Err(invalid(parts[0]));
+            }
+
+            let mut face = Face::default();
+            // anything after the indices is a color, which is ignored
+            for token in &parts[1..=n] {
+                match token.parse::<usize>() {
+                    Ok(idx) if idx < mesh.vertices.len() => face.v.push(idx),
+                    _ => return Err(invalid(token)),
+                }
+            }
+            mesh.faces.push(face);
+        }
+
+        Ok(mesh)
+    }
+
+    fn write_to(&self, writer: &mut dyn Write, mesh: &Mesh) -> Result<(), MeshError> {
+        writeln!(writer, "OFF")?;
+        writeln!(writer, "{} {} 0", mesh.vertices.len(), mesh.faces.len())?;
+
+        for v in &mesh.vertices {
+            writeln!(writer, "{:.6} {:.6} {:.6}", v.0, v.1, v.2)?;
+        }
+
+        for face in &mesh.faces {
+            write!(writer, "{}", face.v.len())?;
+            for idx in &face.v {
+                write!(writer, " {}", idx)?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+// OFF files start with the `OFF` keyword, optionally after comments
+pub fn validate_bytes(bytes: &[u8]) -> bool {
+    let preview = &bytes[..bytes.len().min(4096)];
+    // the preview may end in the middle of a multi byte character
+    String::from_utf8_lossy(preview)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_whitespace().next())
+        == Some("OFF")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn tetrahedron_round_trips() {
+        let tetrahedron = fixtures::obj(fixtures::TETRAHEDRON);
+        let parsed = fixtures::round_trip(&OffCodec, &tetrahedron);
+
+        assert_eq!(parsed.vertices, tetrahedron.vertices);
+        assert_eq!(parsed.faces, tetrahedron.faces);
+    }
+
+    #[test]
+    fn counts_may_follow_the_keyword_and_colors_are_ignored() {
+        let source = "# a triangle\nOFF 3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2 255 0 0\n";
+        let mesh = OffCodec.parse(source.as_bytes()).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.faces[0].v.as_slice(), [0, 1, 2]);
+        assert!(validate_bytes(source.as_bytes()));
+    }
+
+    #[test]
+    fn out_of_range_index_is_rejected() {
+        let source = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+        assert!(matches!(
+            OffCodec.parse(source.as_bytes()),
+            Err(MeshError::InvalidFace { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn huge_vertex_count_of_a_face_is_rejected() {
+        let source = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n18446744073709551615 0 1 2\n";
+        assert!(matches!(
+            OffCodec.parse(source.as_bytes()),
+            Err(MeshError::InvalidFace { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn face_of_two_vertices_is_rejected() {
+        let source = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n2 0 1\n";
+        assert!(matches!(
+            OffCodec.parse(source.as_bytes()),
+            Err(MeshError::InvalidFace { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn missing_vertices_are_truncated() {
+        let source = "OFF\n3 1 0\n0 0 0\n";
+        assert!(matches!(
+            OffCodec.parse(source.as_bytes()),
+            Err(MeshError::Truncated)
+        ));
+    }
+}
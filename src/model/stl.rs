@@ -14,84 +14,335 @@
 // 36-47       | vertex 3 (3 * 4 bytes, (x, y, z))
 // 48-49       | attribute byte count (2 bytes) (usually zero; padding for alignment)
 
-use crate::model::{Face, MAX_TRIANGLES, Mesh, MeshCodec, Vec3};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use crate::{
+    model::{
+        DEFAULT_PRECISION, Face, Group, GroupKind, MAX_TRIANGLES, Mesh, MeshCodec, MeshError,
+        Triangle, Vec3,
+    },
+    ui,
+};
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use rayon::prelude::*;
 use std::{
+    collections::HashMap,
     fs::File,
-    io::{BufWriter, Cursor, Seek, SeekFrom, Write},
+    io::{BufWriter, ErrorKind, Read, Write},
     path::Path,
 };
 
-pub struct StlCodec;
+// stored normals further off the computed normal than this are counted as wrong
+pub const NORMAL_TOLERANCE_DEGREES: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StlCodec {
+    // binary files declaring more triangles than this are rejected
+    pub max_triangles: u32,
+}
+
+impl Default for StlCodec {
+    fn default() -> Self {
+        Self {
+            max_triangles: MAX_TRIANGLES,
+        }
+    }
+}
+
+impl StlCodec {
+    // same as `parse`, but also counts the triangles whose stored normal disagrees with
+    // the normal computed from the winding by more than `NORMAL_TOLERANCE_DEGREES`
+    // zero normals (left for the reader to compute) and degenerate triangles are not counted
+    pub fn parse_checking_normals(&self, bytes: &[u8]) -> Result<(Mesh, usize), MeshError> {
+        let mesh = self.parse(bytes)?;
+        let mismatched = count_wrong_normals(bytes, &mesh)?;
+
+        Ok((mesh, mismatched))
+    }
+
+    // writes the mesh as an ASCII STL instead of the default binary one
+    pub fn write_ascii(&self, path: &Path, mesh: &Mesh) -> Result<(), MeshError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_ascii_to(&mut writer, mesh, DEFAULT_PRECISION)?;
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    // `decimals` is the number of digits after the decimal point of the mantissa
+    pub fn write_ascii_to(
+        &self,
+        writer: &mut dyn Write,
+        mesh: &Mesh,
+        decimals: usize,
+    ) -> Result<(), MeshError> {
+        let name = mesh
+            .header
+            .as_ref()
+            .and_then(header_name)
+            .unwrap_or_else(|| String::from("mesh_rs"));
+        writeln!(writer, "solid {}", name)?;
+
+        let mut degenerate = 0;
+        for triangle in mesh.triangles() {
+            let normal = facet_normal(&triangle, &mut degenerate);
+
+            writeln!(
+                writer,
+                "  facet normal {:.p$e} {:.p$e} {:.p$e}",
+                normal.0,
+                normal.1,
+                normal.2,
+                p = decimals
+            )?;
+            writeln!(writer, "    outer loop")?;
+            for vertex in &triangle.vertices {
+                writeln!(
+                    writer,
+                    "      vertex {:.p$e} {:.p$e} {:.p$e}",
+                    vertex.0,
+                    vertex.1,
+                    vertex.2,
+                    p = decimals
+                )?;
+            }
+            writeln!(writer, "    endloop")?;
+            writeln!(writer, "  endfacet")?;
+        }
+
+        writeln!(writer, "endsolid {}", name)?;
+        warn_degenerate(degenerate);
+
+        Ok(())
+    }
+
+    // parses a binary STL from a reader without buffering the whole file
+    // triangles are read in chunks and vertices are welded as they are read,
+    // so memory only grows with the number of unique vertices
+    pub fn parse_reader(&self, mut reader: impl Read) -> Result<Mesh, MeshError> {
+        // number of triangles (50 bytes each) read per chunk
+        const CHUNK_TRIANGLES: usize = 4096;
+
+        let mut prefix = [0u8; 84];
+        reader.read_exact(&mut prefix)?;
+
+        let mut mesh = Mesh::default();
+        let mut header = [0u8; 80];
+        header.copy_from_slice(&prefix[..80]);
+        mesh.header = Some(header);
+
+        let declared_count = LittleEndian::read_u32(&prefix[80..84]) as usize;
+        let limit = self.max_triangles as usize;
+        if declared_count > limit {
+            return Err(MeshError::InvalidData(format!(
+                "file declares {} triangles, more than the limit of {}",
+                declared_count, limit
+            )));
+        }
+        // some exporters write 0 as the count, read until the end of the input then
+        let mut remaining = if declared_count == 0 {
+            usize::MAX
+        } else {
+            declared_count
+        };
+
+        // using Euler's characteristic, we can estimate the number of unique vertices
+        // as roughly half the number of triangles for a well-formed mesh
+        // the reservations never exceed a single chunk, the vectors grow as triangles are read
+        let estimate = declared_count.min(CHUNK_TRIANGLES);
+        mesh.vertices.reserve(estimate / 2);
+        mesh.faces.reserve(estimate);
+
+        let mut map: HashMap<(u32, u32, u32), usize> = HashMap::with_capacity(estimate / 2);
+        let mut chunk = vec![0u8; CHUNK_TRIANGLES * 50];
+
+        while remaining > 0 {
+            let wanted = remaining.min(CHUNK_TRIANGLES) * 50;
+            let read = read_full(&mut reader, &mut chunk[..wanted])?;
+
+            // a partial triangle at the end of the input is ignored
+            for record in chunk[..read].chunks_exact(50) {
+                // skip normal vector (3 * 4 bytes, (x, y, z))
+                // we can compute it ourselves if needed
+                // in counter part, some exporters write really bad normals
+                let mut face = Face::default();
+
+                for vertex in record[12..48].chunks_exact(12) {
+                    let x = LittleEndian::read_f32(&vertex[0..4]);
+                    let y = LittleEndian::read_f32(&vertex[4..8]);
+                    let z = LittleEndian::read_f32(&vertex[8..12]);
+
+                    let key = (x.to_bits(), y.to_bits(), z.to_bits());
+                    let idx = *map.entry(key).or_insert_with(|| {
+                        mesh.vertices.push(Vec3(x, y, z));
+                        mesh.vertices.len() - 1
+                    });
+                    face.v.push(idx);
+                }
+
+                // the attribute word may hold the color of the face
+                if let Some(color) = decode_color(LittleEndian::read_u16(&record[48..50])) {
+                    // faces without a color before this one default to white
+                    mesh.face_colors.resize(mesh.faces.len(), [255, 255, 255]);
+                    mesh.face_colors.push(color);
+                }
+                mesh.faces.push(face);
+            }
+
+            // files without a count are only bounded by the limit
+            if mesh.faces.len() > limit {
+                return Err(MeshError::InvalidData(format!(
+                    "file has more than the limit of {} triangles",
+                    limit
+                )));
+            }
+
+            if read < wanted {
+                break;
+            }
+            remaining -= read / 50;
+        }
+
+        // keep the colors parallel to the faces when only some of them had one
+        if !mesh.face_colors.is_empty() {
+            mesh.face_colors.resize(mesh.faces.len(), [255, 255, 255]);
+        }
+
+        // a cut off file is still read as far as it goes, but the user should know
+        if declared_count > 0 && mesh.faces.len() < declared_count {
+            ui::print_warn(&format!(
+                "the file declares {} triangles but only {} are complete, it may be truncated.",
+                declared_count,
+                mesh.faces.len()
+            ));
+        }
+
+        Ok(mesh)
+    }
+}
 
 impl MeshCodec for StlCodec {
-    fn parse(&self, bytes: &[u8]) -> anyhow::Result<Mesh> {
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
         if is_ascii(bytes) {
             parse_ascii(bytes)
         } else {
-            parse_binary(bytes)
+            parse_binary(bytes, self)
         }
     }
 
-    fn write(&self, path: &Path, mesh: &Mesh) -> anyhow::Result<()> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-
+    fn write_to(&self, writer: &mut dyn Write, mesh: &Mesh) -> Result<(), MeshError> {
         // write 80 byte header
-        let mut header = [0u8; 80];
-        let signature = b"created by mesh_rs";
-        header[..signature.len()].copy_from_slice(signature);
+        // reuse the header of the source file when there is one
+        let header = mesh.header.unwrap_or_else(|| {
+            let mut header = [0u8; 80];
+            let signature = b"created by mesh_rs";
+            header[..signature.len()].copy_from_slice(signature);
+            header
+        });
         writer.write_all(&header)?;
 
-        let mut triangle_count = 0;
-        // STL only supports triangular faces
-        for face in &mesh.faces {
-            if face.v.len() >= 3 {
-                triangle_count += (face.v.len() - 2) as u32;
+        // STL only supports triangular faces, the count matches what `triangles` yields
+        writer.write_u32::<LittleEndian>(mesh.triangle_count() as u32)?;
+
+        // every triangle of a face gets the color of the face
+        let has_colors = mesh.face_colors.len() == mesh.faces.len();
+
+        // ear clipping, so concave faces are split correctly
+        let mut degenerate = 0;
+        for (face, triangle) in mesh.triangles_with_face_index() {
+            let normal = facet_normal(&triangle, &mut degenerate);
+
+            // write normal
+            writer.write_f32::<LittleEndian>(normal.0)?;
+            writer.write_f32::<LittleEndian>(normal.1)?;
+            writer.write_f32::<LittleEndian>(normal.2)?;
+
+            // write vertices
+            for vertex in &triangle.vertices {
+                writer.write_f32::<LittleEndian>(vertex.0)?;
+                writer.write_f32::<LittleEndian>(vertex.1)?;
+                writer.write_f32::<LittleEndian>(vertex.2)?;
             }
+
+            // write attribute byte count (2 bytes), holding the color when there is one
+            let attribute = if has_colors {
+                encode_color(mesh.face_colors[face])
+            } else {
+                0
+            };
+            writer.write_u16::<LittleEndian>(attribute)?;
         }
-        writer.write_u32::<LittleEndian>(triangle_count)?;
+        warn_degenerate(degenerate);
 
-        for face in &mesh.faces {
-            if face.v.len() < 3 {
-                continue;
-            }
+        Ok(())
+    }
+}
 
-            let v0_idx = face.v[0];
-            let v0 = mesh.vertices[v0_idx];
+// number of triangles of `mesh` whose normal stored in `bytes`, the STL the mesh was
+// parsed from, is wrong as `StlCodec::parse_checking_normals` explains
+// welding keeps the faces in order, so the mesh may be welded
+pub fn count_wrong_normals(bytes: &[u8], mesh: &Mesh) -> Result<usize, MeshError> {
+    let stored = if is_ascii(bytes) {
+        ascii_normals(bytes)?
+    } else {
+        binary_normals(bytes)
+    };
 
-            // Fan triangulation: Connect v0 to v(i) and v(i+1)
-            for i in 1..(face.v.len() - 1) {
-                let v1 = mesh.vertices[face.v[i]];
-                let v2 = mesh.vertices[face.v[i + 1]];
+    let min_dot = NORMAL_TOLERANCE_DEGREES.to_radians().cos();
+    let zero = Vec3(0.0, 0.0, 0.0);
+    Ok(mesh
+        .faces
+        .iter()
+        .zip(stored)
+        .filter(|(face, stored)| {
+            let computed = face.normal(&mesh.vertices);
+            let stored = stored.normalize();
+            computed != zero && stored != zero && computed.dot(stored) < min_dot
+        })
+        .count())
+}
 
-                let a = v1.substraction(v0);
-                let b = v2.substraction(v0);
-                let normal = a.cross(b).normalize();
+// colors in the attribute word, as written by VisCAM and SolidView:
+// bit 15 marks a valid color, bits 10..15 hold red, 5..10 green and 0..5 blue (0..31)
+fn encode_color([r, g, b]: [u8; 3]) -> u16 {
+    let channel = |c: u8| (c as u16 * 31 + 127) / 255;
+    0x8000 | channel(r) << 10 | channel(g) << 5 | channel(b)
+}
 
-                // write normal
-                writer.write_f32::<LittleEndian>(normal.0)?;
-                writer.write_f32::<LittleEndian>(normal.1)?;
-                writer.write_f32::<LittleEndian>(normal.2)?;
+fn decode_color(attribute: u16) -> Option<[u8; 3]> {
+    if attribute & 0x8000 == 0 {
+        return None;
+    }
 
-                // write vertices
-                for vertex in &[v0, v1, v2] {
-                    writer.write_f32::<LittleEndian>(vertex.0)?;
-                    writer.write_f32::<LittleEndian>(vertex.1)?;
-                    writer.write_f32::<LittleEndian>(vertex.2)?;
-                }
+    let channel = |shift: u16| (((attribute >> shift) & 0x1f) * 255 / 31) as u8;
+    Some([channel(10), channel(5), channel(0)])
+}
 
-                // write attribute byte count (2 bytes)
-                writer.write_u16::<LittleEndian>(0)?;
-            }
-        }
+// normal written for a triangle, zero area triangles have no normal and get +Z
+// instead, since some slicers reject (0, 0, 0). `degenerate` counts the fallbacks
+fn facet_normal(triangle: &Triangle, degenerate: &mut usize) -> Vec3 {
+    let normal = triangle.normal();
+    if normal == Vec3(0.0, 0.0, 0.0) {
+        *degenerate += 1;
+        return Vec3(0.0, 0.0, 1.0);
+    }
 
-        writer.flush()?;
-        Ok(())
+    normal
+}
+
+fn warn_degenerate(degenerate: usize) {
+    if degenerate > 0 {
+        ui::print_warn(&format!(
+            "{} zero area triangles were written with a +Z normal.",
+            degenerate
+        ));
     }
 }
 
 pub fn validate_bytes(bytes: &[u8]) -> bool {
+    validate_bytes_with_limit(bytes, MAX_TRIANGLES)
+}
+
+// same as `validate_bytes`, rejecting binary files declaring more than `max_triangles`
+pub fn validate_bytes_with_limit(bytes: &[u8], max_triangles: u32) -> bool {
     if is_ascii(bytes) {
         return true;
     }
@@ -104,7 +355,7 @@ pub fn validate_bytes(bytes: &[u8]) -> bool {
     let triangle_count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
     let data_len = bytes.len() - 84;
 
-    if triangle_count > MAX_TRIANGLES as usize {
+    if triangle_count > max_triangles as usize {
         return false;
     }
 
@@ -118,6 +369,26 @@ pub fn validate_bytes(bytes: &[u8]) -> bool {
     data_len >= expected_min_data
 }
 
+// largest coordinate taken as geometry by `plausible_triangles`
+const PLAUSIBLE_COORDINATE: f32 = 1e12;
+
+// true when the first and the last of the `count` triangles of a binary STL have finite,
+// reasonably sized coordinates. random bytes rarely do, so it separates STL files
+// with trailing junk from other binary files that happen to be large enough
+pub(crate) fn plausible_triangles(bytes: &[u8], count: usize) -> bool {
+    let plausible = |index: usize| {
+        let start = 84 + index * 50;
+        bytes.get(start..start + 48).is_some_and(|record| {
+            record.chunks_exact(4).all(|value| {
+                let value = LittleEndian::read_f32(value);
+                value.is_finite() && value.abs() < PLAUSIBLE_COORDINATE
+            })
+        })
+    };
+
+    count > 0 && plausible(0) && plausible(count - 1)
+}
+
 fn is_ascii(bytes: &[u8]) -> bool {
     // if the file does not start with "solid", it is binary or invalid
     if !bytes.starts_with(b"solid") {
@@ -133,90 +404,581 @@ fn is_ascii(bytes: &[u8]) -> bool {
     }
 }
 
-fn parse_binary(bytes: &[u8]) -> anyhow::Result<Mesh> {
+fn parse_binary(bytes: &[u8], codec: &StlCodec) -> Result<Mesh, MeshError> {
     if bytes.len() < 84 {
-        return Err(anyhow::anyhow!("binary STL file too small"));
+        return Err(MeshError::Truncated);
+    }
+
+    // the declared count is untrusted, a file claiming far more triangles than it
+    // holds is not a cut off download but a broken or malicious file
+    let declared_count = LittleEndian::read_u32(&bytes[80..84]) as usize;
+    let physical_count = (bytes.len() - 84) / 50;
+    if declared_count > physical_count.saturating_mul(2).max(1) {
+        return Err(MeshError::Truncated);
     }
 
-    let mut cursor = Cursor::new(bytes);
+    codec.parse_reader(bytes)
+}
 
-    // Skip 80 byte header
-    cursor.seek(SeekFrom::Start(80))?;
+// fills the buffer from the reader, stopping early only at the end of the input
+// returns the number of bytes read
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, MeshError> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
 
-    let declared_count = cursor.read_u32::<LittleEndian>()? as usize;
-    // the actual number of triangles that can be read from this file
-    let data_len = bytes.len().saturating_sub(84);
-    let physical_count = data_len / 50;
+    Ok(filled)
+}
 
-    let triangle_count = if declared_count == 0 || declared_count > physical_count {
-        physical_count
-    } else {
-        declared_count
-    };
+// lines of an ASCII STL that matter for the geometry
+enum AsciiLine<'a> {
+    Solid(&'a str),
+    EndSolid,
+    Vertex(Vec3),
+    // `endloop` or `endfacet`, closing the current face
+    EndFace,
+}
+
+// the content is split into chunks of whole lines that are parsed in parallel,
+// then the lines are assembled into faces in file order
+fn parse_ascii(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    // bytes per chunk parsed by a single task
+    const CHUNK_SIZE: usize = 1 << 20;
 
-    // seek to the beginning of the triangle data
-    cursor.seek(SeekFrom::Start(84))?;
+    parse_ascii_chunks(bytes, CHUNK_SIZE)
+}
+
+fn parse_ascii_chunks(bytes: &[u8], chunk_size: usize) -> Result<Mesh, MeshError> {
+    let content = std::str::from_utf8(bytes)?;
+    let lines: Vec<AsciiLine> = line_chunks(content, chunk_size)
+        .into_par_iter()
+        // splitting on both characters handles LF, CRLF and old Mac (CR only) line endings
+        .flat_map_iter(|chunk| chunk.split(['\r', '\n']).filter_map(parse_ascii_line))
+        .collect();
 
     let mut mesh = Mesh::default();
-    // using Euler's characteristic, we can estimate the number of unique vertices
-    // as roughly half the number of triangles for a well-formed mesh
-    mesh.vertices.reserve(triangle_count / 2);
-    mesh.faces.reserve(triangle_count);
-
-    for _ in 0..triangle_count {
-        // skip normal vector (3 * 4 bytes, (x, y, z))
-        // we can compute it ourselves if needed
-        // in counter part, some exporters write really bad normals
-        cursor.seek(SeekFrom::Current(12))?;
-
-        let mut face = Face::default();
-
-        for _ in 0..3 {
-            let x = cursor.read_f32::<LittleEndian>()?;
-            let y = cursor.read_f32::<LittleEndian>()?;
-            let z = cursor.read_f32::<LittleEndian>()?;
-
-            mesh.vertices.push(Vec3(x, y, z));
-            face.v.push(mesh.vertices.len() - 1);
+    // every corner of every facet is its own vertex
+    mesh.vertices.reserve(
+        lines
+            .iter()
+            .filter(|line| matches!(line, AsciiLine::Vertex(_)))
+            .count(),
+    );
+
+    let mut face = Face::default();
+    // some files concatenate several solids, every solid becomes a group
+    let mut in_solid = false;
+
+    for line in lines {
+        match line {
+            AsciiLine::EndSolid => {
+                // a facet cut short by the end of its solid is dropped
+                face.v.clear();
+                if let Some(group) = mesh.groups.last_mut()
+                    && in_solid
+                {
+                    group.face_range.end = mesh.faces.len();
+                }
+                in_solid = false;
+            }
+            AsciiLine::Solid(name) => {
+                if mesh.header.is_none() && !name.is_empty() {
+                    // store the solid name the same way a binary header is stored
+                    let mut header = [0u8; 80];
+                    let len = name.len().min(80);
+                    header[..len].copy_from_slice(&name.as_bytes()[..len]);
+                    mesh.header = Some(header);
+                }
+
+                // a solid without `endsolid` ends where the next one starts
+                face.v.clear();
+                if let Some(group) = mesh.groups.last_mut()
+                    && in_solid
+                {
+                    group.face_range.end = mesh.faces.len();
+                }
+                mesh.groups.push(Group {
+                    name: if name.is_empty() { "mesh_rs" } else { name }.to_string(),
+                    kind: GroupKind::Object,
+                    material: None,
+                    face_range: mesh.faces.len()..mesh.faces.len(),
+                });
+                in_solid = true;
+            }
+            AsciiLine::Vertex(vertex) => {
+                mesh.vertices.push(vertex);
+                face.v.push(mesh.vertices.len() - 1);
+            }
+            AsciiLine::EndFace => {
+                if !face.v.is_empty() {
+                    mesh.faces.push(std::mem::take(&mut face));
+                }
+            }
         }
+    }
+
+    // the last solid may be missing its `endsolid`
+    if let Some(group) = mesh.groups.last_mut()
+        && in_solid
+    {
+        group.face_range.end = mesh.faces.len();
+    }
+
+    Ok(mesh)
+}
+
+// None for the lines without geometry (e.g., `facet normal` or `outer loop`),
+// for unknown lines (comments, `color` lines of some exporters) and for malformed
+// vertices, which are skipped. keywords are matched as whole words in any case
+fn parse_ascii_line(line: &str) -> Option<AsciiLine<'_>> {
+    let line = line.trim();
+    let (keyword, rest) = line
+        .split_once(char::is_whitespace)
+        .map_or((line, ""), |(keyword, rest)| (keyword, rest.trim()));
+    let is = |expected: &str| keyword.eq_ignore_ascii_case(expected);
+
+    if is("endsolid") {
+        Some(AsciiLine::EndSolid)
+    } else if is("solid") {
+        Some(AsciiLine::Solid(rest))
+    } else if is("vertex") {
+        // expected format: vertex x y z
+        let mut parts = rest.split_whitespace().map(|part| part.parse::<f32>());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(x)), Some(Ok(y)), Some(Ok(z)), None) => Some(AsciiLine::Vertex(Vec3(x, y, z))),
+            _ => None,
+        }
+    } else if is("endfacet") || is("endloop") {
+        Some(AsciiLine::EndFace)
+    } else {
+        None
+    }
+}
 
-        // skip attribute byte count (2 bytes)
-        cursor.seek(SeekFrom::Current(2))?;
-        mesh.faces.push(face);
+// splits the text into chunks of about `size` bytes, ending at line breaks
+fn line_chunks(content: &str, size: usize) -> Vec<&str> {
+    let mut chunks = Vec::with_capacity(content.len() / size + 1);
+    let mut rest = content;
+
+    while rest.len() > size {
+        let end = match rest.as_bytes()[size..].iter().position(|&b| b == b'\n') {
+            Some(offset) => size + offset + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
     }
 
-    anyhow::Ok(mesh)
+    chunks
 }
 
-fn parse_ascii(bytes: &[u8]) -> anyhow::Result<Mesh> {
+// stored normal of every facet, in file order
+// malformed normals are returned as zero so they stay aligned with the faces
+fn ascii_normals(bytes: &[u8]) -> Result<Vec<Vec3>, MeshError> {
     let content = std::str::from_utf8(bytes)?;
-    let mut mesh = Mesh::default();
 
-    let mut face = Face::default();
+    Ok(content
+        .split(['\r', '\n'])
+        .filter_map(|line| line.trim().strip_prefix("facet normal"))
+        .map(|normal| {
+            let mut parts = normal.split_whitespace().map(|part| part.parse::<f32>());
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(Ok(x)), Some(Ok(y)), Some(Ok(z))) => Vec3(x, y, z),
+                _ => Vec3(0.0, 0.0, 0.0),
+            }
+        })
+        .collect())
+}
 
-    for line in content.lines() {
-        let line = line.trim();
-
-        if line.starts_with("vertex") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            // expected format: vertex x y z
-            if parts.len() == 4
-                && let (Ok(x), Ok(y), Ok(z)) = (
-                    parts[1].parse::<f32>(),
-                    parts[2].parse::<f32>(),
-                    parts[3].parse::<f32>(),
-                )
-            {
-                mesh.vertices.push(Vec3(x, y, z));
-                face.v.push(mesh.vertices.len() - 1);
+// stored normal of every complete triangle record
+fn binary_normals(bytes: &[u8]) -> Vec<Vec3> {
+    bytes
+        .get(84..)
+        .unwrap_or_default()
+        .chunks_exact(50)
+        .map(|record| {
+            Vec3(
+                LittleEndian::read_f32(&record[0..4]),
+                LittleEndian::read_f32(&record[4..8]),
+                LittleEndian::read_f32(&record[8..12]),
+            )
+        })
+        .collect()
+}
+
+// text stored in a header, up to the first NUL byte
+// None when the header does not hold printable text
+fn header_name(header: &[u8; 80]) -> Option<String> {
+    let end = header.iter().position(|&b| b == 0).unwrap_or(header.len());
+    let name = std::str::from_utf8(&header[..end]).ok()?.trim();
+
+    if name.is_empty() || name.chars().any(|c| c.is_control()) {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::{fixtures, model::Format};
+
+    #[test]
+    fn ascii_round_trip_keeps_triangles_and_bounds() {
+        let cube = fixtures::cube();
+        let mut bytes = Vec::new();
+        StlCodec::default()
+            .write_ascii_to(&mut bytes, &cube, 6)
+            .unwrap();
+
+        let text = std::str::from_utf8(&bytes).unwrap();
+        assert!(text.starts_with("solid mesh_rs\n"));
+        assert!(text.contains("vertex 1.000000e0 0.000000e0 0.000000e0"));
+        assert!(text.trim_end().ends_with("endsolid mesh_rs"));
+
+        let parsed = parse_ascii(&bytes).unwrap();
+        assert_eq!(parsed.faces.len(), cube.triangle_count());
+        assert_eq!(parsed.faces.len(), 12);
+        assert_eq!(parsed.bounds().unwrap(), cube.bounds().unwrap());
+    }
+
+    #[test]
+    fn ascii_precision_sets_the_mantissa_decimals() {
+        let mut cube = fixtures::cube();
+        cube.vertices[6] = Vec3(1.0, 1.0, 1.234_567_9);
+        let written = |decimals| {
+            let mut bytes = Vec::new();
+            StlCodec::default()
+                .write_ascii_to(&mut bytes, &cube, decimals)
+                .unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+
+        assert!(written(3).contains("vertex 1.000e0 1.000e0 1.235e0\n"));
+        assert!(written(9).contains("vertex 1.000000000e0 1.000000000e0 1.234567881e0\n"));
+    }
+
+    // binary STL declaring and holding `count` triangles on a small grid of vertices
+    fn binary_stl(count: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes.extend(count.to_le_bytes());
+        for i in 0..count {
+            let (x, y) = ((i % 100) as f32, (i / 100 % 100) as f32);
+            bytes.extend([0u8; 12]);
+            for vertex in [[x, y, 0.0], [x + 1.0, y, 0.0], [x, y + 1.0, 0.0]] {
+                for coordinate in vertex {
+                    bytes.extend(coordinate.to_le_bytes());
+                }
             }
-        } else if (line.starts_with("endfacet") || line.starts_with("endloop"))
-            && !face.v.is_empty()
-        {
-            mesh.faces.push(face);
-            face = Face::default();
+            bytes.extend([0u8; 2]);
         }
+        bytes
+    }
+
+    #[test]
+    fn raised_triangle_limit_accepts_large_files() {
+        let bytes = binary_stl(1_500_000);
+        assert!(StlCodec::default().parse(&bytes).is_err());
+        assert!(!validate_bytes(&bytes));
+        assert_eq!(Format::from_magic_bytes(&bytes), None);
+
+        let codec = StlCodec {
+            max_triangles: 2_000_000,
+        };
+        assert_eq!(codec.parse(&bytes).unwrap().faces.len(), 1_500_000);
+        assert!(validate_bytes_with_limit(&bytes, 2_000_000));
+        assert_eq!(
+            Format::from_magic_bytes_with_limit(&bytes, 2_000_000),
+            Some(Format::STL)
+        );
+    }
+
+    #[test]
+    fn huge_declared_count_fails_without_allocating() {
+        // a bare header claiming about 4 billion triangles
+        let mut bytes = vec![0u8; 80];
+        bytes.extend(4_000_000_000u32.to_le_bytes());
+
+        assert!(matches!(
+            StlCodec::default().parse(&bytes),
+            Err(MeshError::Truncated)
+        ));
+        assert!(StlCodec::default().parse_reader(bytes.as_slice()).is_err());
+        assert!(!validate_bytes(&bytes));
+        assert_eq!(Format::from_magic_bytes(&bytes), None);
     }
 
-    anyhow::Ok(mesh)
+    #[test]
+    fn declared_count_past_twice_the_data_is_truncated() {
+        let mut bytes = binary_stl(10);
+        bytes[80..84].copy_from_slice(&21u32.to_le_bytes());
+        assert!(matches!(
+            StlCodec::default().parse(&bytes),
+            Err(MeshError::Truncated)
+        ));
+
+        // a cut off download still yields the triangles it holds
+        bytes[80..84].copy_from_slice(&20u32.to_le_bytes());
+        assert_eq!(StlCodec::default().parse(&bytes).unwrap().faces.len(), 10);
+    }
+
+    fn facet(normal: &str, vertices: [&str; 3]) -> String {
+        format!(
+            "facet normal {}\nouter loop\nvertex {}\nvertex {}\nvertex {}\nendloop\nendfacet\n",
+            normal, vertices[0], vertices[1], vertices[2]
+        )
+    }
+
+    #[test]
+    fn every_solid_becomes_a_group() {
+        let triangle = facet("0 0 1", ["0 0 0", "1 0 0", "0 1 0"]);
+        let source = format!(
+            "solid base\n{triangle}{triangle}endsolid base\nsolid lid\n{triangle}endsolid lid\n"
+        );
+
+        let mesh = parse_ascii(source.as_bytes()).unwrap();
+        let groups: Vec<(&str, Range<usize>)> = mesh
+            .groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.face_range.clone()))
+            .collect();
+        assert_eq!(groups, [("base", 0..2), ("lid", 2..3)]);
+    }
+
+    #[test]
+    fn inverted_stored_normals_are_counted() {
+        let source = format!(
+            "solid x\n{}{}{}endsolid x\n",
+            facet("0 0 1", ["0 0 0", "1 0 0", "0 1 0"]),
+            facet("0 0 1", ["0 0 1", "0 1 1", "1 0 1"]),
+            // left for the reader to compute
+            facet("0 0 0", ["0 0 2", "0 1 2", "1 0 2"]),
+        );
+
+        let (mesh, mismatched) = StlCodec::default()
+            .parse_checking_normals(source.as_bytes())
+            .unwrap();
+        assert_eq!(mesh.faces.len(), 3);
+        assert_eq!(mismatched, 1);
+
+        let mut cube = fixtures::cube();
+        cube.header = None;
+        let (_, mismatched) = StlCodec::default()
+            .parse_checking_normals(&write(&cube))
+            .unwrap();
+        assert_eq!(mismatched, 0);
+    }
+
+    #[test]
+    fn parallel_ascii_parse_matches_a_single_chunk() {
+        // a large ASCII STL with CRLF line endings, as written on Windows
+        let mut grid = Mesh::new();
+        for i in 0..2000 {
+            grid.merge(&fixtures::box_mesh(
+                Vec3(i as f32, 0.0, 0.0),
+                Vec3(i as f32 + 0.5, 1.0, 1.0),
+            ));
+        }
+        let mut bytes = Vec::new();
+        StlCodec::default()
+            .write_ascii_to(&mut bytes, &grid, 6)
+            .unwrap();
+        let bytes = String::from_utf8(bytes).unwrap().replace('\n', "\r\n");
+
+        let serial = parse_ascii_chunks(bytes.as_bytes(), usize::MAX).unwrap();
+        assert_eq!(serial.faces.len(), 24_000);
+        for chunk_size in [1, 100, 4096] {
+            let parallel = parse_ascii_chunks(bytes.as_bytes(), chunk_size).unwrap();
+            assert_eq!(parallel.vertices, serial.vertices);
+            assert_eq!(parallel.faces, serial.faces);
+        }
+        assert_eq!(
+            parse_ascii(bytes.as_bytes()).unwrap().vertices,
+            serial.vertices
+        );
+    }
+
+    #[test]
+    fn zero_area_triangles_get_a_z_normal() {
+        let mut mesh = fixtures::obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 2 0 0\nf 1 2 3\nf 1 2 4\n");
+        mesh.header = None;
+
+        let bytes = write(&mesh);
+        assert_eq!(LittleEndian::read_u32(&bytes[80..84]), 2);
+        assert_eq!(bytes.len(), 84 + 2 * 50);
+        let normal = |i: usize| {
+            let record = &bytes[84 + i * 50..];
+            [0, 4, 8].map(|offset| LittleEndian::read_f32(&record[offset..]))
+        };
+        assert_eq!(normal(0), [0.0, 0.0, 1.0]);
+        assert_eq!(normal(1), [0.0, 0.0, 1.0]);
+
+        let mut ascii = Vec::new();
+        StlCodec::default()
+            .write_ascii_to(&mut ascii, &mesh, 6)
+            .unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        assert_eq!(
+            ascii
+                .matches("facet normal 0.000000e0 0.000000e0 1.000000e0")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn messy_ascii_lines_are_tolerated() {
+        let source = "solid  messy \r\n\
+            \r\n\
+            ; exported by some tool\r\n\
+            color 0.5 0.5 0.5\r\n\
+            \tfacet normal nan nan nan\r\n\
+            \t  outer loop \r\n\
+            \t    vertex   0 0 0 \r\n\
+            \t    VERTEX 1\t0 0\r\n\
+            \t    vertex 0 1 0\r\n\
+            \t  endloop\r\n\
+            \tendfacet\r\n\
+            \r\n\
+            facet normal 0 0 1\r\nouter loop\r\nvertex 0 0 1\r\nvertex 1 0 1\r\nvertex 0 1 1\r\n\
+            endloop\r\nendfacet\r\n\
+            endsolid messy\r\n";
+
+        let mesh = StlCodec::default().parse(source.as_bytes()).unwrap();
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.vertices[1], Vec3(1.0, 0.0, 0.0));
+        assert_eq!(mesh.groups[0].name, "messy");
+        assert_eq!(mesh.groups[0].face_range, 0..2);
+    }
+
+    fn write(mesh: &Mesh) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        StlCodec::default().write_to(&mut bytes, mesh).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn binary_header_survives_a_round_trip() {
+        let mut header = [0u8; 80];
+        for (i, byte) in header.iter_mut().enumerate() {
+            *byte = (i * 7 + 3) as u8;
+        }
+        let mut cube = fixtures::cube();
+        cube.header = Some(header);
+
+        let written = write(&cube);
+        assert_eq!(written[..80], header);
+        let parsed = StlCodec::default().parse(&written).unwrap();
+        assert_eq!(parsed.header, Some(header));
+        assert_eq!(write(&parsed)[..80], header);
+    }
+
+    #[test]
+    fn default_header_is_the_signature() {
+        let written = write(&fixtures::cube());
+        assert!(written.starts_with(b"created by mesh_rs\0"));
+    }
+
+    #[test]
+    fn ascii_solid_name_is_kept() {
+        let source = b"solid bracket\nfacet normal 0 0 1\nouter loop\n\
+            vertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\nendsolid bracket\n";
+
+        let mesh = StlCodec::default().parse(source).unwrap();
+        assert_eq!(
+            header_name(&mesh.header.unwrap()).as_deref(),
+            Some("bracket")
+        );
+        let mut bytes = Vec::new();
+        StlCodec::default()
+            .write_ascii_to(&mut bytes, &mesh, 6)
+            .unwrap();
+        assert!(bytes.starts_with(b"solid bracket\n"));
+    }
+
+    #[test]
+    fn reader_parses_the_same_mesh_as_the_slice_parser() {
+        let mut cube = fixtures::cube();
+        cube.face_colors = vec![[255, 0, 0]; cube.faces.len()];
+        let bytes = write(&cube);
+
+        let streamed = StlCodec::default()
+            .parse_reader(std::io::Cursor::new(&bytes))
+            .unwrap();
+        let sliced = StlCodec::default().parse(&bytes).unwrap();
+        assert_eq!(streamed.vertices, sliced.vertices);
+        assert_eq!(streamed.faces, sliced.faces);
+        assert_eq!(streamed.face_colors, sliced.face_colors);
+        assert_eq!(streamed.header, sliced.header);
+        // vertices are welded while reading
+        assert_eq!(streamed.vertices.len(), 8);
+        assert_eq!(streamed.faces.len(), 12);
+    }
+
+    #[test]
+    fn face_colors_round_trip_through_the_attribute_word() {
+        let mut mesh = fixtures::obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\n");
+        // values on the 5 bit grid, so they survive exactly
+        mesh.face_colors = vec![[255, 0, 0], [0, 65, 255]];
+
+        let bytes = write(&mesh);
+        // bit 15 set, then 5 bits each of red, green and blue
+        let attribute = |triangle: usize| LittleEndian::read_u16(&bytes[84 + triangle * 50 + 48..]);
+        assert_eq!(attribute(0), 0x8000 | 31 << 10);
+        assert_eq!(attribute(1), 0x8000 | 8 << 5 | 31);
+
+        let parsed = StlCodec::default().parse(&bytes).unwrap();
+        assert_eq!(parsed.face_colors, mesh.face_colors);
+    }
+
+    #[test]
+    fn faces_without_a_color_default_to_white() {
+        let mut mesh = fixtures::obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\n");
+        let mut bytes = write(&mesh);
+        // only the second triangle has a color
+        LittleEndian::write_u16(&mut bytes[84 + 50 + 48..], 0x8000 | 31 << 5);
+
+        mesh = StlCodec::default().parse(&bytes).unwrap();
+        assert_eq!(mesh.face_colors, [[255, 255, 255], [0, 255, 0]]);
+    }
+
+    #[test]
+    fn trailing_zeros_after_the_triangles_are_tolerated() {
+        let mut bytes = binary_stl(10);
+        bytes.extend([0u8; 4096]);
+
+        assert_eq!(Format::from_magic_bytes(&bytes), Some(Format::STL));
+        assert_eq!(StlCodec::default().parse(&bytes).unwrap().faces.len(), 10);
+    }
+
+    #[test]
+    fn large_binary_file_without_geometry_is_not_taken_as_stl() {
+        // a plausible triangle count, followed by NaN coordinates and a long tail
+        let mut bytes = vec![0u8; 80];
+        bytes.extend(10u32.to_le_bytes());
+        bytes.extend([0xff; 500 + 4096]);
+
+        assert_eq!(Format::from_magic_bytes(&bytes), None);
+    }
+
+    #[test]
+    fn file_missing_its_last_bytes_keeps_the_complete_triangles() {
+        let mut bytes = binary_stl(10);
+        bytes.truncate(bytes.len() - 20);
+
+        assert_eq!(StlCodec::default().parse(&bytes).unwrap().faces.len(), 9);
+    }
 }
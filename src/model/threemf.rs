@@ -0,0 +1,434 @@
+// 3MF (3D Manufacturing Format)
+// https://github.com/3MFConsortium/spec_core/blob/master/3MF%20Core%20Specification.md
+//
+// a zip archive (OPC package) with the model stored as XML in `3D/3dmodel.model`:
+// <resources>
+//   <object id="1"><mesh><vertices><vertex x y z/>...</vertices>
+//                        <triangles><triangle v1 v2 v3/>...</triangles></mesh></object>
+//   <object id="2"><components><component objectid="1" transform="..."/></components></object>
+// </resources>
+// <build><item objectid="2" transform="..."/></build>
+//
+// only the geometry of the build items is imported, writing is not supported yet.
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Cursor, Read, Write},
+};
+
+use quick_xml::{Reader, events::Event};
+use zip::ZipArchive;
+
+use crate::model::{Face, MAX_TRIANGLES, Mesh, MeshCodec, MeshError, Vec3};
+
+pub const ZIP_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+// default location of the model part
+const MODEL_PATH: &str = "3D/3dmodel.model";
+const CONTENT_TYPES_PATH: &str = "[Content_Types].xml";
+const MODEL_CONTENT_TYPE: &str = "application/vnd.ms-package.3dmanufacturing-3dmodel+xml";
+
+// components may reference other component objects, deeper nesting is refused
+const MAX_COMPONENT_DEPTH: usize = 32;
+
+// affine transform as stored by 3MF, a 4x3 matrix applied to row vectors:
+// [x' y' z'] = [x y z 1] * M
+#[derive(Debug, Clone, Copy)]
+struct Transform([f32; 12]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+
+    fn apply(&self, v: Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3(
+            v.0 * m[0] + v.1 * m[3] + v.2 * m[6] + m[9],
+            v.0 * m[1] + v.1 * m[4] + v.2 * m[7] + m[10],
+            v.0 * m[2] + v.1 * m[5] + v.2 * m[8] + m[11],
+        )
+    }
+
+    // transform applying `self` first and `then` afterwards
+    fn then(&self, then: &Transform) -> Transform {
+        let (a, b) = (&self.0, &then.0);
+        let mut m = [0.0f32; 12];
+        for row in 0..4 {
+            for col in 0..3 {
+                m[row * 3 + col] =
+                    a[row * 3] * b[col] + a[row * 3 + 1] * b[3 + col] + a[row * 3 + 2] * b[6 + col];
+            }
+            if row == 3 {
+                for col in 0..3 {
+                    m[9 + col] += b[9 + col];
+                }
+            }
+        }
+        Transform(m)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Object {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[usize; 3]>,
+    components: Vec<(u32, Transform)>,
+}
+
+#[derive(Debug, Default)]
+struct Model {
+    objects: HashMap<u32, Object>,
+    // objects to print, with their placement on the build plate
+    items: Vec<(u32, Transform)>,
+}
+
+pub struct ThreeMfCodec;
+
+impl MeshCodec for ThreeMfCodec {
+    fn parse(&self, bytes: &[u8]) -> Result<Mesh, MeshError> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(invalid)?;
+
+        let mut xml = Vec::new();
+        archive
+            .by_name(MODEL_PATH)
+            .map_err(invalid)?
+            .read_to_end(&mut xml)?;
+
+        let Model { objects, items } = parse_model(&xml)?;
+
+        let mut mesh = Mesh::default();
+        // components may place an object many times, the whole model is bounded
+        let mut budget = MAX_TRIANGLES as usize;
+        if items.is_empty() {
+            // files without a build section, import every object as is
+            let mut ids: Vec<&u32> = objects.keys().collect();
+            ids.sort();
+            for id in ids {
+                append_object(
+                    &mut mesh,
+                    &objects,
+                    *id,
+                    Transform::IDENTITY,
+                    &mut HashSet::new(),
+                    &mut budget,
+                )?;
+            }
+        } else {
+            for (id, transform) in items {
+                append_object(
+                    &mut mesh,
+                    &objects,
+                    id,
+                    transform,
+                    &mut HashSet::new(),
+                    &mut budget,
+                )?;
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    fn write_to(&self, _writer: &mut dyn Write, _mesh: &Mesh) -> Result<(), MeshError> {
+        Err(MeshError::UnsupportedFormat)
+    }
+}
+
+// a zip archive is only a 3MF when its content types declare a 3D model part
+pub fn validate_bytes(bytes: &[u8]) -> bool {
+    if !bytes.starts_with(ZIP_MAGIC) {
+        return false;
+    }
+
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(bytes)) else {
+        return false;
+    };
+    let mut content_types = String::new();
+    match archive.by_name(CONTENT_TYPES_PATH) {
+        Ok(mut file) => {
+            file.read_to_string(&mut content_types).is_ok()
+                && content_types.contains(MODEL_CONTENT_TYPE)
+        }
+        Err(_) => false,
+    }
+}
+
+fn invalid(err: impl std::fmt::Display) -> MeshError {
+    MeshError::InvalidData(err.to_string())
+}
+
+fn parse_model(xml: &[u8]) -> Result<Model, MeshError> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+
+    let Model {
+        mut objects,
+        mut items,
+    } = Model::default();
+    let mut current: Option<(u32, Object)> = None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf).map_err(invalid)?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let mut attributes: HashMap<String, String> = HashMap::new();
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(invalid)?;
+                    attributes.insert(
+                        attribute.key.local_name().as_ref().to_string(),
+                        attribute.value.to_string(),
+                    );
+                }
+                let get = |key: &str| attributes.get(key).map(String::as_str);
+
+                match e.local_name().as_ref() {
+                    "object" => {
+                        let id = parse_attribute(get("id"), "object id")?;
+                        if matches!(event, Event::Empty(_)) {
+                            objects.insert(id, Object::default());
+                        } else {
+                            current = Some((id, Object::default()));
+                        }
+                    }
+                    "vertex" => {
+                        if let Some((_, object)) = current.as_mut() {
+                            object.vertices.push(Vec3(
+                                parse_attribute(get("x"), "vertex x")?,
+                                parse_attribute(get("y"), "vertex y")?,
+                                parse_attribute(get("z"), "vertex z")?,
+                            ));
+                        }
+                    }
+                    "triangle" => {
+                        if let Some((_, object)) = current.as_mut() {
+                            object.triangles.push([
+                                parse_attribute(get("v1"), "triangle v1")?,
+                                parse_attribute(get("v2"), "triangle v2")?,
+                                parse_attribute(get("v3"), "triangle v3")?,
+                            ]);
+                        }
+                    }
+                    "component" => {
+                        if let Some((_, object)) = current.as_mut() {
+                            let id = parse_attribute(get("objectid"), "component objectid")?;
+                            object
+                                .components
+                                .push((id, parse_transform(get("transform"))?));
+                        }
+                    }
+                    "item" => {
+                        let id = parse_attribute(get("objectid"), "item objectid")?;
+                        items.push((id, parse_transform(get("transform"))?));
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == "object" => {
+                if let Some((id, object)) = current.take() {
+                    objects.insert(id, object);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(Model { objects, items })
+}
+
+fn parse_attribute<T: std::str::FromStr>(value: Option<&str>, name: &str) -> Result<T, MeshError> {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| MeshError::InvalidData(format!("missing or invalid {}", name)))
+}
+
+fn parse_transform(value: Option<&str>) -> Result<Transform, MeshError> {
+    let Some(value) = value else {
+        return Ok(Transform::IDENTITY);
+    };
+
+    let values: Vec<f32> = value
+        .split_whitespace()
+        .map(|v| {
+            v.parse()
+                .map_err(|_| MeshError::InvalidData(format!("invalid transform '{}'", value)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let matrix: [f32; 12] = values
+        .try_into()
+        .map_err(|_| MeshError::InvalidData(format!("invalid transform '{}'", value)))?;
+    Ok(Transform(matrix))
+}
+
+// appends the object and its components, `path` holds the ids of the objects
+// being appended above this one and `budget` the number of triangles left
+// every placed object takes at least one, so chains of empty objects are bounded too
+fn append_object(
+    mesh: &mut Mesh,
+    objects: &HashMap<u32, Object>,
+    id: u32,
+    transform: Transform,
+    path: &mut HashSet<u32>,
+    budget: &mut usize,
+) -> Result<(), MeshError> {
+    if path.contains(&id) {
+        return Err(MeshError::InvalidData(format!(
+            "component references of object {} form a cycle",
+            id
+        )));
+    }
+    if path.len() > MAX_COMPONENT_DEPTH {
+        return Err(MeshError::InvalidData(format!(
+            "components are nested more than {} levels deep",
+            MAX_COMPONENT_DEPTH
+        )));
+    }
+
+    let object = objects
+        .get(&id)
+        .ok_or_else(|| MeshError::InvalidData(format!("unknown object id {}", id)))?;
+
+    *budget = budget
+        .checked_sub(object.triangles.len().max(1))
+        .ok_or_else(|| {
+            MeshError::InvalidData(format!(
+                "the components place more than {} triangles",
+                MAX_TRIANGLES
+            ))
+        })?;
+
+    let offset = mesh.vertices.len();
+    mesh.vertices
+        .extend(object.vertices.iter().map(|&v| transform.apply(v)));
+    for triangle in &object.triangles {
+        if let Some(idx) = triangle.iter().find(|&&i| i >= object.vertices.len()) {
+            return Err(MeshError::InvalidData(format!(
+                "vertex index {} out of range in object {}",
+                idx, id
+            )));
+        }
+
+        let mut face = Face::default();
+        face.v.extend(triangle.iter().map(|&i| offset + i));
+        mesh.faces.push(face);
+    }
+
+    path.insert(id);
+    for (component, component_transform) in &object.components {
+        append_object(
+            mesh,
+            objects,
+            *component,
+            component_transform.then(&transform),
+            path,
+            budget,
+        )?;
+    }
+    path.remove(&id);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>
+</Types>"#;
+
+    // a tetrahedron printed once as is and once through a component moved along X,
+    // with the second build item lifted along Z
+    const MODEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<model unit="millimeter" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+  <resources>
+    <object id="1" type="model">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/>
+          <vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="1" z="0"/>
+          <vertex x="0" y="0" z="1"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="2" v3="1"/>
+          <triangle v1="0" v2="1" v3="3"/>
+          <triangle v1="0" v2="3" v3="2"/>
+          <triangle v1="1" v2="2" v3="3"/>
+        </triangles>
+      </mesh>
+    </object>
+    <object id="2" type="model">
+      <components>
+        <component objectid="1" transform="1 0 0 0 1 0 0 0 1 10 0 0"/>
+      </components>
+    </object>
+  </resources>
+  <build>
+    <item objectid="1"/>
+    <item objectid="2" transform="1 0 0 0 1 0 0 0 1 0 0 5"/>
+  </build>
+</model>"#;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn build_items_are_placed_with_their_transforms() {
+        let bytes = archive(&[(CONTENT_TYPES_PATH, CONTENT_TYPES), (MODEL_PATH, MODEL)]);
+        assert!(validate_bytes(&bytes));
+
+        let mesh = ThreeMfCodec.parse(&bytes).unwrap();
+        assert_eq!(mesh.triangle_count(), 8);
+        assert_eq!(mesh.vertices[4], Vec3(10.0, 0.0, 5.0));
+        assert_eq!(
+            mesh.bounds().unwrap(),
+            (Vec3(0.0, 0.0, 0.0), Vec3(11.0, 1.0, 6.0))
+        );
+    }
+
+    #[test]
+    fn zip_without_a_model_content_type_is_not_3mf() {
+        let bytes = archive(&[(MODEL_PATH, MODEL)]);
+        assert!(!validate_bytes(&bytes));
+    }
+
+    #[test]
+    fn cyclic_components_are_rejected() {
+        let cyclic = MODEL.replace(r#"<component objectid="1""#, r#"<component objectid="2""#);
+        let bytes = archive(&[(CONTENT_TYPES_PATH, CONTENT_TYPES), (MODEL_PATH, &cyclic)]);
+        assert!(matches!(
+            ThreeMfCodec.parse(&bytes),
+            Err(MeshError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn doubling_component_chain_is_refused() {
+        // every object places the previous one twice, 2^30 tetrahedra in the end
+        let mut chain = String::new();
+        for id in 3..=32 {
+            chain.push_str(&format!(
+                r#"<object id="{id}" type="model"><components><component objectid="{child}"/><component objectid="{child}"/></components></object>"#,
+                child = id - 1
+            ));
+        }
+        let doubling = MODEL
+            .replace("  </resources>", &format!("{}</resources>", chain))
+            .replace(r#"<item objectid="2""#, r#"<item objectid="32""#);
+        let bytes = archive(&[(CONTENT_TYPES_PATH, CONTENT_TYPES), (MODEL_PATH, &doubling)]);
+        let err = ThreeMfCodec.parse(&bytes).unwrap_err();
+        assert!(err.to_string().contains("triangles"), "{}", err);
+    }
+}
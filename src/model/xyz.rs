@@ -0,0 +1,75 @@
+// XYZ point cloud
+// one vertex per line: `x y z`, followed by `nx ny nz` when every vertex has a normal
+// the format has no faces, so it is only used to export the vertices of a mesh
+use crate::model::{Mesh, MeshCodec, MeshError};
+use std::io::Write;
+
+pub struct PointCloudCodec;
+
+impl MeshCodec for PointCloudCodec {
+    fn parse(&self, _bytes: &[u8]) -> Result<Mesh, MeshError> {
+        // a point cloud has no surface to analyze
+        Err(MeshError::UnsupportedFormat)
+    }
+
+    fn write_to(&self, writer: &mut dyn Write, mesh: &Mesh) -> Result<(), MeshError> {
+        if mesh.normals.len() == mesh.vertices.len() {
+            for (v, n) in mesh.vertices.iter().zip(&mesh.normals) {
+                writeln!(
+                    writer,
+                    "{:.6} {:.6} {:.6} {:.6} {:.6} {:.6}",
+                    v.0, v.1, v.2, n.0, n.1, n.2
+                )?;
+            }
+        } else {
+            for v in &mesh.vertices {
+                writeln!(writer, "{:.6} {:.6} {:.6}", v.0, v.1, v.2)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, model::Vec3};
+
+    fn write(mesh: &Mesh) -> String {
+        let mut bytes = Vec::new();
+        PointCloudCodec.write_to(&mut bytes, mesh).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn parse_line(line: &str) -> Vec<f32> {
+        line.split(' ')
+            .map(|value| value.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn cube_writes_one_line_per_vertex() {
+        let cube = fixtures::cube();
+        let written = write(&cube);
+
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 8);
+        for (line, v) in lines.iter().zip(&cube.vertices) {
+            assert_eq!(parse_line(line), [v.0, v.1, v.2]);
+        }
+    }
+
+    #[test]
+    fn normals_are_added_when_every_vertex_has_one() {
+        let mut cube = fixtures::cube();
+        cube.normals = vec![Vec3(0.0, 0.0, 1.0); cube.vertices.len()];
+        assert_eq!(
+            parse_line(write(&cube).lines().nth(1).unwrap()),
+            [1.0, 0.0, 0.0, 0.0, 0.0, 1.0]
+        );
+
+        cube.normals.pop();
+        assert_eq!(parse_line(write(&cube).lines().nth(1).unwrap()).len(), 3);
+    }
+}
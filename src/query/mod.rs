@@ -0,0 +1,490 @@
+// Spatial queries against the surface of a mesh
+// the triangles are stored in a bounding volume hierarchy (BVH), so a query only
+// visits the few triangles near it instead of every triangle of the mesh
+pub mod sdf;
+pub mod slice;
+pub mod voxel;
+
+use rayon::prelude::*;
+
+use crate::{
+    calculate::sample_surface,
+    model::{Mesh, Triangle, Vec3},
+};
+
+// maximum number of triangles in a leaf node
+const LEAF_SIZE: usize = 4;
+
+// seed of the surface samples of `hausdorff_distance`, so the estimate is repeatable
+const HAUSDORFF_SEED: u64 = 0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    // distance along the (unit) ray direction
+    pub distance: f32,
+    // index of the mesh face the hit triangle belongs to
+    pub face: usize,
+    pub triangle: Triangle,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClosestPoint {
+    // the point on the surface nearest to the query point
+    pub point: Vec3,
+    pub distance: f32,
+    // index of the mesh face the point lies on
+    pub face: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    // leaf: the triangles start..start + count
+    // inner node: the left child follows the node, the right child is at `start`
+    start: usize,
+    count: usize,
+}
+
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    // face index of every triangle
+    faces: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn new(mesh: &Mesh) -> Self {
+        let (faces, triangles): (Vec<usize>, Vec<Triangle>) =
+            mesh.triangles_with_face_index().unzip();
+
+        let mut bvh = Self {
+            triangles,
+            faces,
+            nodes: Vec::new(),
+        };
+
+        if !bvh.triangles.is_empty() {
+            let mut order: Vec<usize> = (0..bvh.triangles.len()).collect();
+            let centroids: Vec<Vec3> = bvh.triangles.iter().map(centroid).collect();
+            bvh.build(&mut order, &centroids, 0);
+
+            // store the triangles in leaf order
+            bvh.triangles = order.iter().map(|&i| bvh.triangles[i]).collect();
+            bvh.faces = order.iter().map(|&i| bvh.faces[i]).collect();
+        }
+
+        bvh
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    // recursively splits order[..] at the median centroid of its longest axis
+    // `offset` is the position of order[0] in the final triangle order
+    fn build(&mut self, order: &mut [usize], centroids: &[Vec3], offset: usize) -> usize {
+        let (min, max) = bounds(order.iter().flat_map(|&i| self.triangles[i].vertices));
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            start: offset,
+            count: order.len(),
+        });
+
+        if order.len() <= LEAF_SIZE {
+            return index;
+        }
+
+        let (cmin, cmax) = bounds(order.iter().map(|&i| centroids[i]));
+        let extent = [cmax.0 - cmin.0, cmax.1 - cmin.1, cmax.2 - cmin.2];
+        let axis = (0..3)
+            .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+            .unwrap_or(0);
+
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&a, &b| {
+            axis_of(centroids[a], axis).total_cmp(&axis_of(centroids[b], axis))
+        });
+
+        let (left, right) = order.split_at_mut(mid);
+        self.build(left, centroids, offset);
+        let right_index = self.build(right, centroids, offset + mid);
+
+        self.nodes[index].start = right_index;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    // nearest intersection of the ray with the surface, both sides of a triangle are hit
+    // triangles of the `exclude` face are skipped, e.g., the face the ray starts from
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        exclude: Option<usize>,
+    ) -> Option<RayHit> {
+        let direction = direction.normalize();
+        let inverse = Vec3(1.0 / direction.0, 1.0 / direction.1, 1.0 / direction.2);
+
+        let mut nearest: Option<RayHit> = None;
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = self.nodes[index];
+            let limit = nearest.map_or(f32::INFINITY, |hit| hit.distance);
+            if !ray_hits_box(origin, inverse, node.min, node.max, limit) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(index + 1);
+                stack.push(node.start);
+                continue;
+            }
+
+            for i in node.start..node.start + node.count {
+                if exclude == Some(self.faces[i]) {
+                    continue;
+                }
+
+                if let Some(distance) = ray_triangle(origin, direction, &self.triangles[i])
+                    && distance < limit
+                    && nearest.is_none_or(|hit| distance < hit.distance)
+                {
+                    nearest = Some(RayHit {
+                        distance,
+                        face: self.faces[i],
+                        triangle: self.triangles[i],
+                    });
+                }
+            }
+        }
+
+        nearest
+    }
+
+    // point of the surface nearest to `point`, None for an empty mesh
+    pub fn closest_point(&self, point: Vec3) -> Option<ClosestPoint> {
+        let mut best: Option<(f32, Vec3, usize)> = None;
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = self.nodes[index];
+            let limit = best.map_or(f32::INFINITY, |(distance, _, _)| distance);
+            if box_distance_squared(point, node.min, node.max) > limit {
+                continue;
+            }
+
+            if node.count == 0 {
+                // visit the nearer child first, so the other one is more likely pruned
+                let (left, right) = (self.nodes[index + 1], self.nodes[node.start]);
+                let left_distance = box_distance_squared(point, left.min, left.max);
+                let right_distance = box_distance_squared(point, right.min, right.max);
+                if left_distance < right_distance {
+                    stack.push(node.start);
+                    stack.push(index + 1);
+                } else {
+                    stack.push(index + 1);
+                    stack.push(node.start);
+                }
+                continue;
+            }
+
+            for i in node.start..node.start + node.count {
+                let candidate = closest_point_on_triangle(point, &self.triangles[i]);
+                let offset = point - candidate;
+                let distance = offset.dot(offset);
+                if best.is_none_or(|(best_distance, _, _)| distance < best_distance) {
+                    best = Some((distance, candidate, self.faces[i]));
+                }
+            }
+        }
+
+        best.map(|(distance, point, face)| ClosestPoint {
+            point,
+            distance: distance.sqrt(),
+            face,
+        })
+    }
+
+    // number of times the ray crosses the surface
+    pub fn count_crossings(&self, origin: Vec3, direction: Vec3) -> usize {
+        self.ray_crossings(origin, direction).len()
+    }
+
+    // distances along the (unit) ray direction of every crossing with the surface, sorted
+    // a ray through an edge or vertex hits every triangle sharing it, hits closer together
+    // than `merge_distance` are counted once
+    pub fn ray_crossings_merged(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        merge_distance: f32,
+    ) -> Vec<f32> {
+        let mut crossings = self.ray_crossings(origin, direction);
+        crossings.sort_unstable_by(f32::total_cmp);
+        crossings.dedup_by(|next, previous| *next - *previous <= merge_distance);
+
+        crossings
+    }
+
+    // distances along the (unit) ray direction of every crossing with the surface, unsorted
+    pub fn ray_crossings(&self, origin: Vec3, direction: Vec3) -> Vec<f32> {
+        let direction = direction.normalize();
+        let inverse = Vec3(1.0 / direction.0, 1.0 / direction.1, 1.0 / direction.2);
+
+        let mut crossings = Vec::new();
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = self.nodes[index];
+            if !ray_hits_box(origin, inverse, node.min, node.max, f32::INFINITY) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(index + 1);
+                stack.push(node.start);
+                continue;
+            }
+
+            crossings.extend(
+                self.triangles[node.start..node.start + node.count]
+                    .iter()
+                    .filter_map(|triangle| ray_triangle(origin, direction, triangle)),
+            );
+        }
+
+        crossings
+    }
+
+    // true when the point is inside the closed surface
+    // a ray crossing the surface an odd number of times starts inside, the majority of three
+    // rays is used so a ray grazing an edge or a small hole does not decide alone
+    pub fn contains(&self, point: Vec3) -> bool {
+        // directions off the axes (normalized when cast), so rays do not run along the edges of axis aligned meshes
+        const DIRECTIONS: [Vec3; 3] = [
+            Vec3(0.48, 0.57, 0.67),
+            Vec3(-0.61, 0.69, 0.39),
+            Vec3(0.27, -0.53, -0.8),
+        ];
+
+        DIRECTIONS
+            .iter()
+            .filter(|&&direction| self.count_crossings(point, direction) % 2 == 1)
+            .count()
+            >= 2
+    }
+}
+
+// estimate of the (symmetric) Hausdorff distance between the surfaces of two meshes,
+// the largest distance from a point of one surface to the closest point of the other.
+// `samples` points are picked on each surface, so it can only underestimate the
+// true distance. infinite when only one of the meshes has triangles
+pub fn hausdorff_distance(a: &Mesh, b: &Mesh, samples: usize) -> f32 {
+    let one_sided = |from: &Mesh, to: &Mesh| {
+        let bvh = Bvh::new(to);
+        sample_surface(from, samples, HAUSDORFF_SEED)
+            .par_iter()
+            .map(|&point| {
+                bvh.closest_point(point)
+                    .map_or(f32::INFINITY, |closest| closest.distance)
+            })
+            .reduce(|| 0.0, f32::max)
+    };
+
+    one_sided(a, b).max(one_sided(b, a))
+}
+
+// point of the triangle nearest to `p`
+// https://realtimecollisiondetection.net (Ericson, 5.1.5 closest point on triangle to point)
+pub fn closest_point_on_triangle(p: Vec3, triangle: &Triangle) -> Vec3 {
+    let [a, b, c] = triangle.vertices;
+    let along = |from: Vec3, edge: Vec3, t: f32| {
+        Vec3(
+            from.0 + edge.0 * t,
+            from.1 + edge.1 * t,
+            from.2 + edge.2 * t,
+        )
+    };
+
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return along(a, ab, d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return along(a, ac, d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return along(b, c - b, (d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // inside the face
+    let denominator = va + vb + vc;
+    if denominator == 0.0 {
+        // degenerate triangle, all the edge cases above failed on rounding
+        return a;
+    }
+    let (v, w) = (vb / denominator, vc / denominator);
+    along(along(a, ab, v), ac, w)
+}
+
+// distance along the unit direction to the triangle, None when the ray misses it
+// https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm
+pub fn ray_triangle(origin: Vec3, direction: Vec3, triangle: &Triangle) -> Option<f32> {
+    const EPSILON: f32 = 1e-7;
+
+    let [v0, v1, v2] = triangle.vertices;
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    // the ray is parallel to the triangle
+    if determinant.abs() < EPSILON * edge1.length() * edge2.length() {
+        return None;
+    }
+
+    let inverse = 1.0 / determinant;
+    let s = origin - v0;
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inverse;
+    (t > 0.0).then_some(t)
+}
+
+// slab test, true when the ray enters the box before `limit`
+#[inline]
+fn ray_hits_box(origin: Vec3, inverse: Vec3, min: Vec3, max: Vec3, limit: f32) -> bool {
+    let mut near = 0.0f32;
+    let mut far = limit;
+
+    for axis in 0..3 {
+        let (o, inv) = (axis_of(origin, axis), axis_of(inverse, axis));
+        let t1 = (axis_of(min, axis) - o) * inv;
+        let t2 = (axis_of(max, axis) - o) * inv;
+
+        // NaN (0 * inf) for rays in the plane of a slab keeps the previous bounds
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+
+    near <= far
+}
+
+// squared distance from the point to the box, 0 inside it
+#[inline]
+fn box_distance_squared(p: Vec3, min: Vec3, max: Vec3) -> f32 {
+    let mut distance = 0.0;
+    for axis in 0..3 {
+        let v = axis_of(p, axis);
+        let outside = (axis_of(min, axis) - v)
+            .max(v - axis_of(max, axis))
+            .max(0.0);
+        distance += outside * outside;
+    }
+
+    distance
+}
+
+#[inline]
+fn axis_of(v: Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.0,
+        1 => v.1,
+        _ => v.2,
+    }
+}
+
+#[inline]
+fn centroid(triangle: &Triangle) -> Vec3 {
+    let [a, b, c] = triangle.vertices;
+    Vec3(
+        (a.0 + b.0 + c.0) / 3.0,
+        (a.1 + b.1 + c.1) / 3.0,
+        (a.2 + b.2 + c.2) / 3.0,
+    )
+}
+
+fn bounds(points: impl Iterator<Item = Vec3>) -> (Vec3, Vec3) {
+    let mut min = Vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+    let mut max = Vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for p in points {
+        min = Vec3(min.0.min(p.0), min.1.min(p.1), min.2.min(p.2));
+        max = Vec3(max.0.max(p.0), max.1.max(p.1), max.2.max(p.2));
+    }
+
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate, fixtures};
+
+    #[test]
+    fn mesh_is_at_zero_distance_from_itself() {
+        let cube = fixtures::cube();
+        // the sampled points lie on the surface up to rounding
+        let distance = hausdorff_distance(&cube, &cube, 1_000);
+        assert!(distance < 1e-6, "{}", distance);
+    }
+
+    #[test]
+    fn shifted_cube_is_at_the_distance_of_the_shift() {
+        let cube = fixtures::cube();
+        let mut shifted = cube.clone();
+        calculate::translate(&mut shifted, Vec3(0.0, 0.0, 0.5));
+
+        // every point of the top of the shifted cube is 0.5 above the cube
+        let distance = hausdorff_distance(&cube, &shifted, 1_000);
+        assert!((distance - 0.5).abs() < 1e-4, "{}", distance);
+    }
+}
@@ -0,0 +1,171 @@
+// signed distance field sampled on a regular grid
+// the distance to the surface comes from the closest point query and the sign
+// from the containment test: negative inside the mesh, positive outside
+use std::io::Write;
+
+use rayon::prelude::*;
+
+use crate::{
+    model::{Mesh, MeshError, Vec3},
+    query::Bvh,
+};
+
+// share of the diagonal added around the bounding box,
+// so the outer samples of the grid lie outside the mesh
+const GRID_PADDING: f32 = 0.1;
+
+// refuses grids larger than this, 512³ samples
+pub const MAX_SAMPLES: usize = 1 << 27;
+
+// samples the signed distance on `resolution` points along every axis, covering the
+// padded bounding box of the mesh. returns the samples, x varying fastest
+// (index = x + resolution * (y + resolution * z)), with the min and max grid corners
+pub fn sample_sdf(mesh: &Mesh, resolution: usize) -> Result<(Vec<f32>, Vec3, Vec3), MeshError> {
+    if resolution < 2 {
+        return Err(MeshError::InvalidData(String::from(
+            "the grid needs at least 2 samples per axis",
+        )));
+    }
+    let total = resolution
+        .checked_mul(resolution)
+        .and_then(|n| n.checked_mul(resolution))
+        .filter(|&n| n <= MAX_SAMPLES)
+        .ok_or_else(|| {
+            MeshError::InvalidData(format!(
+                "a resolution of {} needs more than {} samples",
+                resolution, MAX_SAMPLES
+            ))
+        })?;
+
+    let (min, max) = mesh.bounds()?;
+    let padding = (max - min).length() * GRID_PADDING;
+    if padding == 0.0 {
+        return Err(MeshError::ZeroDimensions);
+    }
+    let min = Vec3(min.0 - padding, min.1 - padding, min.2 - padding);
+    let max = Vec3(max.0 + padding, max.1 + padding, max.2 + padding);
+
+    let bvh = Bvh::new(mesh);
+    if bvh.triangle_count() == 0 {
+        return Err(MeshError::Empty);
+    }
+
+    let steps = (resolution - 1) as f32;
+    let step = Vec3(
+        (max.0 - min.0) / steps,
+        (max.1 - min.1) / steps,
+        (max.2 - min.2) / steps,
+    );
+
+    let mut samples = vec![0.0f32; total];
+    samples
+        .par_chunks_mut(resolution * resolution)
+        .enumerate()
+        .for_each(|(z, slice)| {
+            for (i, sample) in slice.iter_mut().enumerate() {
+                let (x, y) = (i % resolution, i / resolution);
+                let point = Vec3(
+                    min.0 + step.0 * x as f32,
+                    min.1 + step.1 * y as f32,
+                    min.2 + step.2 * z as f32,
+                );
+
+                let distance = bvh
+                    .closest_point(point)
+                    .map_or(f32::INFINITY, |closest| closest.distance);
+                *sample = if bvh.contains(point) {
+                    -distance
+                } else {
+                    distance
+                };
+            }
+        });
+
+    Ok((samples, min, max))
+}
+
+// writes the samples as little endian f32, without any header
+pub fn write_raw(writer: &mut dyn Write, samples: &[f32]) -> Result<(), MeshError> {
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+// writes the samples as a NumPy `.npy` array of shape (z, y, x)
+// https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html
+pub fn write_npy(
+    writer: &mut dyn Write,
+    samples: &[f32],
+    resolution: usize,
+) -> Result<(), MeshError> {
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({0}, {0}, {0}), }}",
+        resolution
+    );
+    // magic (6) + version (2) + header length (2) + header, padded to 64 bytes with a newline
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+
+    write_raw(writer, samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn sphere_is_negative_inside_and_positive_at_the_corners() {
+        let sphere = fixtures::uv_sphere(1.0, 16, 32);
+        let resolution = 9;
+        let (samples, min, max) = sample_sdf(&sphere, resolution).unwrap();
+        assert_eq!(samples.len(), resolution * resolution * resolution);
+        assert!(min.0 < -1.0 && max.0 > 1.0);
+
+        let index = |x: usize, y: usize, z: usize| x + resolution * (y + resolution * z);
+        let center = samples[index(4, 4, 4)];
+        assert!(center < -0.95 && center > -1.0, "{center}");
+
+        let last = resolution - 1;
+        for z in [0, last] {
+            for y in [0, last] {
+                for x in [0, last] {
+                    assert!(samples[index(x, y, z)] > 0.5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn npy_header_is_padded_to_64_bytes() {
+        let mut bytes = Vec::new();
+        write_npy(&mut bytes, &[1.0; 8], 2).unwrap();
+
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        assert_eq!(bytes[10 + header_len - 1], b'\n');
+        assert_eq!(bytes.len(), 10 + header_len + 8 * 4);
+    }
+
+    #[test]
+    fn single_sample_grid_is_rejected() {
+        assert!(sample_sdf(&fixtures::cube(), 1).is_err());
+    }
+
+    #[test]
+    fn oversized_grid_is_refused_before_allocating() {
+        for resolution in [513, 100_000, 3_000_000] {
+            assert!(matches!(
+                sample_sdf(&fixtures::cube(), resolution),
+                Err(MeshError::InvalidData(_))
+            ));
+        }
+    }
+}
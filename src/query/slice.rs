@@ -0,0 +1,175 @@
+// contours of a mesh sliced into horizontal layers, e.g. for a slicer preview
+// every layer is cut at its middle, so the planes never coincide with the flat
+// top and bottom faces printed meshes usually have
+use std::io::Write;
+
+use rayon::prelude::*;
+
+use crate::{
+    calculate::section::section_loops,
+    model::{Mesh, MeshError, Vec3},
+};
+
+// height of the cutting plane and the closed contour loops of a layer
+pub type Layer = (f32, Vec<Vec<Vec3>>);
+
+// refuses to cut more layers than this, 1000 m at a micrometer per layer
+pub const MAX_LAYERS: usize = 1_000_000;
+
+// every layer of `layer_height`, from the bottom of the mesh up
+pub fn slice_layers(mesh: &Mesh, layer_height: f32) -> Result<Vec<Layer>, MeshError> {
+    if !(layer_height > 0.0 && layer_height.is_finite()) {
+        return Err(MeshError::InvalidData(format!(
+            "invalid layer height {}",
+            layer_height
+        )));
+    }
+
+    let (min, max) = mesh.bounds()?;
+    let layers = ((max.2 - min.2) / layer_height).ceil().max(1.0);
+    if layers > MAX_LAYERS as f32 {
+        return Err(MeshError::InvalidData(format!(
+            "a layer height of {} needs more than {} layers",
+            layer_height, MAX_LAYERS
+        )));
+    }
+
+    Ok((0..layers as usize)
+        .into_par_iter()
+        .map(|layer| {
+            let z = min.2 + (layer as f32 + 0.5) * layer_height;
+            (z, section_loops(mesh, z))
+        })
+        .collect())
+}
+
+// writes one line per layer (`layer <z> <loops>`) followed by one line per loop
+// listing the X Y coordinates of its points
+pub fn write_polylines(
+    writer: &mut dyn Write,
+    layers: &[Layer],
+    precision: usize,
+) -> Result<(), MeshError> {
+    for (z, loops) in layers {
+        writeln!(writer, "layer {:.*} {}", precision, z, loops.len())?;
+        for contour in loops {
+            let points: Vec<String> = contour
+                .iter()
+                .map(|point| format!("{:.*} {:.*}", precision, point.0, precision, point.1))
+                .collect();
+            writeln!(writer, "{}", points.join(" "))?;
+        }
+    }
+
+    Ok(())
+}
+
+// writes the layers on top of each other as an SVG seen from +Z, one group per layer.
+// SVG runs Y downwards, so Y is flipped to keep the drawing the right way up
+pub fn write_svg(
+    writer: &mut dyn Write,
+    layers: &[Layer],
+    precision: usize,
+) -> Result<(), MeshError> {
+    // 0 - y rather than -y, so points on the X axis are not written as -0
+    let flip = |y: f32| 0.0 - y;
+    let (min, max) = layers
+        .iter()
+        .flat_map(|(_, loops)| loops.iter().flatten())
+        .fold(
+            (
+                (f32::INFINITY, f32::INFINITY),
+                (f32::NEG_INFINITY, f32::NEG_INFINITY),
+            ),
+            |(min, max), point| {
+                (
+                    (min.0.min(point.0), min.1.min(flip(point.1))),
+                    (max.0.max(point.0), max.1.max(flip(point.1))),
+                )
+            },
+        );
+    let (min, max) = if min.0 <= max.0 {
+        (min, max)
+    } else {
+        ((0.0, 0.0), (0.0, 0.0))
+    };
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.*} {:.*} {:.*} {:.*}">"#,
+        precision,
+        min.0,
+        precision,
+        min.1,
+        precision,
+        max.0 - min.0,
+        precision,
+        max.1 - min.1
+    )?;
+    for (z, loops) in layers {
+        writeln!(
+            writer,
+            r#"  <g id="layer-{:.*}" fill="none" stroke="black" stroke-width="0.1%">"#,
+            precision, z
+        )?;
+        for contour in loops {
+            let points: Vec<String> = contour
+                .iter()
+                .map(|point| format!("{:.*},{:.*}", precision, point.0, precision, flip(point.1)))
+                .collect();
+            writeln!(writer, r#"    <polygon points="{}"/>"#, points.join(" "))?;
+        }
+        writeln!(writer, "  </g>")?;
+    }
+    writeln!(writer, "</svg>")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn cube_slices_into_one_square_per_layer() {
+        let layers = slice_layers(&fixtures::cube(), 0.25).unwrap();
+
+        let heights: Vec<f32> = layers.iter().map(|&(z, _)| z).collect();
+        assert_eq!(heights, [0.125, 0.375, 0.625, 0.875]);
+        for (_, loops) in &layers {
+            assert_eq!(loops.len(), 1);
+            // on the outline of the unit square, enclosing all of it
+            let contour = &loops[0];
+            for point in contour {
+                let on_edge = |c: f32| c.abs() < 1e-6 || (c - 1.0).abs() < 1e-6;
+                assert!(on_edge(point.0) || on_edge(point.1), "{:?}", point);
+            }
+            let area: f32 = (0..contour.len())
+                .map(|i| {
+                    let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+                    a.0 * b.1 - b.0 * a.1
+                })
+                .sum::<f32>()
+                / 2.0;
+            assert!((area.abs() - 1.0).abs() < 1e-5, "{}", area);
+        }
+    }
+
+    #[test]
+    fn too_many_layers_are_refused() {
+        let tower = fixtures::box_mesh(Vec3(0.0, 0.0, 0.0), Vec3(1.0, 1.0, 1000.0));
+        assert!(matches!(
+            slice_layers(&tower, 1e-4),
+            Err(MeshError::InvalidData(_))
+        ));
+        assert!(matches!(
+            slice_layers(&tower, 0.0),
+            Err(MeshError::InvalidData(_))
+        ));
+        assert!(matches!(
+            slice_layers(&Mesh::new(), 0.2),
+            Err(MeshError::Empty)
+        ));
+    }
+}
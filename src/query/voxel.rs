@@ -0,0 +1,152 @@
+// solid voxelization into a dense occupancy grid
+// every column of voxels along Z is filled with a single ray: the crossings of the ray
+// with the surface alternate between entering and leaving the solid (the parity test),
+// so a voxel is filled when its center lies between an odd crossing and the next one
+use rayon::prelude::*;
+
+use crate::{
+    model::{Mesh, MeshError, Vec3},
+    query::Bvh,
+};
+
+// refuses grids larger than this, 512³ voxels
+pub const MAX_VOXELS: usize = 1 << 27;
+
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    // minimum corner of the first voxel
+    pub origin: Vec3,
+    // edge length of a voxel
+    pub voxel_size: f32,
+    // number of voxels along X, Y and Z
+    pub dims: [usize; 3],
+    // occupancy, x varying fastest (index = x + dims[0] * (y + dims[1] * z))
+    cells: Vec<bool>,
+}
+
+impl VoxelGrid {
+    pub fn is_filled(&self, x: usize, y: usize, z: usize) -> bool {
+        let [nx, ny, _] = self.dims;
+        self.cells[x + nx * (y + ny * z)]
+    }
+
+    pub fn filled_count(&self) -> usize {
+        self.cells.par_iter().filter(|&&filled| filled).count()
+    }
+
+    // volume of the filled voxels, approaches the mesh volume as the voxels get smaller
+    pub fn volume(&self) -> f64 {
+        let size = self.voxel_size as f64;
+        self.filled_count() as f64 * size * size * size
+    }
+}
+
+// the grid is aligned with the bounding box of the mesh
+// the mesh should be closed, rays leaking through holes fill or empty whole columns
+pub fn voxelize(mesh: &Mesh, voxel_size: f32) -> Result<VoxelGrid, MeshError> {
+    if !(voxel_size > 0.0 && voxel_size.is_finite()) {
+        return Err(MeshError::InvalidData(format!(
+            "invalid voxel size {}",
+            voxel_size
+        )));
+    }
+
+    let (min, max) = mesh.bounds()?;
+    let extents = max - min;
+    let count = |extent: f32| ((extent / voxel_size).ceil() as usize).max(1);
+    let dims = [count(extents.0), count(extents.1), count(extents.2)];
+
+    let total = dims[0]
+        .checked_mul(dims[1])
+        .and_then(|n| n.checked_mul(dims[2]))
+        .filter(|&n| n <= MAX_VOXELS)
+        .ok_or_else(|| {
+            MeshError::InvalidData(format!(
+                "a voxel size of {} needs more than {} voxels",
+                voxel_size, MAX_VOXELS
+            ))
+        })?;
+
+    let bvh = Bvh::new(mesh);
+    let [nx, ny, nz] = dims;
+    // rays start below the mesh, so every crossing lies ahead of them
+    let start_z = min.2 - voxel_size;
+    let merge_distance = extents.length() * 1e-6;
+
+    let mut cells = vec![false; total];
+    // columns are independent, compute the filled ranges of every column in parallel
+    let columns: Vec<Vec<(usize, usize)>> = (0..nx * ny)
+        .into_par_iter()
+        .map(|column| {
+            let (x, y) = (column % nx, column / nx);
+            let origin = Vec3(
+                min.0 + (x as f32 + 0.5) * voxel_size,
+                min.1 + (y as f32 + 0.5) * voxel_size,
+                start_z,
+            );
+
+            bvh.ray_crossings_merged(origin, Vec3(0.0, 0.0, 1.0), merge_distance)
+                .chunks_exact(2)
+                .filter_map(|pair| {
+                    // first and last voxel whose center lies between the two crossings
+                    let enter = start_z + pair[0];
+                    let leave = start_z + pair[1];
+                    let first = ((enter - min.2) / voxel_size - 0.5).ceil().max(0.0) as usize;
+                    let last = ((leave - min.2) / voxel_size - 0.5).floor();
+                    (last >= 0.0 && first <= last as usize)
+                        .then(|| (first, (last as usize).min(nz - 1)))
+                })
+                .collect()
+        })
+        .collect();
+
+    for (column, ranges) in columns.into_iter().enumerate() {
+        for (first, last) in ranges {
+            for z in first..=last {
+                cells[column + nx * ny * z] = true;
+            }
+        }
+    }
+
+    Ok(VoxelGrid {
+        origin: min,
+        voxel_size,
+        dims,
+        cells,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate, fixtures};
+
+    #[test]
+    fn unit_cube_fills_a_thousand_voxels() {
+        let grid = voxelize(&fixtures::cube(), 0.1).unwrap();
+
+        assert_eq!(grid.dims, [10, 10, 10]);
+        assert_eq!(grid.filled_count(), 1000);
+        assert!((grid.volume() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sphere_volume_approaches_the_mesh_volume() {
+        let sphere = fixtures::uv_sphere(1.0, 24, 48);
+        let grid = voxelize(&sphere, 0.05).unwrap();
+
+        let volume = calculate::volume(&sphere);
+        assert!((grid.volume() - volume).abs() / volume < 0.05);
+        // the center is solid, the corners of the bounding box are not
+        let [nx, ny, nz] = grid.dims;
+        assert!(grid.is_filled(nx / 2, ny / 2, nz / 2));
+        assert!(!grid.is_filled(0, 0, 0));
+        assert!(!grid.is_filled(nx - 1, ny - 1, nz - 1));
+    }
+
+    #[test]
+    fn tiny_voxels_are_refused() {
+        assert!(voxelize(&fixtures::cube(), 1e-4).is_err());
+        assert!(voxelize(&fixtures::cube(), 0.0).is_err());
+    }
+}
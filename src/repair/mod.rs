@@ -1,60 +1,78 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::model::{Mesh, Vec3};
+use crate::model::{Face, Mesh, Vec3};
 
 pub fn remove_degenerate_faces(mesh: &mut Mesh) -> usize {
-    let vertices = &mesh.vertices;
-    let before = mesh.faces.len();
+    let degenerate: Vec<bool> = mesh
+        .faces
+        .iter()
+        .map(|face| face_is_degenerate(&mesh.vertices, &face.v))
+        .collect();
 
-    mesh.faces.retain(|face| {
-        let indices = &face.v;
-        if indices.len() < 3 {
-            return false;
-        }
+    let mut degenerate = degenerate.into_iter();
+    mesh.retain_faces(|_| !degenerate.next().unwrap_or(false))
+}
 
-        let v0 = vertices[indices[0]];
-        for i in 1..indices.len() - 1 {
-            let v1 = vertices[indices[i]];
-            let v2 = vertices[indices[i + 1]];
+// number of faces `remove_degenerate_faces` would remove
+pub fn count_degenerate_faces(mesh: &Mesh) -> usize {
+    mesh.faces
+        .iter()
+        .filter(|face| face_is_degenerate(&mesh.vertices, &face.v))
+        .count()
+}
 
-            if !triangle_is_degenerate(v0, v1, v2) {
-                return true;
-            }
-        }
+// a face is degenerate when it has less than 3 vertices or all of its fan triangles have no area
+fn face_is_degenerate(vertices: &[Vec3], indices: &[usize]) -> bool {
+    if indices.len() < 3 {
+        return true;
+    }
+
+    let v0 = vertices[indices[0]];
+    for i in 1..indices.len() - 1 {
+        let v1 = vertices[indices[i]];
+        let v2 = vertices[indices[i + 1]];
 
-        false
-    });
+        if !triangle_is_degenerate(v0, v1, v2) {
+            return false;
+        }
+    }
 
-    before - mesh.faces.len()
+    true
 }
 
 #[inline]
 fn triangle_is_degenerate(v0: Vec3, v1: Vec3, v2: Vec3) -> bool {
     const AREA_EPSILON: f32 = f32::EPSILON * f32::EPSILON;
 
-    let edge1 = v1.substraction(v0);
-    let edge2 = v2.substraction(v0);
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
     let cross = edge1.cross(edge2);
 
     cross.dot(cross) < AREA_EPSILON
 }
 
+// reversed duplicates are removed too, see `Mesh::remove_duplicate_faces`
 pub fn remove_duplicate_faces(mesh: &mut Mesh) -> usize {
-    let before = mesh.faces.len();
-    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    mesh.remove_duplicate_faces(true)
+}
 
-    mesh.faces.retain(|face| {
-        if face.v.len() < 3 {
-            return true;
-        }
+// number of faces `remove_duplicate_faces` would remove
+pub fn count_duplicate_faces(mesh: &Mesh) -> usize {
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
 
-        let mut key: Vec<usize> = face.v.to_vec();
-        key.sort_unstable();
+    mesh.faces
+        .iter()
+        .filter(|face| {
+            if face.v.len() < 3 {
+                return false;
+            }
 
-        seen.insert(key)
-    });
+            let mut key: Vec<usize> = face.v.to_vec();
+            key.sort_unstable();
 
-    before - mesh.faces.len()
+            !seen.insert(key)
+        })
+        .count()
 }
 
 pub fn resolve_non_manifold_edges(mesh: &mut Mesh) -> (usize, usize) {
@@ -96,10 +114,206 @@ pub fn resolve_non_manifold_edges(mesh: &mut Mesh) -> (usize, usize) {
             faces_remapped += 1;
         }
     }
+    mesh.invalidate_face_areas();
 
     (non_manifold_edges.len(), faces_remapped)
 }
 
+// holes with at most this many edges count as small and are filled by `repair`
+pub const MAX_HOLE_EDGES: usize = 32;
+
+// merges vertices closer than `tolerance` to an earlier vertex into that vertex
+// returns the number of vertices removed
+pub fn weld_vertices(mesh: &mut Mesh, tolerance: f32) -> usize {
+    let before = mesh.vertices.len();
+
+    if tolerance > 0.0 && tolerance.is_finite() {
+        // kept vertices by grid cell, a vertex is only compared to the 27 cells around it
+        let cell = |v: Vec3| {
+            (
+                (v.0 / tolerance).floor() as i64,
+                (v.1 / tolerance).floor() as i64,
+                (v.2 / tolerance).floor() as i64,
+            )
+        };
+        let mut grid: HashMap<(i64, i64, i64), Vec<Vec3>> = HashMap::new();
+
+        for vertex in mesh.vertices.iter_mut() {
+            let (x, y, z) = cell(*vertex);
+            let nearby = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                .filter_map(|(dx, dy, dz)| grid.get(&(x + dx, y + dy, z + dz)))
+                .flatten()
+                .find(|kept| (**kept - *vertex).length() <= tolerance)
+                .copied();
+
+            match nearby {
+                // moved onto the kept vertex, so welding merges the two
+                Some(kept) => *vertex = kept,
+                None => grid.entry((x, y, z)).or_default().push(*vertex),
+            }
+        }
+        mesh.invalidate_face_areas();
+    }
+
+    mesh.weld();
+    before - mesh.vertices.len()
+}
+
+// removes the vertices no face, line or point uses, returns the number of vertices removed
+pub fn remove_unused_vertices(mesh: &mut Mesh) -> usize {
+    let mut used = vec![false; mesh.vertices.len()];
+    for face in &mesh.faces {
+        for &idx in &face.v {
+            used[idx] = true;
+        }
+    }
+    for &idx in mesh.lines.iter().flatten().chain(&mesh.points) {
+        used[idx] = true;
+    }
+
+    let before = mesh.vertices.len();
+    if used.iter().all(|&used| used) {
+        return 0;
+    }
+
+    // lookup table: old index -> new index
+    let mut remap: Vec<usize> = vec![0; before];
+    let mut next = 0;
+    for (old_index, &used) in used.iter().enumerate() {
+        if used {
+            remap[old_index] = next;
+            next += 1;
+        }
+    }
+
+    let has_colors = mesh.colors.len() == before;
+    let has_weights = mesh.weights.len() == before;
+    let mut keep = used.iter();
+    mesh.vertices.retain(|_| *keep.next().unwrap_or(&false));
+    if has_colors {
+        let mut keep = used.iter();
+        mesh.colors.retain(|_| *keep.next().unwrap_or(&false));
+    }
+    if has_weights {
+        let mut keep = used.iter();
+        mesh.weights.retain(|_| *keep.next().unwrap_or(&false));
+    }
+    for face in &mut mesh.faces {
+        for idx in face.v.iter_mut() {
+            *idx = remap[*idx];
+        }
+    }
+    mesh.remap_lines_and_points(&remap);
+    mesh.invalidate_face_areas();
+
+    before - mesh.vertices.len()
+}
+
+// makes the winding of neighboring faces agree, so every edge is used once in each direction
+// every connected part follows its first face, then parts enclosing a negative volume are
+// turned inside out so the normals face outwards. non-manifold edges are not followed
+// returns the number of faces reversed
+pub fn orient_faces(mesh: &mut Mesh) -> usize {
+    let edge_faces = mesh.edge_faces();
+    let mut reversed = vec![false; mesh.faces.len()];
+    let mut visited = vec![false; mesh.faces.len()];
+
+    for start in 0..mesh.faces.len() {
+        if visited[start] || mesh.faces[start].v.len() < 3 {
+            continue;
+        }
+        visited[start] = true;
+
+        let mut part = vec![start];
+        let mut queue = vec![start];
+        while let Some(face) = queue.pop() {
+            let n = mesh.faces[face].v.len();
+            for i in 0..n {
+                let (v0, v1) = (mesh.faces[face].v[i], mesh.faces[face].v[(i + 1) % n]);
+                let Some(faces) = edge_faces.get(&canonical_edge(v0, v1)) else {
+                    continue;
+                };
+                if faces.len() != 2 {
+                    continue;
+                }
+
+                let neighbor = if faces[0] == face { faces[1] } else { faces[0] };
+                if visited[neighbor] {
+                    continue;
+                }
+                visited[neighbor] = true;
+
+                // a consistent neighbor runs along the shared edge the other way
+                if uses_directed_edge(&mesh.faces[neighbor].v, v0, v1) {
+                    mesh.faces[neighbor].reverse();
+                    reversed[neighbor] = !reversed[neighbor];
+                }
+                part.push(neighbor);
+                queue.push(neighbor);
+            }
+        }
+
+        let volume: f64 = part
+            .iter()
+            .flat_map(|&face| mesh.faces[face].triangles(&mesh.vertices))
+            .map(|triangle| triangle.signed_volume())
+            .sum();
+        if volume < 0.0 {
+            for &face in &part {
+                mesh.faces[face].reverse();
+                reversed[face] = !reversed[face];
+            }
+        }
+    }
+
+    mesh.invalidate_face_areas();
+
+    reversed.iter().filter(|&&reversed| reversed).count()
+}
+
+// closes the holes with at most `max_edges` edges with a single face each
+// returns the number of holes filled
+pub fn fill_holes(mesh: &mut Mesh, max_edges: usize) -> usize {
+    let holes: Vec<Vec<usize>> = mesh
+        .boundary_loops()
+        .into_iter()
+        .filter(|boundary| boundary.len() >= 3 && boundary.len() <= max_edges)
+        .collect();
+    if holes.is_empty() {
+        return 0;
+    }
+
+    let before = mesh.faces.len();
+    for boundary in &holes {
+        // the rim follows the winding of the faces around it, the new face runs the other way
+        let mut face = Face {
+            v: boundary.iter().copied().collect(),
+            ..Face::default()
+        };
+        face.reverse();
+        mesh.faces.push(face);
+    }
+
+    // the new faces join the group that ends with the mesh, if any
+    if let Some(group) = mesh.groups.last_mut()
+        && group.face_range.end == before
+    {
+        group.face_range.end = mesh.faces.len();
+    }
+    if mesh.face_colors.len() == before {
+        mesh.face_colors.resize(mesh.faces.len(), [255, 255, 255]);
+    }
+    mesh.invalidate_face_areas();
+
+    holes.len()
+}
+
+// true when the polygon goes from `from` straight to `to`
+fn uses_directed_edge(v: &[usize], from: usize, to: usize) -> bool {
+    (0..v.len()).any(|i| v[i] == from && v[(i + 1) % v.len()] == to)
+}
+
 #[inline]
 fn canonical_edge(v1: usize, v2: usize) -> (usize, usize) {
     if v1 < v2 { (v1, v2) } else { (v2, v1) }
@@ -1,46 +1,170 @@
 use colored::*;
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    // only values and errors are printed
+    Quiet,
+    Normal,
+    // additionally prints timings and other details
+    Verbose,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Quiet,
+        1 => Level::Normal,
+        _ => Level::Verbose,
+    }
+}
+
+pub fn is_quiet() -> bool {
+    level() == Level::Quiet
+}
+
+// set when stdout carries data (e.g., a mesh written to `-`),
+// messages are printed to stderr then so they do not corrupt it
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+pub fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if STDOUT_RESERVED.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+// details only shown in verbose mode, printed to stderr
+pub fn print_verbose(label: &str, msg: &str) {
+    if level() < Level::Verbose {
+        return;
+    }
+    eprintln!(
+        "{} {}",
+        format!("[Verbose] {}:", label).magenta().bold(),
+        msg
+    );
+}
 
 pub fn print_error(msg: &str) {
     eprintln!("{} {}", "[Error]".red().bold(), msg);
 }
 
 pub fn print_success(msg: &str) {
-    println!("{} {}", "[Success]".green().bold(), msg);
+    if is_quiet() {
+        return;
+    }
+    out!("{} {}", "[Success]".green().bold(), msg);
 }
 
 pub fn print_warn(msg: &str) {
+    if is_quiet() {
+        return;
+    }
     eprintln!("{} {}", "[Warn]".yellow().bold(), msg);
 }
 
 pub fn print_info(label: &str, msg: &str) {
-    println!("{} {}", format!("[Info] {}:", label).cyan().bold(), msg);
+    if is_quiet() {
+        return;
+    }
+    out!("{} {}", format!("[Info] {}:", label).cyan().bold(), msg);
 }
 
 pub fn print_section(title: &str) {
-    println!("\n{}", title.bold().underline());
+    if is_quiet() {
+        return;
+    }
+    out!("\n{}", title.bold().underline());
 }
 
+// in quiet mode only the value is printed, so scripts can use it directly
 pub fn print_kv<T: Display>(key: &str, value: T) {
-    println!("{:<15} {}", format!("{}:", key).bold(), value);
+    if is_quiet() {
+        out!("{}", value);
+        return;
+    }
+    out!("{}", kv_line(key, value));
+}
+
+fn kv_line<T: Display>(key: &str, value: T) -> String {
+    format!("{:<15} {}", format!("{}:", key).bold(), value)
+}
+
+// width of a full bar printed by `print_bar`, in characters
+const BAR_WIDTH: usize = 40;
+
+// a row of a bar chart: the label, a bar scaled relative to `max` and the value
+// in quiet mode only the label and the value are printed
+pub fn print_bar(label: &str, value: usize, max: usize) {
+    if is_quiet() {
+        out!("{} {}", label, value);
+        return;
+    }
+
+    let filled = if max == 0 {
+        0
+    } else {
+        (value * BAR_WIDTH).div_ceil(max)
+    };
+    out!(
+        "{} {:<width$} {}",
+        label,
+        "#".repeat(filled).cyan(),
+        value,
+        width = BAR_WIDTH
+    );
 }
 
 pub fn print_newline() {
-    println!();
+    if is_quiet() {
+        return;
+    }
+    out!();
 }
 
 pub fn print_plain(msg: &str) {
-    println!("{}", msg);
+    out!("{}", msg);
 }
 
 pub fn print_bold(msg: &str) {
-    println!("{}", msg.bold());
+    out!("{}", msg.bold());
 }
 
 pub fn print_underline(msg: &str) {
-    println!("{}", msg.underline());
+    out!("{}", msg.underline());
 }
 
 pub fn print_italic(msg: &str) {
-    println!("{}", msg.italic());
+    out!("{}", msg.italic());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_colors_leave_plain_strings() {
+        colored::control::set_override(false);
+        assert_eq!(kv_line("Vertices", 8), "Vertices:       8");
+
+        colored::control::set_override(true);
+        assert!(kv_line("Vertices", 8).contains("\x1b["));
+        colored::control::unset_override();
+    }
 }
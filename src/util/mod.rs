@@ -3,7 +3,7 @@ use crate::{model::Mesh, ui};
 pub const MIN_MM_VALUE: f64 = 1.0;
 
 pub fn warn_units(file_name: &str, volume: f64, diagonal: f32) {
-    if volume > MIN_MM_VALUE {
+    if volume > MIN_MM_VALUE || ui::is_quiet() {
         return;
     }
 
@@ -47,9 +47,12 @@ pub fn warn_topology(mesh: &Mesh) {
         ));
         ui::print_warn("this may lead to issues in 3D printing or simulations.");
     } else if boundary_edges_count > 0 {
+        let holes = mesh.hole_count();
         ui::print_newline();
         ui::print_warn(&format!(
-            "the mesh has {} boundary edges.",
+            "the mesh has {} {} ({} boundary edges).",
+            holes,
+            if holes == 1 { "hole" } else { "holes" },
             boundary_edges_count
         ));
         ui::print_warn("this indicates holes in the mesh that may need to be fixed.");
@@ -0,0 +1,650 @@
+// end to end tests running the mesh_rs binary on files in a temporary directory
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+const CUBE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 2 3 7 6
+f 3 4 8 7
+f 4 1 5 8
+";
+
+// writes `contents` to a file unique to the calling test
+fn fixture(name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mesh_rs-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn mesh_rs(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mesh_rs"))
+        .args(args)
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn convert_to_stdout_writes_only_the_mesh() {
+    let cube = fixture("stdout.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "convert", "-"]);
+
+    assert!(output.status.success());
+    let written = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(written.lines().filter(|l| l.starts_with("v ")).count(), 8);
+    assert_eq!(written.lines().filter(|l| l.starts_with("f ")).count(), 6);
+    assert!(!written.contains("[Success]"));
+}
+
+#[test]
+fn no_color_flag_strips_escape_codes() {
+    let cube = fixture("no-color.obj", CUBE);
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh_rs"))
+        .args(["--no-color", cube.to_str().unwrap(), "stats"])
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout).unwrap().contains('\x1b'));
+}
+
+#[test]
+fn quiet_prints_only_the_value() {
+    let cube = fixture("quiet.obj", CUBE);
+    let output = mesh_rs(&["-q", cube.to_str().unwrap(), "volume"]);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1.0000\n");
+}
+
+#[test]
+fn quiet_takes_precedence_over_verbose() {
+    let cube = fixture("quiet-verbose.obj", CUBE);
+    let output = mesh_rs(&["-q", "-v", cube.to_str().unwrap(), "volume"]);
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1.0000\n");
+    assert!(
+        !String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("[Verbose]")
+    );
+}
+
+#[test]
+fn verbose_prints_timings_to_stderr() {
+    let cube = fixture("verbose.obj", CUBE);
+    let output = mesh_rs(&["-v", cube.to_str().unwrap(), "volume"]);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("[Verbose] Parsed:"));
+    assert!(stderr.contains("[Verbose] Computed:"));
+}
+
+#[test]
+fn timings_print_every_phase() {
+    let cube = fixture("timings.obj", CUBE);
+    let output = mesh_rs(&["--timings", cube.to_str().unwrap(), "volume"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for phase in ["Parse time", "Weld time", "Compute time"] {
+        assert!(
+            stdout.contains(&format!("[Info] {}: ", phase)),
+            "{}",
+            stdout
+        );
+    }
+}
+
+#[test]
+fn validate_accepts_a_closed_cube() {
+    let cube = fixture("valid.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "validate"]);
+
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn validate_exits_with_3_on_an_open_mesh() {
+    // the cube without its top face
+    let open = CUBE.replace("f 5 6 7 8\n", "");
+    let open = fixture("open.obj", open);
+    let output = mesh_rs(&[open.to_str().unwrap(), "validate"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("holes"));
+}
+
+#[test]
+fn validate_exits_with_2_on_non_manifold_edges() {
+    // a fin sharing the bottom front edge with two cube faces
+    let fin = format!("{CUBE}v 0.5 -1 -1\nf 1 2 9\n");
+    let fin = fixture("non-manifold.obj", fin);
+    let output = mesh_rs(&[fin.to_str().unwrap(), "validate"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn validate_exits_with_1_on_a_malformed_file() {
+    // a binary STL header without the triangle count
+    let truncated = fixture("truncated.stl", "x".repeat(40));
+    let output = mesh_rs(&[truncated.to_str().unwrap(), "validate"]);
+
+    assert_eq!(output.status.code(), Some(1));
+}
+
+// the cube with every coordinate of 1 replaced by `size`
+fn cube_of(size: &str) -> String {
+    CUBE.lines()
+        .map(|line| match line.strip_prefix("v ") {
+            Some(coordinates) => format!("v {}\n", coordinates.replace('1', size)),
+            None => format!("{line}\n"),
+        })
+        .collect()
+}
+
+#[test]
+fn strict_validate_fails_on_warnings() {
+    // a closed cube, too small to be in millimeters
+    let tiny = cube_of("0.5");
+    let tiny = fixture("tiny.obj", tiny);
+    let path = tiny.to_str().unwrap();
+
+    assert_eq!(mesh_rs(&[path, "validate"]).status.code(), Some(0));
+    assert_eq!(
+        mesh_rs(&[path, "validate", "--strict"]).status.code(),
+        Some(5)
+    );
+}
+
+#[test]
+fn stats_reports_the_vertices_merged_by_welding() {
+    // a binary STL of the cube, storing the 3 corners of each of its 12 triangles
+    let cube = fixture("stl-source.obj", CUBE);
+    let stl = cube.with_extension("stl");
+    let output = mesh_rs(&[cube.to_str().unwrap(), "convert", stl.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let output = mesh_rs(&[stl.to_str().unwrap(), "stats"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Vertices:       8\n"));
+    assert!(stdout.contains("Duplicates:     28 of 36 stored vertices\n"));
+}
+
+#[test]
+fn signed_volume_of_an_inverted_cube_is_negative() {
+    let inverted: String = CUBE
+        .lines()
+        .map(|line| match line.strip_prefix("f ") {
+            Some(face) => {
+                let mut corners: Vec<&str> = face.split(' ').collect();
+                corners.reverse();
+                format!("f {}\n", corners.join(" "))
+            }
+            None => format!("{line}\n"),
+        })
+        .collect();
+    let inverted = fixture("inverted.obj", inverted);
+    let path = inverted.to_str().unwrap();
+
+    let signed = mesh_rs(&["-q", path, "volume", "--signed"]);
+    assert_eq!(String::from_utf8(signed.stdout).unwrap(), "-1.0000\n");
+    let absolute = mesh_rs(&["-q", path, "volume"]);
+    assert_eq!(String::from_utf8(absolute.stdout).unwrap(), "1.0000\n");
+
+    let stats = mesh_rs(&[path, "stats"]);
+    assert!(
+        String::from_utf8(stats.stderr)
+            .unwrap()
+            .contains("inverted normals")
+    );
+}
+
+#[test]
+fn volume_of_an_open_mesh_is_marked_approximate() {
+    let open = fixture("open-volume.obj", CUBE.replace("f 5 6 7 8\n", ""));
+    let output = mesh_rs(&[open.to_str().unwrap(), "volume"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Volume (approximate, mesh has holes)"));
+}
+
+#[test]
+fn height_counts_the_layers() {
+    let tower = fixture("tower.obj", cube_of("20"));
+    let output = mesh_rs(&["-q", tower.to_str().unwrap(), "height", "0.2"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["0.0000", "20.0000", "20.0000", "100"]
+    );
+}
+
+#[test]
+fn header_only_stl_prints_zero_stats() {
+    // an 80 byte header and a triangle count of 0
+    let empty = fixture("header-only.stl", "\0".repeat(84));
+    let output = mesh_rs(&[empty.to_str().unwrap(), "stats"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Triangles:      0\n"), "{}", stdout);
+    assert!(stdout.contains("Volume:         0.0000\n"), "{}", stdout);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the mesh is empty"), "{}", stderr);
+}
+
+#[test]
+fn zero_byte_file_is_rejected_as_empty() {
+    let empty = fixture("empty.stl", "");
+    let output = mesh_rs(&[empty.to_str().unwrap(), "stats"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("mesh has no vertices"), "{}", stderr);
+}
+
+// the unit cube without its top, with a reversed side, a duplicated side,
+// a degenerate face and an unused vertex
+const BROKEN_CUBE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+v 5 5 5
+f 1 4 3 2
+f 1 2 6 5
+f 6 7 3 2
+f 3 4 8 7
+f 4 1 5 8
+f 4 1 5 8
+f 1 2 1
+";
+
+#[test]
+fn repair_makes_a_broken_cube_watertight() {
+    let broken = fixture("broken.obj", BROKEN_CUBE);
+    let repaired = broken.with_file_name("broken_fixed.obj");
+    let output = mesh_rs(&[
+        broken.to_str().unwrap(),
+        "repair",
+        "-o",
+        repaired.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for step in [
+        "Degenerate faces: 1",
+        "Duplicate faces: 1",
+        "Unused vertices: 1",
+        "Reversed faces: 1",
+        "Filled holes:   1",
+        "The repaired mesh is watertight.",
+    ] {
+        assert!(stdout.contains(step), "{}", stdout);
+    }
+
+    let volume = mesh_rs(&["-q", repaired.to_str().unwrap(), "volume"]);
+    assert_eq!(String::from_utf8(volume.stdout).unwrap(), "1.0000\n");
+}
+
+#[test]
+fn repair_steps_can_be_skipped() {
+    let broken = fixture("broken-skip.obj", BROKEN_CUBE);
+    let repaired = broken.with_file_name("broken-skip_fixed.obj");
+    let output = mesh_rs(&[
+        broken.to_str().unwrap(),
+        "repair",
+        "--no-fill-holes",
+        "-o",
+        repaired.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success());
+    assert!(
+        !String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("Filled holes")
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("still not watertight (4 boundary edges"),
+        "{}",
+        stderr
+    );
+}
+
+// number of corners of every `f` line of the OBJ
+fn face_sizes(obj: &str) -> Vec<usize> {
+    obj.lines()
+        .filter(|line| line.starts_with("f "))
+        .map(|line| line.split_whitespace().count() - 1)
+        .collect()
+}
+
+#[test]
+fn convert_keeps_quads_unless_triangulating() {
+    let cube = fixture("quads.obj", CUBE);
+
+    let kept = mesh_rs(&[cube.to_str().unwrap(), "convert", "-"]);
+    assert_eq!(face_sizes(&String::from_utf8(kept.stdout).unwrap()), [4; 6]);
+
+    let split = mesh_rs(&[cube.to_str().unwrap(), "convert", "-", "--triangulate"]);
+    assert_eq!(
+        face_sizes(&String::from_utf8(split.stdout).unwrap()),
+        [3; 12]
+    );
+}
+
+#[test]
+fn diff_of_a_mesh_with_itself_reports_zero() {
+    let cube = fixture("diff.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "diff", cube.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Bounds:         0.000000\n"), "{}", stdout);
+    assert!(stdout.contains("Hausdorff:      0.000000\n"), "{}", stdout);
+}
+
+#[test]
+fn diff_of_different_meshes_fails() {
+    let cube = fixture("diff-small.obj", CUBE);
+    let large = fixture("diff-large.obj", cube_of("2"));
+    let output = mesh_rs(&[cube.to_str().unwrap(), "diff", large.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("the meshes differ (bounds, volume, surfaces)"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn inch_cube_converted_to_millimeters_scales_by_25_4() {
+    let cube = fixture("inch.obj", CUBE);
+    let output = mesh_rs(&[
+        "--units",
+        "in",
+        "--to-units",
+        "mm",
+        cube.to_str().unwrap(),
+        "diagonal",
+    ]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // 25.4 * sqrt(3)
+    assert!(
+        stdout.contains("[Info] Units: in -> mm (x25.4)\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Diagonal:       43.9941\n"), "{}", stdout);
+}
+
+#[test]
+fn unknown_unit_is_rejected() {
+    let cube = fixture("feet.obj", CUBE);
+    let output = mesh_rs(&["--units", "ft", cube.to_str().unwrap(), "diagonal"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[possible values: mm, cm, m, in]"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn quads_converted_to_stl_are_split_into_triangles() {
+    let cube = fixture("quad-cube.obj", CUBE);
+    let stl = cube.with_file_name("quad-cube.stl");
+    let output = mesh_rs(&[cube.to_str().unwrap(), "convert", stl.to_str().unwrap()]);
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("6 faces have more than 3 vertices"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("split with ear clipping"), "{}", stderr);
+
+    // the triangle count follows the 80 byte header
+    let bytes = fs::read(&stl).unwrap();
+    assert_eq!(u32::from_le_bytes(bytes[80..84].try_into().unwrap()), 12);
+    let volume = mesh_rs(&["-q", stl.to_str().unwrap(), "volume"]);
+    assert_eq!(String::from_utf8(volume.stdout).unwrap(), "1.0000\n");
+}
+
+#[test]
+fn fan_splitting_of_a_concave_polygon_covers_too_much() {
+    // an L of area 3, the fan around the first corner leaves the polygon
+    let polygon = fixture(
+        "l-shape.obj",
+        "v 0 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\nv 1 2 0\nv 0 2 0\nf 2 3 4 5 6 1\n",
+    );
+    let footprint = |fan: bool| {
+        let stl = polygon.with_file_name(format!("l-shape-{}.stl", fan));
+        let mut args = vec![polygon.to_str().unwrap(), "convert", stl.to_str().unwrap()];
+        if fan {
+            args.insert(0, "--fan");
+        }
+        assert!(mesh_rs(&args).status.success());
+        let output = mesh_rs(&[stl.to_str().unwrap(), "footprint"]);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(footprint(false), "Footprint area: 3.0000\n");
+    assert_eq!(footprint(true), "Footprint area: 3.2500\n");
+}
+
+#[test]
+fn truncated_binary_stl_warns_and_keeps_the_complete_triangles() {
+    // 3 triangles declared, the last one cut off 20 bytes before its end
+    let mut bytes = vec![0u8; 80];
+    bytes.extend(3u32.to_le_bytes());
+    for i in 0..3 {
+        bytes.extend([0u8; 12]);
+        for vertex in [
+            [0.0, 0.0, i as f32],
+            [1.0, 0.0, i as f32],
+            [0.0, 1.0, i as f32],
+        ] {
+            for coordinate in vertex {
+                bytes.extend(f32::to_le_bytes(coordinate));
+            }
+        }
+        bytes.extend([0u8; 2]);
+    }
+    bytes.truncate(bytes.len() - 20);
+    let stl = fixture("truncated.stl", bytes);
+    let output = mesh_rs(&[stl.to_str().unwrap(), "stats"]);
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("the file declares 3 triangles but only 2 are complete"),
+        "{}",
+        stderr
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Triangles:      2\n"), "{}", stdout);
+}
+
+#[test]
+fn format_flag_forces_obj_on_a_file_detected_as_stl() {
+    // an STL name and an ASCII STL start, but OBJ statements
+    let odd = fixture("odd.stl", format!("solid facet and vertex data\n{CUBE}"));
+
+    let detected = mesh_rs(&[odd.to_str().unwrap(), "stats"]);
+    assert!(
+        String::from_utf8(detected.stdout)
+            .unwrap()
+            .contains("Format:         STL\n")
+    );
+
+    let output = mesh_rs(&["-v", "--format", "obj", odd.to_str().unwrap(), "stats"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Format:         OBJ\n"), "{}", stdout);
+    assert!(stdout.contains("Triangles:      12\n"), "{}", stdout);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("reading as OBJ as requested, the content looks like STL"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn format_flag_reads_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mesh_rs"))
+        .args(["-q", "--format", "obj", "-", "volume"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(CUBE.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1.0000\n");
+}
+
+#[test]
+fn check_normals_reads_stdin() {
+    // the corner tetrahedron, the stored normal of the slanted face points down
+    let stl = "\
+solid tetrahedron
+facet normal 0 0 -1\nouter loop\nvertex 0 0 0\nvertex 0 1 0\nvertex 1 0 0\nendloop\nendfacet
+facet normal 0 -1 0\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 0 1\nendloop\nendfacet
+facet normal -1 0 0\nouter loop\nvertex 0 0 0\nvertex 0 0 1\nvertex 0 1 0\nendloop\nendfacet
+facet normal 0 0 -1\nouter loop\nvertex 1 0 0\nvertex 0 1 0\nvertex 0 0 1\nendloop\nendfacet
+endsolid tetrahedron
+";
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mesh_rs"))
+        .args(["-", "stats", "--check-normals"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stl.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Wrong Normals:  1\n"), "{}", stdout);
+}
+
+#[test]
+fn normals_audit_reports_the_wrong_normals() {
+    // a square facing +Z, two of its four stored normals point elsewhere
+    let square = fixture(
+        "normals.obj",
+        "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0 1\nvn 1 0 0\nvn 0 0 -1\nf 1//1 2//1 3//2 4//3\n",
+    );
+    let output = mesh_rs(&[square.to_str().unwrap(), "normals-audit"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("50.0% (2 of 4)"), "{}", stdout);
+    assert!(stdout.contains("180.00°"), "{}", stdout);
+
+    let cube = fixture("no-normals.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "normals-audit"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "no stored normals\n"
+    );
+}
+
+#[test]
+fn slicing_into_too_many_layers_fails() {
+    let cube = fixture("thin-layers.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "slice", "0.0000001", "-"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("needs more than 1000000 layers"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn components_count_solid_and_open_shells() {
+    let cube = fixture("one-shell.obj", CUBE);
+    let output = mesh_rs(&[cube.to_str().unwrap(), "components"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 component: 1 solid, 0 open\n"
+    );
+
+    // a second cube next to the first, without its top
+    let mut two = CUBE.to_string();
+    for line in CUBE.lines() {
+        let mut words = line.split(' ');
+        match words.next() {
+            Some("v") => {
+                let x: f32 = words.next().unwrap().parse().unwrap();
+                let rest: Vec<&str> = words.collect();
+                two += &format!("v {} {}\n", x + 3.0, rest.join(" "));
+            }
+            Some("f") if line != "f 5 6 7 8" => {
+                let indices: Vec<String> = words
+                    .map(|i| (i.parse::<usize>().unwrap() + 8).to_string())
+                    .collect();
+                two += &format!("f {}\n", indices.join(" "));
+            }
+            _ => {}
+        }
+    }
+    let shells = fixture("two-shells.obj", two);
+    let output = mesh_rs(&[shells.to_str().unwrap(), "components"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2 components: 1 solid, 1 open\n"
+    );
+}